@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
 
 pub const HEADER_AUTH: &str = "authorization";
+/// Headers of webhook requests, see `signing::webhook_signature`
+pub const HEADER_WEBHOOK_EVENT: &str = "x-tama-event";
+pub const HEADER_WEBHOOK_DELIVERY: &str = "x-tama-delivery";
+pub const HEADER_WEBHOOK_SIGNATURE: &str = "x-tama-signature";
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct RegisterRequest {
@@ -14,13 +18,68 @@ pub struct LoginRequest {
     pub password: String,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct CreateContentRequest {
     pub channel_id: i64,
     pub name: String,
     pub art: String,
     pub midi: String,
     pub fps: f32,
+    #[serde(default)]
+    pub visibility: Visibility,
+    /// Unix timestamp before which the content stays hidden, `None` publishes right away
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publish_at: Option<i64>,
+    /// Base64 signature of `signing::content_digest` with the channel's registered key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+/// Where a mirrored content was first published
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ContentProvenance {
+    pub server_url: String,
+    /// Id of the content on `server_url`
+    pub content_id: i64,
+}
+
+/// Registers the key future uploads of the channel are signed with
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RegisterKeyRequest {
+    /// SPKI PEM of an RSA key, at least `signing::KEY_BITS` bits
+    pub public_key: String,
+}
+
+/// Who can see a content
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    /// Listed in `/feed` and on the channel
+    #[default]
+    Public,
+    /// Reachable by id or share token, never listed
+    Unlisted,
+    /// Only visible to the owning channel
+    Private,
+}
+
+impl Visibility {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Visibility::Public => "public",
+            Visibility::Unlisted => "unlisted",
+            Visibility::Private => "private",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "public" => Some(Visibility::Public),
+            "unlisted" => Some(Visibility::Unlisted),
+            "private" => Some(Visibility::Private),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -34,6 +93,250 @@ pub struct AuthResponse {
     pub token: String,
     pub expires_at: i64,
     pub channel: ChannelInfo,
+    /// Acting user, absent when logged in with the channel password
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<UserInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<Role>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct UserInfo {
+    pub id: i64,
+    pub name: String,
+}
+
+/// What a member can do on a channel
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Everything, including managing members and the profile
+    Owner,
+    /// Uploads contents
+    Editor,
+    /// Sees unlisted, private and scheduled contents
+    Viewer,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Owner => "owner",
+            Role::Editor => "editor",
+            Role::Viewer => "viewer",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "owner" => Some(Role::Owner),
+            "editor" => Some(Role::Editor),
+            "viewer" => Some(Role::Viewer),
+            _ => None,
+        }
+    }
+
+    pub fn can_edit(&self) -> bool {
+        matches!(self, Role::Owner | Role::Editor)
+    }
+
+    pub fn can_manage(&self) -> bool {
+        matches!(self, Role::Owner)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UserRegisterRequest {
+    pub user_name: String,
+    pub password: String,
+}
+
+/// Logs a user in to one of the channels they are a member of
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UserLoginRequest {
+    pub user_name: String,
+    pub password: String,
+    pub channel_name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CreateInviteRequest {
+    pub role: Role,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct InviteResponse {
+    pub token: String,
+    pub channel: ChannelInfo,
+    pub role: Role,
+    pub expires_at: i64,
+}
+
+/// Accepting an invite also logs the user in to the channel
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AcceptInviteRequest {
+    pub user_name: String,
+    pub password: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MemberInfo {
+    pub user: UserInfo,
+    pub role: Role,
+    pub joined_at: i64,
+}
+
+/// Authentication events recorded for a channel
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthEventKind {
+    Register,
+    LoginSuccess,
+    LoginFailure,
+    /// Login refused without checking the password because the channel is locked out
+    LockedOut,
+    /// Token handed to a member without a login, when they accept an invite
+    TokenIssued,
+}
+
+impl AuthEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuthEventKind::Register => "register",
+            AuthEventKind::LoginSuccess => "login_success",
+            AuthEventKind::LoginFailure => "login_failure",
+            AuthEventKind::LockedOut => "locked_out",
+            AuthEventKind::TokenIssued => "token_issued",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "register" => Some(AuthEventKind::Register),
+            "login_success" => Some(AuthEventKind::LoginSuccess),
+            "login_failure" => Some(AuthEventKind::LoginFailure),
+            "locked_out" => Some(AuthEventKind::LockedOut),
+            "token_issued" => Some(AuthEventKind::TokenIssued),
+            _ => None,
+        }
+    }
+}
+
+/// Entry of `GET /channel/me/security-log`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SecurityEvent {
+    pub kind: AuthEventKind,
+    pub created_at: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip: Option<String>,
+    /// Member the event is about, absent for channel password logins
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_name: Option<String>,
+}
+
+/// Events a webhook can subscribe to
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    /// A content was uploaded to the channel, whatever its visibility
+    ContentCreated,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::ContentCreated => "content_created",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "content_created" => Some(WebhookEvent::ContentCreated),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    /// Key of the `X-Tama-Signature` HMAC, never sent back
+    pub secret: String,
+    pub events: Vec<WebhookEvent>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct WebhookInfo {
+    pub id: i64,
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    pub created_at: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    /// Not delivered yet, more attempts to come
+    Pending,
+    Succeeded,
+    /// Gave up, either out of attempts or refused by the receiver
+    Failed,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Pending => "pending",
+            DeliveryStatus::Succeeded => "succeeded",
+            DeliveryStatus::Failed => "failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(DeliveryStatus::Pending),
+            "succeeded" => Some(DeliveryStatus::Succeeded),
+            "failed" => Some(DeliveryStatus::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// Entry of `GET /channel/me/webhooks/deliveries`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub webhook_id: i64,
+    pub url: String,
+    pub event: WebhookEvent,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    /// HTTP status of the last attempt, if the receiver answered
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_status: Option<u16>,
+    /// Why the last attempt failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CreateCommentRequest {
+    pub body: String,
+}
+
+/// Entry of `GET /content/:id/comments`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CommentInfo {
+    pub id: i64,
+    pub content_id: i64,
+    /// Channel the author was logged in to
+    pub channel_id: i64,
+    /// Member name for member logins, channel name otherwise
+    pub author: String,
+    pub body: String,
+    pub created_at: i64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -41,4 +344,39 @@ pub struct CreateContentResponse {
     pub id: i64,
     pub channel_id: i64,
     pub message: String,
+    /// Token for `/share/<token>`, only issued for unlisted contents
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub share_token: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ChannelProfile {
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub bio: Option<String>,
+    /// Single-frame braille sheet, `Ascii Art Animation, WxH` header included
+    #[serde(default)]
+    pub avatar: Option<String>,
+    #[serde(default)]
+    pub links: Vec<String>,
+}
+
+impl ChannelProfile {
+    pub fn is_empty(&self) -> bool {
+        self.display_name.is_none() && self.bio.is_none() && self.avatar.is_none() && self.links.is_empty()
+    }
+}
+
+/// Fields left out are kept as they are, empty strings clear them
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct UpdateProfileRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bio: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avatar: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub links: Option<Vec<String>>,
 }
@@ -1,10 +1,9 @@
 use image::{DynamicImage, GenericImageView, ImageBuffer, Luma, Rgba};
 
-pub const TV_WIDTH: usize = 32;
-pub const TV_HEIGHT: usize = 10;
-pub const PIXEL_WIDTH: usize = TV_WIDTH * 2;
-pub const PIXEL_HEIGHT: usize = TV_HEIGHT * 4;
+use super::{PIXEL_HEIGHT, PIXEL_WIDTH};
+
 const TARGET_ASPECT_RATIO: f32 = PIXEL_WIDTH as f32 / PIXEL_HEIGHT as f32;
+const DOT_VALUES: [u32; 8] = [0x01, 0x08, 0x02, 0x10, 0x04, 0x20, 0x40, 0x80];
 
 pub struct ImageConverter;
 
@@ -144,8 +143,6 @@ impl ImageConverter {
     fn dots_to_braille(dots: [bool; 8]) -> char {
         let mut code: u32 = 0x2800;
 
-        const DOT_VALUES: [u32; 8] = [0x01, 0x08, 0x02, 0x10, 0x04, 0x20, 0x40, 0x80];
-
         for (i, &dot) in dots.iter().enumerate() {
             if dot {
                 code |= DOT_VALUES[i];
@@ -154,6 +151,43 @@ impl ImageConverter {
 
         char::from_u32(code).unwrap_or('⠀')
     }
+
+    /// Reverse of `image_to_braille_string`: raised dots become black pixels, everything
+    /// else (blank cells, spaces, non-braille characters) stays white.
+    pub fn braille_string_to_image(art: &str, char_width: usize, char_height: usize) -> ImageBuffer<Luma<u8>, Vec<u8>> {
+        let mut img = ImageBuffer::from_pixel((char_width * 2) as u32, (char_height * 4) as u32, Luma([255u8]));
+
+        for (char_y, line) in art.lines().take(char_height).enumerate() {
+            for (char_x, c) in line.chars().take(char_width).enumerate() {
+                let dots = Self::braille_to_dots(c);
+
+                for dy in 0..4 {
+                    for dx in 0..2 {
+                        if dots[dy * 2 + dx] {
+                            img.put_pixel((char_x * 2 + dx) as u32, (char_y * 4 + dy) as u32, Luma([0u8]));
+                        }
+                    }
+                }
+            }
+        }
+
+        img
+    }
+
+    fn braille_to_dots(c: char) -> [bool; 8] {
+        let mut dots = [false; 8];
+        let code = c as u32;
+
+        if !(0x2800..=0x28FF).contains(&code) {
+            return dots;
+        }
+
+        for (i, value) in DOT_VALUES.iter().enumerate() {
+            dots[i] = code & value != 0;
+        }
+
+        dots
+    }
 }
 
 #[cfg(test)]
@@ -174,6 +208,21 @@ mod tests {
         assert_eq!(result, '⣿');
     }
 
+    #[test]
+    fn test_braille_round_trip() {
+        let art = "⠁⣿⠀\n⢕⠈⡀";
+        let img = ImageConverter::braille_string_to_image(art, 3, 2);
+
+        assert_eq!(img.dimensions(), (6, 8));
+        assert_eq!(ImageConverter::image_to_braille_string(&img, 3, 2), art);
+    }
+
+    #[test]
+    fn test_braille_to_image_ignores_other_characters() {
+        let img = ImageConverter::braille_string_to_image("a ", 2, 1);
+        assert!(img.pixels().all(|p| p[0] == 255));
+    }
+
     #[test]
     fn test_apply_threshold() {
         let img = ImageBuffer::from_fn(4, 4, |x, y| {
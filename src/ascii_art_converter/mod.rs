@@ -1,8 +1,18 @@
+#[cfg(feature = "converter")]
 mod converter;
+mod validation;
 
-pub use converter::{ImageConverter, TV_WIDTH, TV_HEIGHT, PIXEL_WIDTH, PIXEL_HEIGHT};
+#[cfg(feature = "converter")]
+pub use converter::ImageConverter;
+pub use validation::MAX_FRAMES;
 use std::fmt;
 
+pub const TV_WIDTH: usize = 32;
+pub const TV_HEIGHT: usize = 10;
+pub const PIXEL_WIDTH: usize = TV_WIDTH * 2;
+pub const PIXEL_HEIGHT: usize = TV_HEIGHT * 4;
+
+#[derive(Debug)]
 pub struct AsciiArtSheet {
     pub width: usize,
     pub height: usize,
@@ -0,0 +1,183 @@
+use super::{AsciiArtSheet, TV_HEIGHT, TV_WIDTH};
+
+pub const MAX_FRAMES: usize = 100;
+
+const HEADER_PREFIX: &str = "Ascii Art Animation, ";
+
+impl AsciiArtSheet {
+    /// Strict counterpart of `from_string`, for art coming from untrusted sources.
+    ///
+    /// Checks the header, that the art fits the TV, that every frame has exactly
+    /// `height` lines of exactly `width` characters and that there are at most
+    /// `MAX_FRAMES` frames. Errors name the offending frame and line.
+    pub fn validate(content: &str) -> Result<Self, String> {
+        let mut lines: Vec<&str> = content.lines().collect();
+
+        // Editors usually leave a trailing newline or some whitespace behind
+        while lines.last().is_some_and(|line| line.trim().is_empty()) {
+            lines.pop();
+        }
+
+        let Some((header, body)) = lines.split_first() else {
+            return Err("Art is empty".to_string());
+        };
+        let (width, height) = parse_header(header)?;
+
+        if width > TV_WIDTH || height > TV_HEIGHT {
+            return Err(format!(
+                "Art is {width}x{height}, the TV fits at most {TV_WIDTH}x{TV_HEIGHT}"
+            ));
+        }
+
+        if body.is_empty() {
+            return Err("Art has no frames".to_string());
+        }
+
+        let frame_count = body.len().div_ceil(height);
+        if frame_count > MAX_FRAMES {
+            return Err(format!("Art has {frame_count} frames, at most {MAX_FRAMES} are allowed"));
+        }
+
+        for (frame_index, frame) in body.chunks(height).enumerate() {
+            let frame_number = frame_index + 1;
+
+            if frame.len() != height {
+                return Err(format!(
+                    "Frame {frame_number} is incomplete: expected {height} lines, found {}",
+                    frame.len()
+                ));
+            }
+
+            for (line_index, line) in frame.iter().enumerate() {
+                let location = format!(
+                    "Frame {frame_number}, line {} (line {} of the art)",
+                    line_index + 1,
+                    frame_index * height + line_index + 2
+                );
+
+                if let Some(column) = line.chars().position(char::is_control) {
+                    return Err(format!("{location}: control character at column {}", column + 1));
+                }
+
+                let line_width = line.chars().count();
+                if line_width != width {
+                    return Err(format!("{location}: expected {width} characters, found {line_width}"));
+                }
+            }
+        }
+
+        Self::from_string(content)
+    }
+}
+
+/// Parses `Ascii Art Animation, WxH` with an optional `, Nfps` suffix
+fn parse_header(header: &str) -> Result<(usize, usize), String> {
+    let invalid = || {
+        format!("Invalid header '{header}', expected 'Ascii Art Animation, WIDTHxHEIGHT[, FPSfps]'")
+    };
+
+    let parts: Vec<&str> = header
+        .strip_prefix(HEADER_PREFIX)
+        .ok_or_else(invalid)?
+        .split(',')
+        .map(str::trim)
+        .collect();
+
+    let (dimensions, fps) = match parts.as_slice() {
+        [dimensions] => (*dimensions, None),
+        [dimensions, fps] => (*dimensions, Some(*fps)),
+        _ => return Err(invalid()),
+    };
+
+    if let Some(fps) = fps {
+        let valid_fps = fps
+            .strip_suffix("fps")
+            .and_then(|value| value.trim().parse::<f32>().ok())
+            .is_some_and(|value| value.is_finite() && value > 0.0);
+
+        if !valid_fps {
+            return Err(invalid());
+        }
+    }
+
+    let (width, height) = dimensions.split_once('x').ok_or_else(invalid)?;
+    let width: usize = width.parse().map_err(|_| invalid())?;
+    let height: usize = height.parse().map_err(|_| invalid())?;
+
+    if width == 0 || height == 0 {
+        return Err(format!("Invalid dimensions {width}x{height} in header"));
+    }
+
+    Ok((width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_accepts_well_formed_art() {
+        let sheet = AsciiArtSheet::validate("Ascii Art Animation, 3x2, 5fps\n⠁⠁⠁\n⠁⠁⠁\n⠂⠂⠂\n⠂⠂⠂\n").unwrap();
+        assert_eq!(sheet.frame_count(), 2);
+        assert_eq!((sheet.width, sheet.height), (3, 2));
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_headers() {
+        for art in [
+            "Ascii Art Animation 3x2\n⠁⠁⠁",
+            "Ascii Art Animation, 3by2\n⠁⠁⠁",
+            "Ascii Art Animation, 3x2, 2fps⠀⠀\n⠁⠁⠁",
+            "Ascii Art Animation, 3x2, 5fps, extra\n⠁⠁⠁",
+            "Ascii Art Animation, 0x2\n⠁⠁⠁",
+        ] {
+            assert!(AsciiArtSheet::validate(art).is_err(), "should reject {art:?}");
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_art_larger_than_tv() {
+        let line = "⠁".repeat(TV_WIDTH + 1);
+        let err = AsciiArtSheet::validate(&format!("Ascii Art Animation, {}x1\n{line}", TV_WIDTH + 1)).unwrap_err();
+        assert!(err.contains("the TV fits at most 32x10"), "{err}");
+    }
+
+    #[test]
+    fn test_validate_names_frame_and_line() {
+        let err = AsciiArtSheet::validate("Ascii Art Animation, 3x2\n⠁⠁⠁\n⠁⠁⠁\n⠂⠂⠂\n⠂⠂\n").unwrap_err();
+        assert_eq!(err, "Frame 2, line 2 (line 5 of the art): expected 3 characters, found 2");
+    }
+
+    #[test]
+    fn test_validate_rejects_incomplete_frame() {
+        let err = AsciiArtSheet::validate("Ascii Art Animation, 3x2\n⠁⠁⠁\n⠁⠁⠁\n⠂⠂⠂").unwrap_err();
+        assert_eq!(err, "Frame 2 is incomplete: expected 2 lines, found 1");
+    }
+
+    #[test]
+    fn test_validate_rejects_control_characters() {
+        let err = AsciiArtSheet::validate("Ascii Art Animation, 3x1\n⠁\u{1b}⠁").unwrap_err();
+        assert!(err.contains("control character at column 2"), "{err}");
+    }
+
+    #[test]
+    fn test_validate_caps_frame_count() {
+        let art = format!("Ascii Art Animation, 1x1\n{}", "⠁\n".repeat(MAX_FRAMES + 1));
+        assert!(AsciiArtSheet::validate(&art).is_err());
+    }
+
+    #[test]
+    fn test_bundled_sprites_are_valid() {
+        use crate::content_parser;
+
+        for path in ["sprites/neko_idle.txt", "sprites/neko_sleep.txt", "sprites/static.txt"] {
+            let file = std::fs::read_to_string(std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(path))
+                .unwrap_or_else(|e| panic!("{path}: {e}"));
+            let art = match content_parser::parse_content(&file) {
+                Ok(content) => content.art,
+                Err(_) => file,
+            };
+            assert!(AsciiArtSheet::validate(&art).is_ok(), "{path}: {:?}", AsciiArtSheet::validate(&art).err());
+        }
+    }
+}
@@ -6,6 +6,7 @@ pub use content::ChannelContent;
 pub use config::{ChannelConfig, ContentConfig};
 pub use feed::{FeedItem, FeedManager};
 
+pub use crate::signing::Verification;
 use crate::ui::AsciiArtPlayer;
 use std::fs;
 
@@ -16,6 +17,8 @@ pub struct Channel {
     pub content: ChannelContent,
     pub content_id: i64,
     pub server_url: Option<String>,
+    /// Only checked for contents from servers other than the home one
+    pub verification: Option<Verification>,
 }
 
 impl Channel {
@@ -30,6 +33,7 @@ impl Channel {
             content,
             content_id,
             server_url: None,
+            verification: None,
         })
     }
 
@@ -38,6 +42,11 @@ impl Channel {
         self
     }
 
+    pub fn with_verification(mut self, verification: Verification) -> Self {
+        self.verification = Some(verification);
+        self
+    }
+
     pub fn render(&mut self, delta_time: f32) -> &str {
         self.player.update(delta_time);
         self.player.current_frame()
@@ -1,16 +1,17 @@
-use super::auth_config::AuthConfig;
+use super::auth_config::{AuthConfig, AuthProfiles};
 
-pub fn store_auth(channel_id: i64, channel_name: String, token: String) -> Result<(), String> {
-    let auth = AuthConfig {
-        channel_id,
-        channel_name,
-        jwt_token: token,
-    };
-    auth.save()
+/// Saves a login under `profile` and makes it the default. A single login saved by an older
+/// version stays bound to `legacy_server_url`, the server it was made on.
+pub fn store_auth(legacy_server_url: &str, profile: &str, auth: AuthConfig) -> Result<(), String> {
+    let mut profiles = AuthProfiles::load(legacy_server_url)?;
+    profiles.insert(profile, auth);
+    profiles.save()
 }
 
-pub fn clear_auth() -> Result<(), String> {
-    AuthConfig::delete()
+pub fn clear_auth(legacy_server_url: &str, profile: &str) -> Result<(), String> {
+    let mut profiles = AuthProfiles::load(legacy_server_url)?;
+    profiles.remove(profile);
+    profiles.save()
 }
 
 #[cfg(test)]
@@ -20,9 +21,12 @@ mod tests {
     #[test]
     fn test_store_auth() {
         let auth = AuthConfig {
+            server_url: "http://localhost:3000".to_string(),
             channel_id: 1,
             channel_name: "test".to_string(),
             jwt_token: "test_token".to_string(),
+            user_name: None,
+            expires_at: None,
         };
 
         assert_eq!(auth.channel_id, 1);
@@ -33,9 +37,12 @@ mod tests {
     #[test]
     fn test_auth_validation() {
         let auth = AuthConfig {
+            server_url: "http://localhost:3000".to_string(),
             channel_id: 1,
             channel_name: "test".to_string(),
             jwt_token: "test_token".to_string(),
+            user_name: None,
+            expires_at: None,
         };
 
         assert!(auth.validate().is_ok());
@@ -1,36 +1,157 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+use super::paths::{self, Dirs};
+use crate::api::AuthResponse;
+
+/// Profile `tama auth` saves to when no `--profile` is given
+pub const DEFAULT_PROFILE: &str = "default";
+/// Sessions expiring sooner than this get a warning on every command
+pub const EXPIRY_WARNING_SECS: i64 = 3 * 24 * 60 * 60;
+
+/// A login to one channel on one server
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct AuthConfig {
+    /// Empty in files written before profiles, `AuthProfiles::load` fills it in
+    #[serde(default)]
+    pub server_url: String,
     pub channel_id: i64,
     pub channel_name: String,
     pub jwt_token: String,
+    /// Set when logged in as a channel member rather than with the channel password
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_name: Option<String>,
+    /// Unix timestamp, unknown for logins saved by older versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expiry {
+    Unknown,
+    Valid,
+    ExpiresSoon { remaining_secs: i64 },
+    Expired,
 }
 
 impl AuthConfig {
+    pub fn from_response(server_url: &str, response: &AuthResponse) -> Self {
+        Self {
+            server_url: server_url.to_string(),
+            channel_id: response.channel.id,
+            channel_name: response.channel.name.clone(),
+            jwt_token: response.token.clone(),
+            user_name: response.user.as_ref().map(|user| user.name.clone()),
+            expires_at: Some(response.expires_at),
+        }
+    }
+
+    pub fn expiry(&self, now: i64) -> Expiry {
+        match self.expires_at {
+            None => Expiry::Unknown,
+            Some(expires_at) if expires_at <= now => Expiry::Expired,
+            Some(expires_at) if expires_at - now < EXPIRY_WARNING_SECS => {
+                Expiry::ExpiresSoon { remaining_secs: expires_at - now }
+            }
+            Some(_) => Expiry::Valid,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.server_url.is_empty() {
+            return Err("Server URL is empty".to_string());
+        }
+
+        if self.channel_id == 0 {
+            return Err("Channel ID is not set".to_string());
+        }
+
+        if self.channel_name.is_empty() {
+            return Err("Channel name is empty".to_string());
+        }
+
+        if self.jwt_token.is_empty() {
+            return Err("JWT token is empty".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+/// Named logins, stored in `auth.json`
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct AuthProfiles {
+    /// Used when no `--profile` is given, the last one authenticated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_profile: Option<String>,
+    pub profiles: BTreeMap<String, AuthConfig>,
+}
+
+/// Files written before profiles held a single login
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AuthFile {
+    Profiles(AuthProfiles),
+    Single(AuthConfig),
+}
+
+impl AuthFile {
+    fn into_profiles(self, legacy_server_url: &str) -> AuthProfiles {
+        match self {
+            AuthFile::Profiles(profiles) => profiles,
+            AuthFile::Single(mut auth) => {
+                if auth.server_url.is_empty() {
+                    auth.server_url = legacy_server_url.to_string();
+                }
+                let mut profiles = AuthProfiles::default();
+                profiles.insert(DEFAULT_PROFILE, auth);
+                profiles
+            }
+        }
+    }
+}
+
+impl AuthProfiles {
     pub fn default_auth_path() -> PathBuf {
-        PathBuf::from(".").join("auth.json")
+        Dirs::from_env().data.join("auth.json")
     }
 
-    pub fn load() -> Result<Self, String> {
+    /// No profiles if there is no auth file yet. A single login saved by an older version
+    /// becomes the default profile, bound to `legacy_server_url`.
+    pub fn load(legacy_server_url: &str) -> Result<Self, String> {
         let path = Self::default_auth_path();
-        Self::load_from_path(&path)
+        Self::load_from_path(&path, legacy_server_url)
+    }
+
+    pub fn load_from_path(path: &Path, legacy_server_url: &str) -> Result<Self, String> {
+        Ok(Self::read_file(path)?.map_or_else(Self::default, |file| file.into_profiles(legacy_server_url)))
     }
 
-    pub fn load_from_path(path: &Path) -> Result<Self, String> {
+    /// Rewrites a single login saved by an older version as profiles, bound to `legacy_server_url`.
+    /// Returns false if the file is missing or already holds profiles.
+    pub fn upgrade_single_login(path: &Path, legacy_server_url: &str) -> Result<bool, String> {
+        match Self::read_file(path)? {
+            Some(file @ AuthFile::Single(_)) => {
+                file.into_profiles(legacy_server_url).save_to_path(path)?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn read_file(path: &Path) -> Result<Option<AuthFile>, String> {
         if !path.exists() {
-            return Err("Auth file not found".to_string());
+            return Ok(None);
         }
 
         let content = fs::read_to_string(path)
             .map_err(|e| format!("Failed to read auth file: {e}"))?;
 
-        let auth: AuthConfig = serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse auth file: {e}"))?;
-
-        Ok(auth)
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| format!("Failed to parse auth file: {e}"))
     }
 
     pub fn save(&self) -> Result<(), String> {
@@ -38,48 +159,60 @@ impl AuthConfig {
         self.save_to_path(&path)
     }
 
+    /// The file holds session tokens, so only its owner can read it
     pub fn save_to_path(&self, path: &Path) -> Result<(), String> {
         if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)
+            paths::create_private_dir(parent)
                 .map_err(|e| format!("Failed to create auth directory: {e}"))?;
         }
 
         let json = serde_json::to_string_pretty(self)
             .map_err(|e| format!("Failed to serialize auth: {e}"))?;
 
-        fs::write(path, json)
+        paths::write_private_file(path, json.as_bytes())
             .map_err(|e| format!("Failed to write auth file: {e}"))?;
 
         Ok(())
     }
 
-    pub fn auth_exists() -> bool {
-        Self::default_auth_path().exists()
+    /// Saves a login under `name`, making it the default
+    pub fn insert(&mut self, name: &str, auth: AuthConfig) {
+        self.profiles.insert(name.to_string(), auth);
+        self.default_profile = Some(name.to_string());
     }
 
-    pub fn delete() -> Result<(), String> {
-        let path = Self::default_auth_path();
-        if path.exists() {
-            fs::remove_file(&path)
-                .map_err(|e| format!("Failed to delete auth file: {e}"))?;
+    pub fn remove(&mut self, name: &str) -> Option<AuthConfig> {
+        let removed = self.profiles.remove(name);
+        if self.default_profile.as_deref() == Some(name) {
+            self.default_profile = None;
         }
-        Ok(())
+        removed
     }
 
-    pub fn validate(&self) -> Result<(), String> {
-        if self.channel_id == 0 {
-            return Err("Channel ID is not set".to_string());
-        }
+    /// Name of the profile commands use without `--profile`
+    pub fn default_name(&self) -> Option<&str> {
+        self.default_profile
+            .as_deref()
+            .filter(|name| self.profiles.contains_key(*name))
+            .or_else(|| self.profiles.contains_key(DEFAULT_PROFILE).then_some(DEFAULT_PROFILE))
+            .or_else(|| self.profiles.keys().next().map(String::as_str))
+    }
 
-        if self.channel_name.is_empty() {
-            return Err("Channel name is empty".to_string());
-        }
+    /// The profile called `name`, or the default one
+    pub fn get(&self, name: Option<&str>) -> Option<(&str, &AuthConfig)> {
+        let name = name.or_else(|| self.default_name())?;
+        self.profiles.get_key_value(name).map(|(name, auth)| (name.as_str(), auth))
+    }
 
-        if self.jwt_token.is_empty() {
-            return Err("JWT token is empty".to_string());
-        }
+    /// A login valid on `server_url`, preferring the default profile
+    pub fn for_server(&self, server_url: &str) -> Option<&AuthConfig> {
+        let server_url = server_url.trim_end_matches('/');
+        let matches = |auth: &&AuthConfig| auth.server_url.trim_end_matches('/') == server_url;
 
-        Ok(())
+        self.get(None)
+            .map(|(_, auth)| auth)
+            .filter(matches)
+            .or_else(|| self.profiles.values().find(matches))
     }
 }
 
@@ -87,57 +220,121 @@ impl AuthConfig {
 mod tests {
     use super::*;
 
+    fn auth(server_url: &str, channel_name: &str) -> AuthConfig {
+        AuthConfig {
+            server_url: server_url.to_string(),
+            channel_id: 1,
+            channel_name: channel_name.to_string(),
+            jwt_token: "test_token".to_string(),
+            user_name: None,
+            expires_at: None,
+        }
+    }
+
     #[test]
     fn test_auth_serialization() {
-        let auth = AuthConfig {
-            channel_id: 123,
-            channel_name: "Test Channel".to_string(),
-            jwt_token: "test_token".to_string(),
-        };
+        let auth = auth("http://localhost:3000", "Test Channel");
 
         let json = serde_json::to_string(&auth).unwrap();
         let deserialized: AuthConfig = serde_json::from_str(&json).unwrap();
 
-        assert_eq!(auth.channel_id, deserialized.channel_id);
-        assert_eq!(auth.channel_name, deserialized.channel_name);
-        assert_eq!(auth.jwt_token, deserialized.jwt_token);
+        assert_eq!(auth, deserialized);
     }
 
     #[test]
-    fn test_validate_valid_auth() {
-        let auth = AuthConfig {
-            channel_id: 1,
-            channel_name: "test".to_string(),
-            jwt_token: "test_token".to_string(),
-        };
+    fn test_single_login_becomes_default_profile() {
+        let dir = std::env::temp_dir().join(format!("tama-auth-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("auth.json");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&path, r#"{"channel_id":1,"channel_name":"test","jwt_token":"token"}"#).unwrap();
+
+        let profiles = AuthProfiles::load_from_path(&path, "http://localhost:3000").unwrap();
+        let (name, auth) = profiles.get(None).unwrap();
+        assert_eq!(name, DEFAULT_PROFILE);
+        assert_eq!(auth.server_url, "http://localhost:3000");
+        assert_eq!((auth.user_name.as_deref(), auth.expires_at), (None, None));
 
-        assert!(auth.validate().is_ok());
+        assert_eq!(AuthProfiles::load_from_path(&dir.join("missing.json"), "").unwrap(), AuthProfiles::default());
+        fs::remove_dir_all(dir).unwrap();
     }
 
     #[test]
-    fn test_validate_empty_fields() {
-        let auth = AuthConfig {
-            channel_id: 0,
-            channel_name: "test".to_string(),
-            jwt_token: "test_token".to_string(),
-        };
+    fn test_single_login_is_upgraded_in_place() {
+        let dir = std::env::temp_dir().join(format!("tama-auth-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("auth.json");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&path, r#"{"channel_id":1,"channel_name":"test","jwt_token":"token"}"#).unwrap();
 
-        assert!(auth.validate().is_err());
+        assert!(AuthProfiles::upgrade_single_login(&path, "https://tama.example").unwrap());
+        assert!(!AuthProfiles::upgrade_single_login(&path, "http://localhost:3000").unwrap());
+        assert!(!AuthProfiles::upgrade_single_login(&dir.join("missing.json"), "").unwrap());
 
-        let auth = AuthConfig {
-            channel_id: 1,
-            channel_name: String::new(),
-            jwt_token: "test_token".to_string(),
-        };
+        // Bound for good, whatever server later loads pass
+        let profiles = AuthProfiles::load_from_path(&path, "http://localhost:3000").unwrap();
+        assert_eq!(profiles.get(None).unwrap().1.server_url, "https://tama.example");
+        fs::remove_dir_all(dir).unwrap();
+    }
 
-        assert!(auth.validate().is_err());
+    #[cfg(unix)]
+    #[test]
+    fn test_auth_file_is_private() {
+        use std::os::unix::fs::PermissionsExt;
 
-        let auth = AuthConfig {
-            channel_id: 1,
-            channel_name: "test".to_string(),
-            jwt_token: String::new(),
-        };
+        let dir = std::env::temp_dir().join(format!("tama-auth-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("auth.json");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&path, "{}").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        let mut profiles = AuthProfiles::default();
+        profiles.insert("work", auth("https://tama.example", "neko"));
+        profiles.save_to_path(&path).unwrap();
+
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(AuthProfiles::load_from_path(&path, "").unwrap(), profiles);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_profile_selection() {
+        let mut profiles = AuthProfiles::default();
+        assert!(profiles.get(None).is_none());
 
-        assert!(auth.validate().is_err());
+        profiles.insert("home", auth("http://localhost:3000", "neko"));
+        profiles.insert("work", auth("https://tama.example/", "mugs"));
+
+        assert_eq!(profiles.get(None).unwrap().0, "work");
+        assert_eq!(profiles.get(Some("home")).unwrap().1.channel_name, "neko");
+        assert!(profiles.get(Some("other")).is_none());
+
+        assert_eq!(profiles.for_server("http://localhost:3000/").unwrap().channel_name, "neko");
+        assert_eq!(profiles.for_server("https://tama.example").unwrap().channel_name, "mugs");
+        assert!(profiles.for_server("http://localhost:3001").is_none());
+
+        profiles.remove("work");
+        assert_eq!(profiles.get(None).unwrap().0, "home");
+    }
+
+    #[test]
+    fn test_expiry() {
+        let mut auth = auth("http://localhost:3000", "neko");
+        assert_eq!(auth.expiry(1000), Expiry::Unknown);
+
+        auth.expires_at = Some(1000 + EXPIRY_WARNING_SECS * 2);
+        assert_eq!(auth.expiry(1000), Expiry::Valid);
+        auth.expires_at = Some(1000 + 60);
+        assert_eq!(auth.expiry(1000), Expiry::ExpiresSoon { remaining_secs: 60 });
+        assert_eq!(auth.expiry(1060), Expiry::Expired);
+    }
+
+    #[test]
+    fn test_validate_empty_fields() {
+        assert!(auth("http://localhost:3000", "test").validate().is_ok());
+        assert!(auth("", "test").validate().is_err());
+        assert!(auth("http://localhost:3000", "").validate().is_err());
+        assert!(AuthConfig { channel_id: 0, ..auth("http://localhost:3000", "test") }.validate().is_err());
+        assert!(AuthConfig { jwt_token: String::new(), ..auth("http://localhost:3000", "test") }.validate().is_err());
     }
 }
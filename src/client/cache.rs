@@ -0,0 +1,276 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::{ChannelInfo, ContentData, FeedItem};
+
+pub const DEFAULT_MAX_MB: u64 = 20;
+
+/// A content as last fetched from `server_url`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CachedContent {
+    pub server_url: String,
+    /// Unknown when the content was fetched on its own, by id or share link
+    pub channel: Option<ChannelInfo>,
+    pub content: ContentData,
+}
+
+pub struct CacheEntry {
+    pub item: CachedContent,
+    pub size: u64,
+    pub last_used: SystemTime,
+}
+
+/// Contents fetched from any server, one file per content under `<dir>/<server>/<id>.json`.
+/// Once the files add up to more than `max_bytes`, the least recently used ones are removed.
+#[derive(Clone)]
+pub struct ContentCache {
+    dir: PathBuf,
+    max_bytes: u64,
+}
+
+impl ContentCache {
+    pub fn new(dir: PathBuf, max_bytes: u64) -> Self {
+        Self { dir, max_bytes }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn max_bytes(&self) -> u64 {
+        self.max_bytes
+    }
+
+    fn entry_path(&self, server_url: &str, content_id: i64) -> PathBuf {
+        let server_dir: String = server_url
+            .trim_end_matches('/')
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+            .collect();
+
+        self.dir.join(server_dir).join(format!("{content_id}.json"))
+    }
+
+    fn write(&self, server_url: &str, channel: Option<&ChannelInfo>, content: &ContentData) -> Result<(), String> {
+        let path = self.entry_path(server_url, content.id);
+
+        // Contents fetched by id don't say which channel they belong to, the feed does
+        let channel = channel.cloned()
+            .or_else(|| read_entry(&path).ok().and_then(|cached| cached.channel));
+
+        let item = CachedContent {
+            server_url: server_url.trim_end_matches('/').to_string(),
+            channel,
+            content: content.clone(),
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create cache directory: {e}"))?;
+        }
+
+        let json = serde_json::to_string(&item)
+            .map_err(|e| format!("Failed to serialize cache entry: {e}"))?;
+
+        fs::write(&path, json)
+            .map_err(|e| format!("Failed to write cache entry: {e}"))
+    }
+
+    pub fn store(&self, server_url: &str, channel: Option<&ChannelInfo>, content: &ContentData) -> Result<(), String> {
+        self.write(server_url, channel, content)?;
+        self.evict()
+    }
+
+    pub fn store_feed(&self, server_url: &str, items: &[FeedItem]) -> Result<(), String> {
+        for item in items {
+            self.write(server_url, Some(&item.channel), &item.content)?;
+        }
+        self.evict()
+    }
+
+    /// Marks the content as used, so it is among the last to be evicted
+    pub fn get(&self, server_url: &str, content_id: i64) -> Option<CachedContent> {
+        let path = self.entry_path(server_url, content_id);
+        let item = read_entry(&path).ok()?;
+
+        let _ = fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(SystemTime::now()));
+
+        Some(item)
+    }
+
+    /// Most recently used first. Unreadable files are skipped.
+    pub fn entries(&self) -> Result<Vec<CacheEntry>, String> {
+        let mut entries: Vec<CacheEntry> = self.files()?
+            .into_iter()
+            .filter_map(|(path, size, last_used)| {
+                read_entry(&path).ok().map(|item| CacheEntry { item, size, last_used })
+            })
+            .collect();
+
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.last_used));
+        Ok(entries)
+    }
+
+    /// Cached contents of `server_url`, most recently used first
+    pub fn server_entries(&self, server_url: &str) -> Result<Vec<CachedContent>, String> {
+        let server_url = server_url.trim_end_matches('/');
+
+        Ok(self.entries()?
+            .into_iter()
+            .map(|entry| entry.item)
+            .filter(|item| item.server_url == server_url)
+            .collect())
+    }
+
+    /// Removes every entry, returning how many there were
+    pub fn clear(&self) -> Result<usize, String> {
+        let count = self.files()?.len();
+
+        if self.dir.exists() {
+            fs::remove_dir_all(&self.dir)
+                .map_err(|e| format!("Failed to clear cache: {e}"))?;
+        }
+        Ok(count)
+    }
+
+    /// Path, size and modification time of every cache file
+    fn files(&self) -> Result<Vec<(PathBuf, u64, SystemTime)>, String> {
+        let mut files = Vec::new();
+        if !self.dir.exists() {
+            return Ok(files);
+        }
+
+        let server_dirs = fs::read_dir(&self.dir)
+            .map_err(|e| format!("Failed to read cache directory: {e}"))?;
+
+        for server_dir in server_dirs.flatten() {
+            let Ok(contents) = fs::read_dir(server_dir.path()) else {
+                continue;
+            };
+
+            for file in contents.flatten() {
+                let path = file.path();
+                if path.extension().is_none_or(|extension| extension != "json") {
+                    continue;
+                }
+                if let Ok(metadata) = file.metadata() {
+                    let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                    files.push((path, metadata.len(), modified));
+                }
+            }
+        }
+        Ok(files)
+    }
+
+    fn evict(&self) -> Result<(), String> {
+        let mut files = self.files()?;
+        let mut total: u64 = files.iter().map(|(_, size, _)| size).sum();
+        if total <= self.max_bytes {
+            return Ok(());
+        }
+
+        files.sort_by_key(|(_, _, modified)| *modified);
+        for (path, size, _) in files {
+            if total <= self.max_bytes {
+                break;
+            }
+            fs::remove_file(&path)
+                .map_err(|e| format!("Failed to evict cache entry: {e}"))?;
+            total -= size;
+        }
+        Ok(())
+    }
+}
+
+fn read_entry(path: &Path) -> Result<CachedContent, String> {
+    let json = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read cache entry: {e}"))?;
+
+    serde_json::from_str(&json)
+        .map_err(|e| format!("Failed to parse cache entry: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const SERVER: &str = "http://localhost:3000";
+
+    fn cache(max_bytes: u64) -> ContentCache {
+        let dir = std::env::temp_dir().join(format!("tama-cache-{}", uuid::Uuid::new_v4()));
+        ContentCache::new(dir, max_bytes)
+    }
+
+    fn content(id: i64) -> ContentData {
+        ContentData {
+            id,
+            name: String::new(),
+            art: "⣿".repeat(100),
+            midi_composition: "4c".to_string(),
+            fps: 10.0,
+            signature: None,
+            public_key: None,
+            origin: None,
+        }
+    }
+
+    fn feed_item(id: i64) -> FeedItem {
+        FeedItem {
+            channel: ChannelInfo { id: 7, name: "neko".to_string() },
+            content: content(id),
+        }
+    }
+
+    fn set_last_used(cache: &ContentCache, content_id: i64, seconds_ago: u64) {
+        let path = cache.entry_path(SERVER, content_id);
+        let file = fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(seconds_ago)).unwrap();
+    }
+
+    #[test]
+    fn test_store_and_get() {
+        let cache = cache(u64::MAX);
+        cache.store_feed(SERVER, &[feed_item(1)]).unwrap();
+        // Fetching the content on its own keeps the channel the feed said it belongs to
+        cache.store(&format!("{SERVER}/"), None, &content(1)).unwrap();
+        cache.store(SERVER, None, &content(2)).unwrap();
+
+        let cached = cache.get(SERVER, 1).unwrap();
+        assert_eq!(cached.channel.unwrap().name, "neko");
+        assert!(cache.get(SERVER, 2).unwrap().channel.is_none());
+        assert!(cache.get("http://localhost:3001", 1).is_none());
+        assert_eq!(cache.server_entries(SERVER).unwrap().len(), 2);
+
+        assert_eq!(cache.clear().unwrap(), 2);
+        assert!(cache.entries().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_least_recently_used_are_evicted() {
+        let cache = cache(u64::MAX);
+        cache.store_feed(SERVER, &[feed_item(1), feed_item(2), feed_item(3)]).unwrap();
+        let entry_size = cache.entries().unwrap()[0].size;
+
+        set_last_used(&cache, 1, 30);
+        set_last_used(&cache, 2, 20);
+        set_last_used(&cache, 3, 10);
+        cache.get(SERVER, 1).unwrap();
+
+        // Room for three entries, the fourth pushes out the one used longest ago
+        let cache = ContentCache::new(cache.dir().to_path_buf(), entry_size * 3 + entry_size / 2);
+        cache.store_feed(SERVER, &[feed_item(4)]).unwrap();
+
+        let ids: Vec<i64> = cache.entries().unwrap().iter().map(|entry| entry.item.content.id).collect();
+        assert_eq!(ids.len(), 3);
+        assert!(!ids.contains(&2));
+        assert_eq!(ids[2], 3);
+
+        cache.clear().unwrap();
+    }
+}
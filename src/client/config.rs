@@ -1,4 +1,9 @@
+use super::cache;
+use super::federation::ServerStats;
+use super::http::HttpConfig;
+use super::paths::Dirs;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -9,15 +14,48 @@ pub struct TamaConfig {
     pub servers: Vec<String>,
     #[serde(default)]
     pub server_override: bool,
+    /// Timeouts, retries and user agent of every request
+    #[serde(default)]
+    pub http: HttpConfig,
+    /// Size cap of the offline content cache
+    #[serde(default = "default_cache_max_mb")]
+    pub cache_max_mb: u64,
+    /// Latency and failures of every server the feed was fetched from, to put flaky ones last
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub server_stats: BTreeMap<String, ServerStats>,
+}
+
+fn default_cache_max_mb() -> u64 {
+    cache::DEFAULT_MAX_MB
 }
 
 impl TamaConfig {
+    pub fn new(server_url: String) -> Self {
+        Self {
+            server_url,
+            servers: vec![],
+            server_override: false,
+            http: HttpConfig::default(),
+            cache_max_mb: cache::DEFAULT_MAX_MB,
+            server_stats: BTreeMap::new(),
+        }
+    }
+
     pub fn default_config_path() -> PathBuf {
-        PathBuf::from(".").join("config.json")
+        Dirs::from_env().config.join("config.json")
     }
 
     pub fn default_keys_dir() -> PathBuf {
-        PathBuf::from(".")
+        Dirs::from_env().data
+    }
+
+    pub fn default_cache_dir() -> PathBuf {
+        Dirs::from_env().cache
+    }
+
+    /// Cache at the default location, capped at `cache_max_mb`
+    pub fn content_cache(&self) -> cache::ContentCache {
+        cache::ContentCache::new(Self::default_cache_dir(), self.cache_max_mb.saturating_mul(1024 * 1024))
     }
 
     pub fn load() -> Result<Self, String> {
@@ -82,6 +120,9 @@ mod tests {
             server_url: "http://localhost:3000".to_string(),
             servers: vec![],
             server_override: false,
+            http: HttpConfig::default(),
+            cache_max_mb: cache::DEFAULT_MAX_MB,
+            server_stats: BTreeMap::new(),
         };
 
         let json = serde_json::to_string(&config).unwrap();
@@ -90,6 +131,14 @@ mod tests {
         assert_eq!(config.server_url, deserialized.server_url);
         assert_eq!(config.servers, deserialized.servers);
         assert_eq!(config.server_override, deserialized.server_override);
+        assert_eq!(config.http, deserialized.http);
+    }
+
+    #[test]
+    fn test_config_without_http_section() {
+        let config: TamaConfig = serde_json::from_str(r#"{"server_url":"http://localhost:3000"}"#).unwrap();
+        assert_eq!(config.http, HttpConfig::default());
+        assert_eq!(config.cache_max_mb, cache::DEFAULT_MAX_MB);
     }
 
     #[test]
@@ -98,6 +147,9 @@ mod tests {
             server_url: "http://localhost:3000".to_string(),
             servers: vec![],
             server_override: false,
+            http: HttpConfig::default(),
+            cache_max_mb: cache::DEFAULT_MAX_MB,
+            server_stats: BTreeMap::new(),
         };
 
         assert!(config.validate().is_ok());
@@ -109,6 +161,9 @@ mod tests {
             server_url: String::new(),
             servers: vec![],
             server_override: false,
+            http: HttpConfig::default(),
+            cache_max_mb: cache::DEFAULT_MAX_MB,
+            server_stats: BTreeMap::new(),
         };
 
         assert!(config.validate().is_err());
@@ -0,0 +1,314 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::time::{Duration, Instant};
+
+use super::cache::ContentCache;
+use super::{http, ApiClient, FeedItem};
+use crate::signing;
+
+/// How a server has answered feed requests so far, kept in `config.json`
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ServerStats {
+    /// Moving average over successful fetches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(default)]
+    pub successes: u64,
+    #[serde(default)]
+    pub failures: u64,
+    /// Failures since the last success
+    #[serde(default)]
+    pub consecutive_failures: u32,
+}
+
+impl ServerStats {
+    pub fn record_success(&mut self, latency: Duration) {
+        let sample = latency.as_millis() as u64;
+        self.latency_ms = Some(match self.latency_ms {
+            Some(average) => (average * 3 + sample) / 4,
+            None => sample,
+        });
+        self.successes += 1;
+        self.consecutive_failures = 0;
+    }
+
+    pub fn record_failure(&mut self) {
+        self.failures += 1;
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+    }
+
+    /// Lower goes first: servers failing right now, then slow ones, are pushed back.
+    /// Servers never heard from get a chance before the ones known to be slow.
+    fn priority(&self) -> (u32, u64) {
+        (self.consecutive_failures, self.latency_ms.unwrap_or(0))
+    }
+}
+
+/// Key of a server in the stats, so `https://a/` and `https://a` share them
+pub fn server_key(server_url: &str) -> String {
+    server_url.trim_end_matches('/').to_string()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FetchStatus {
+    /// `duplicates` of the `items` were already in the feed from another server
+    Ok { items: usize, duplicates: usize },
+    Failed(String),
+    TimedOut,
+}
+
+#[derive(Debug, Clone)]
+pub struct ServerStatus {
+    pub server_url: String,
+    pub latency: Duration,
+    pub status: FetchStatus,
+}
+
+impl ServerStatus {
+    pub fn is_ok(&self) -> bool {
+        matches!(self.status, FetchStatus::Ok { .. })
+    }
+}
+
+/// A feed item along with the server it came from
+#[derive(Debug, Clone)]
+pub struct FederatedItem {
+    pub server_url: String,
+    pub item: FeedItem,
+}
+
+pub struct FederatedFeedResult {
+    pub items: Vec<FederatedItem>,
+    /// One per server, in the order their items were interleaved
+    pub statuses: Vec<ServerStatus>,
+}
+
+impl FederatedFeedResult {
+    pub fn any_reachable(&self) -> bool {
+        self.statuses.iter().any(ServerStatus::is_ok)
+    }
+}
+
+/// The feeds of several servers merged into one: fetched in parallel, taking one item
+/// from each server in turn, and showing contents mirrored on more than one server once.
+pub struct FederatedFeed {
+    servers: Vec<String>,
+    /// How long a server gets to answer before its feed is left out. Defaults to the request
+    /// budget of the HTTP settings, so retries get to run their course.
+    timeout: Option<Duration>,
+    cache: Option<ContentCache>,
+}
+
+impl FederatedFeed {
+    pub fn new(servers: Vec<String>) -> Self {
+        Self {
+            servers,
+            timeout: None,
+            cache: None,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_cache(mut self, cache: ContentCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Servers in the order their items are interleaved, the flaky and slow ones last.
+    /// Ties keep the configured order.
+    pub fn ordered_servers(&self, stats: &BTreeMap<String, ServerStats>) -> Vec<String> {
+        let mut servers = self.servers.clone();
+        servers.sort_by_key(|server_url| {
+            stats.get(&server_key(server_url)).map(ServerStats::priority).unwrap_or_default()
+        });
+        servers
+    }
+
+    /// Fetches every server, recording how each one did in `stats`
+    pub async fn fetch(&self, stats: &mut BTreeMap<String, ServerStats>) -> FederatedFeedResult {
+        let servers = self.ordered_servers(stats);
+        let timeout = self.timeout.unwrap_or_else(|| http::shared().request_budget());
+
+        let tasks: Vec<_> = servers
+            .iter()
+            .map(|server_url| {
+                let mut client = ApiClient::new(server_url.clone());
+                if let Some(cache) = &self.cache {
+                    client = client.with_cache(cache.clone());
+                }
+
+                tokio::spawn(async move {
+                    let started = Instant::now();
+                    let result = tokio::time::timeout(timeout, client.fetch_feed()).await;
+                    (result, started.elapsed())
+                })
+            })
+            .collect();
+
+        let mut fetched = Vec::new();
+        for (server_url, task) in servers.into_iter().zip(tasks) {
+            let (result, latency) = match task.await {
+                Ok((Ok(Ok(items)), latency)) => (Ok(items), latency),
+                Ok((Ok(Err(e)), latency)) => (Err(FetchStatus::Failed(e)), latency),
+                Ok((Err(_), latency)) => (Err(FetchStatus::TimedOut), latency),
+                Err(e) => (Err(FetchStatus::Failed(format!("Task error: {e}"))), Duration::ZERO),
+            };
+
+            let server_stats = stats.entry(server_key(&server_url)).or_default();
+            match &result {
+                Ok(_) => server_stats.record_success(latency),
+                Err(_) => server_stats.record_failure(),
+            }
+            fetched.push((server_url, latency, result));
+        }
+
+        let mut statuses = Vec::new();
+        let mut feeds = Vec::new();
+        for (server_url, latency, result) in fetched {
+            let status = match result {
+                Ok(items) => {
+                    let status = FetchStatus::Ok { items: items.len(), duplicates: 0 };
+                    feeds.push((server_url.clone(), items));
+                    status
+                }
+                Err(status) => status,
+            };
+            statuses.push(ServerStatus { server_url, latency, status });
+        }
+
+        let (items, duplicates) = merge(feeds);
+        let mut duplicates = duplicates.into_iter();
+        for status in &mut statuses {
+            if let FetchStatus::Ok { duplicates: count, .. } = &mut status.status {
+                *count = duplicates.next().unwrap_or(0);
+            }
+        }
+
+        FederatedFeedResult { items, statuses }
+    }
+}
+
+/// Interleaves the feeds round-robin, keeping the first copy of each content.
+/// Returns the merged items and how many duplicates each feed had.
+fn merge(feeds: Vec<(String, Vec<FeedItem>)>) -> (Vec<FederatedItem>, Vec<usize>) {
+    let mut duplicates = vec![0; feeds.len()];
+    let mut seen = HashSet::new();
+    let mut items = Vec::new();
+
+    let mut feeds: Vec<_> = feeds
+        .into_iter()
+        .map(|(server_url, items)| (server_url, items.into_iter()))
+        .collect();
+
+    loop {
+        let mut any_left = false;
+        for (index, (server_url, feed)) in feeds.iter_mut().enumerate() {
+            let Some(item) = feed.next() else {
+                continue;
+            };
+            any_left = true;
+
+            let content = &item.content;
+            if seen.insert(signing::content_digest(&content.art, &content.midi_composition, content.fps)) {
+                items.push(FederatedItem { server_url: server_url.clone(), item });
+            } else {
+                duplicates[index] += 1;
+            }
+        }
+        if !any_left {
+            break;
+        }
+    }
+
+    (items, duplicates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{ChannelInfo, ContentData};
+
+    fn item(id: i64, art: &str) -> FeedItem {
+        FeedItem {
+            channel: ChannelInfo { id: 1, name: "neko".to_string() },
+            content: ContentData {
+                id,
+                name: String::new(),
+                art: art.to_string(),
+                midi_composition: "4c".to_string(),
+                fps: 10.0,
+                signature: None,
+                public_key: None,
+                origin: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_merge_interleaves_and_dedupes() {
+        let feeds = vec![
+            ("https://a".to_string(), vec![item(1, "a1"), item(2, "a2"), item(3, "a3")]),
+            ("https://b".to_string(), vec![item(7, "b1"), item(8, "a2")]),
+            ("https://c".to_string(), vec![item(9, "c1")]),
+        ];
+
+        let (items, duplicates) = merge(feeds);
+        let order: Vec<(&str, i64)> = items
+            .iter()
+            .map(|item| (item.server_url.as_str(), item.item.content.id))
+            .collect();
+
+        assert_eq!(order, vec![
+            ("https://a", 1), ("https://b", 7), ("https://c", 9),
+            ("https://a", 2),
+            ("https://a", 3),
+        ]);
+        assert_eq!(duplicates, vec![0, 1, 0]);
+    }
+
+    #[test]
+    fn test_flaky_and_slow_servers_go_last() {
+        let feed = FederatedFeed::new(vec![
+            "https://flaky".to_string(),
+            "https://slow/".to_string(),
+            "https://new".to_string(),
+            "https://fast".to_string(),
+        ]);
+
+        let mut stats = BTreeMap::new();
+        let mut flaky = ServerStats::default();
+        flaky.record_success(Duration::from_millis(10));
+        flaky.record_failure();
+        stats.insert("https://flaky".to_string(), flaky);
+
+        let mut slow = ServerStats::default();
+        slow.record_success(Duration::from_millis(900));
+        stats.insert("https://slow".to_string(), slow);
+
+        let mut fast = ServerStats::default();
+        fast.record_success(Duration::from_millis(40));
+        stats.insert("https://fast".to_string(), fast);
+
+        assert_eq!(feed.ordered_servers(&stats), vec!["https://new", "https://fast", "https://slow/", "https://flaky"]);
+    }
+
+    #[test]
+    fn test_server_stats() {
+        let mut stats = ServerStats::default();
+        stats.record_success(Duration::from_millis(100));
+        stats.record_success(Duration::from_millis(500));
+        assert_eq!(stats.latency_ms, Some(200));
+
+        stats.record_failure();
+        stats.record_failure();
+        assert_eq!((stats.successes, stats.failures, stats.consecutive_failures), (2, 2, 2));
+
+        stats.record_success(Duration::from_millis(200));
+        assert_eq!(stats.consecutive_failures, 0);
+        assert_eq!(stats.failures, 2);
+    }
+}
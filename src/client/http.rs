@@ -0,0 +1,235 @@
+use rand::Rng;
+use reqwest::{header, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Longest `Retry-After` we are willing to wait, longer ones fail right away
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Network settings, the `http` section of `config.json`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct HttpConfig {
+    pub connect_timeout_secs: u64,
+    /// Longest wait for the next bytes of a response. Slow downloads are fine as long as data keeps coming.
+    pub read_timeout_secs: u64,
+    /// Extra attempts of GET requests after a network error, a 429 or a 502/503/504
+    pub max_retries: u32,
+    /// Backoff before the first retry, doubled before each of the following ones
+    pub retry_base_delay_ms: u64,
+    /// Defaults to `tama/<version>`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout_secs: 5,
+            read_timeout_secs: 30,
+            max_retries: 3,
+            retry_base_delay_ms: 500,
+            user_agent: None,
+        }
+    }
+}
+
+impl HttpConfig {
+    pub fn user_agent(&self) -> String {
+        self.user_agent
+            .clone()
+            .unwrap_or_else(|| concat!("tama/", env!("CARGO_PKG_VERSION")).to_string())
+    }
+
+    /// Longest a GET can take with all its retries and their backoff, as long as the server
+    /// answers in time and doesn't ask for a long `Retry-After`
+    pub fn request_budget(&self) -> Duration {
+        let attempt = Duration::from_secs(self.connect_timeout_secs.saturating_add(self.read_timeout_secs));
+        let base_delay = Duration::from_millis(self.retry_base_delay_ms);
+        let backoff: Duration = (0..self.max_retries).map(|retry| jittered(base_delay, retry, 1.0)).sum();
+        attempt.saturating_mul(self.max_retries.saturating_add(1)).saturating_add(backoff)
+    }
+}
+
+/// Pooled HTTP client. Clones share connections, so every `ApiClient` can hold one.
+#[derive(Clone)]
+pub struct HttpClient {
+    client: reqwest::Client,
+    max_retries: u32,
+    retry_base_delay: Duration,
+    request_budget: Duration,
+}
+
+static SHARED: OnceLock<HttpClient> = OnceLock::new();
+
+/// Sets up the client `shared` returns. Only the first call counts, and only if
+/// it happens before the first request.
+pub fn configure(config: &HttpConfig) -> Result<(), String> {
+    let _ = SHARED.set(HttpClient::new(config)?);
+    Ok(())
+}
+
+/// Client used by `ApiClient::new`
+pub fn shared() -> HttpClient {
+    SHARED
+        .get_or_init(|| HttpClient::new(&HttpConfig::default()).expect("Default HTTP settings are valid"))
+        .clone()
+}
+
+impl HttpClient {
+    /// Fails on settings reqwest refuses, such as a user agent that isn't a valid header
+    pub fn new(config: &HttpConfig) -> Result<Self, String> {
+        let client = reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(config.connect_timeout_secs))
+            .read_timeout(Duration::from_secs(config.read_timeout_secs))
+            .user_agent(config.user_agent())
+            .build()
+            .map_err(|e| format!("Failed to set up the HTTP client: {e}"))?;
+
+        Ok(Self {
+            client,
+            max_retries: config.max_retries,
+            retry_base_delay: Duration::from_millis(config.retry_base_delay_ms),
+            request_budget: config.request_budget(),
+        })
+    }
+
+    /// See `HttpConfig::request_budget`
+    pub fn request_budget(&self) -> Duration {
+        self.request_budget
+    }
+
+    /// For requests that must not be repeated
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    /// GET with retries. Network errors and overloaded servers are retried with jittered
+    /// exponential backoff, rate limited requests after the server's `Retry-After`.
+    pub async fn get(&self, url: &str, token: Option<&str>) -> Result<reqwest::Response, reqwest::Error> {
+        let mut attempt = 0;
+
+        loop {
+            let mut request = self.client.get(url);
+            if let Some(token) = token {
+                request = request.header(header::AUTHORIZATION, format!("Bearer {token}"));
+            }
+
+            let result = request.send().await;
+            if attempt >= self.max_retries {
+                return result;
+            }
+
+            let delay = match &result {
+                Ok(response) => match retry_delay(response, self.retry_base_delay, attempt) {
+                    Some(delay) => delay,
+                    None => return result,
+                },
+                Err(e) if e.is_timeout() || e.is_connect() || e.is_request() => backoff(self.retry_base_delay, attempt),
+                Err(_) => return result,
+            };
+
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+/// Wait before retrying a request that got `response`, `None` if it should not be retried
+fn retry_delay(response: &reqwest::Response, base_delay: Duration, attempt: u32) -> Option<Duration> {
+    match response.status() {
+        StatusCode::TOO_MANY_REQUESTS => {
+            let retry_after = response.headers()
+                .get(header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| parse_retry_after(value, chrono::Utc::now()));
+
+            match retry_after {
+                Some(delay) if delay > MAX_RETRY_AFTER => None,
+                Some(delay) => Some(delay),
+                None => Some(backoff(base_delay, attempt)),
+            }
+        }
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT => {
+            Some(backoff(base_delay, attempt))
+        }
+        _ => None,
+    }
+}
+
+/// Random delay between half and all of `base_delay * 2^attempt`, so that clients
+/// failing together don't all come back at the same moment
+fn backoff(base_delay: Duration, attempt: u32) -> Duration {
+    jittered(base_delay, attempt, rand::thread_rng().gen_range(0.0..=1.0))
+}
+
+fn jittered(base_delay: Duration, attempt: u32, jitter: f64) -> Duration {
+    let ceiling = base_delay.saturating_mul(2u32.saturating_pow(attempt));
+    ceiling.mul_f64(0.5 + jitter.clamp(0.0, 1.0) / 2.0)
+}
+
+/// `Retry-After` holds either seconds or an HTTP date
+fn parse_retry_after(value: &str, now: chrono::DateTime<chrono::Utc>) -> Option<Duration> {
+    let value = value.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some((date.with_timezone(&chrono::Utc) - now).to_std().unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = chrono::DateTime::parse_from_rfc3339("2015-10-21T07:28:00Z").unwrap().with_timezone(&chrono::Utc);
+
+        assert_eq!(parse_retry_after("120", now), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now), Some(Duration::from_secs(30)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn test_backoff_is_jittered_and_doubles() {
+        let base = Duration::from_millis(100);
+        assert_eq!(jittered(base, 0, 0.0), Duration::from_millis(50));
+        assert_eq!(jittered(base, 0, 1.0), Duration::from_millis(100));
+        assert_eq!(jittered(base, 2, 1.0), Duration::from_millis(400));
+
+        for attempt in 0..4 {
+            let delay = backoff(base, attempt);
+            assert!(delay >= base * 2u32.pow(attempt) / 2 && delay <= base * 2u32.pow(attempt));
+        }
+    }
+
+    #[test]
+    fn test_config_defaults() {
+        let config: HttpConfig = serde_json::from_str(r#"{"read_timeout_secs": 10}"#).unwrap();
+        assert_eq!(config.read_timeout_secs, 10);
+        assert_eq!(config.max_retries, HttpConfig::default().max_retries);
+        assert!(config.user_agent().starts_with("tama/"));
+    }
+
+    #[test]
+    fn test_request_budget_covers_every_attempt() {
+        // 4 attempts of 5s to connect and 30s to read, then 0.5s + 1s + 2s of backoff
+        assert_eq!(HttpConfig::default().request_budget(), Duration::from_millis(143_500));
+
+        let config = HttpConfig { max_retries: 0, ..Default::default() };
+        assert_eq!(config.request_budget(), Duration::from_secs(35));
+    }
+
+    #[test]
+    fn test_invalid_settings_are_reported() {
+        assert!(HttpClient::new(&HttpConfig::default()).is_ok());
+
+        let config = HttpConfig { user_agent: Some("tama\n".to_string()), ..Default::default() };
+        assert!(HttpClient::new(&config).is_err());
+    }
+}
@@ -0,0 +1,74 @@
+use rsa::RsaPrivateKey;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::paths;
+use crate::signing;
+
+const PRIVATE_KEY_FILE: &str = "tama_signing_key.pem";
+
+pub fn private_key_path(keys_dir: &Path) -> PathBuf {
+    keys_dir.join(PRIVATE_KEY_FILE)
+}
+
+/// The channel's signing key, `None` if `tama auth` never created one
+pub fn load_private_key(keys_dir: &Path) -> Result<Option<RsaPrivateKey>, String> {
+    let path = private_key_path(keys_dir);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let pem = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read signing key: {e}"))?;
+
+    signing::private_key_from_pem(&pem).map(Some)
+}
+
+/// Loads the signing key, generating and saving one first if there is none.
+/// Returns whether the key was just created.
+pub fn load_or_generate_private_key(keys_dir: &Path) -> Result<(RsaPrivateKey, bool), String> {
+    if let Some(private_key) = load_private_key(keys_dir)? {
+        return Ok((private_key, false));
+    }
+
+    let private_key = signing::generate_private_key()?;
+    save_private_key(keys_dir, &private_key)?;
+    Ok((private_key, true))
+}
+
+fn save_private_key(keys_dir: &Path, private_key: &RsaPrivateKey) -> Result<(), String> {
+    paths::create_private_dir(keys_dir)
+        .map_err(|e| format!("Failed to create keys directory: {e}"))?;
+
+    let pem = signing::private_key_to_pem(private_key)?;
+
+    paths::write_private_file(&private_key_path(keys_dir), pem.as_bytes())
+        .map_err(|e| format!("Failed to write signing key: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_is_generated_once() {
+        let dir = std::env::temp_dir().join(format!("tama-keys-{}", uuid::Uuid::new_v4()));
+
+        assert!(load_private_key(&dir).unwrap().is_none());
+        let (created, is_new) = load_or_generate_private_key(&dir).unwrap();
+        assert!(is_new);
+
+        let (loaded, is_new) = load_or_generate_private_key(&dir).unwrap();
+        assert!(!is_new);
+        assert_eq!(loaded, created);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(private_key_path(&dir)).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
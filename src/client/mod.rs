@@ -1,11 +1,23 @@
 pub mod auth;
 pub mod auth_config;
+pub mod cache;
 pub mod config;
+pub mod federation;
+pub mod http;
+pub mod keys;
+pub mod paths;
+pub mod trusted_keys;
 
 use crate::api::{
-    AuthResponse, CreateContentRequest, CreateContentResponse, LoginRequest, RegisterRequest,
+    AcceptInviteRequest, AuthResponse, ChannelProfile, CommentInfo, CreateCommentRequest, CreateContentRequest,
+    CreateContentResponse, ContentProvenance, CreateInviteRequest, CreateWebhookRequest, InviteResponse, LoginRequest, MemberInfo, RegisterKeyRequest,
+    RegisterRequest, Role, SecurityEvent, UpdateProfileRequest, UserInfo, UserLoginRequest, UserRegisterRequest, WebhookDelivery,
+    WebhookInfo,
 };
+use crate::signing::Verification;
+use crate::signing;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ChannelInfo {
@@ -22,21 +34,75 @@ pub struct FeedItem {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ContentData {
     pub id: i64,
+    /// Empty when the server predates content names on the wire
+    #[serde(default)]
+    pub name: String,
     pub art: String,
     pub midi_composition: String,
     pub fps: f32,
+    #[serde(default)]
+    pub signature: Option<String>,
+    #[serde(default)]
+    pub public_key: Option<String>,
+    /// Set when the server mirrored the content from a peer
+    #[serde(default)]
+    pub origin: Option<ContentProvenance>,
+}
+
+impl ContentData {
+    /// Checks the signature against `trusted_key`, never against the `public_key` the server
+    /// sent along, see `TrustedKeys`
+    pub fn verification(&self, trusted_key: Option<&str>) -> Verification {
+        let Some(signature) = &self.signature else {
+            return Verification::Unsigned;
+        };
+        let Some(public_key) = trusted_key else {
+            return Verification::Untrusted;
+        };
+
+        match signing::public_key_from_pem(public_key) {
+            Ok(public_key) if signing::verify_content(&public_key, &self.art, &self.midi_composition, self.fps, signature) => {
+                Verification::Verified
+            }
+            _ => Verification::Invalid,
+        }
+    }
+}
+
+/// What the server's broadcast is airing right now
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BroadcastNow {
+    pub channel: ChannelInfo,
+    pub content: ContentData,
+    pub elapsed_ms: i64,
+    pub duration_ms: i64,
+}
+
+impl BroadcastNow {
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_millis(self.elapsed_ms.max(0) as u64)
+    }
+
+    /// Time left until the next content goes on air
+    pub fn remaining(&self) -> Duration {
+        Duration::from_millis((self.duration_ms - self.elapsed_ms).max(0) as u64)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ChannelResponse {
     pub id: i64,
     pub name: String,
+    #[serde(default)]
+    pub profile: ChannelProfile,
     pub contents: Vec<ContentData>,
 }
 
 pub struct ApiClient {
     base_url: String,
     session_token: Option<String>,
+    http: http::HttpClient,
+    cache: Option<cache::ContentCache>,
 }
 
 impl ApiClient {
@@ -44,6 +110,8 @@ impl ApiClient {
         Self {
             base_url,
             session_token: None,
+            http: http::shared(),
+            cache: None,
         }
     }
 
@@ -51,6 +119,8 @@ impl ApiClient {
         Self {
             base_url,
             session_token: Some(token),
+            http: http::shared(),
+            cache: None,
         }
     }
 
@@ -58,6 +128,21 @@ impl ApiClient {
         self.session_token = Some(token);
     }
 
+    /// Keeps a copy of every fetched content, for when no server can be reached
+    pub fn with_cache(mut self, cache: cache::ContentCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Caching is best effort, a full disk shouldn't stop playback
+    fn cache_contents(&self, channel: Option<&ChannelInfo>, contents: &[ContentData]) {
+        if let Some(cache) = &self.cache {
+            for content in contents {
+                let _ = cache.store(&self.base_url, channel, content);
+            }
+        }
+    }
+
     async fn handle_response<T: serde::de::DeserializeOwned>(
         response: reqwest::Response,
     ) -> Result<T, String> {
@@ -67,38 +152,50 @@ impl ApiClient {
             response.json::<T>().await
                 .map_err(|e| format!("Failed to parse response: {e}"))
         } else {
-            let error_text = response.text().await
-                .unwrap_or_else(|_| String::from("Unknown error"));
-
-            let error_msg = match status.as_u16() {
-                404 => format!("Channel not found - it may have been deleted or never existed. Server response: {error_text}"),
-                401 => format!("Authentication failed - invalid credentials or signature. Server response: {error_text}"),
-                403 => format!("Access forbidden. Server response: {error_text}"),
-                400 => format!("Bad request. Server response: {error_text}"),
-                500 => format!("Server error. Server response: {error_text}"),
-                _ => format!("Request failed with status: {status}. Server response: {error_text}"),
-            };
-
-            Err(error_msg)
+            Err(Self::error_message(response, "Channel").await)
+        }
+    }
+
+    /// `resource` names what a 404 couldn't find
+    async fn error_message(response: reqwest::Response, resource: &str) -> String {
+        let status = response.status();
+        let error_text = response.text().await
+            .unwrap_or_else(|_| String::from("Unknown error"));
+
+        match status.as_u16() {
+            404 => format!("{resource} not found - it may have been deleted or never existed. Server response: {error_text}"),
+            401 => format!("Authentication failed - invalid credentials or signature. Server response: {error_text}"),
+            403 => format!("Access forbidden. Server response: {error_text}"),
+            400 => format!("Bad request. Server response: {error_text}"),
+            429 => format!("Too many requests - try again later. Server response: {error_text}"),
+            500 => format!("Server error. Server response: {error_text}"),
+            _ => format!("Request failed with status: {status}. Server response: {error_text}"),
         }
     }
 
     pub async fn fetch_feed(&self) -> Result<Vec<FeedItem>, String> {
         let url = format!("{}/feed", self.base_url);
 
-        let response = reqwest::get(&url).await
+        let response = self.http.get(&url, None).await
             .map_err(|e| format!("Failed to fetch feed: {e}"))?;
 
-        Self::handle_response(response).await
+        let feed: Vec<FeedItem> = Self::handle_response(response).await?;
+        if let Some(cache) = &self.cache {
+            let _ = cache.store_feed(&self.base_url, &feed);
+        }
+        Ok(feed)
     }
 
     pub async fn fetch_channel(&self, channel_identifier: &str) -> Result<ChannelResponse, String> {
         let url = format!("{}/channel/{}", self.base_url, channel_identifier);
 
-        let response = reqwest::get(&url).await
+        let response = self.http.get(&url, None).await
             .map_err(|e| format!("Failed to fetch channel: {e}"))?;
 
-        Self::handle_response(response).await
+        let channel: ChannelResponse = Self::handle_response(response).await?;
+        let info = ChannelInfo { id: channel.id, name: channel.name.clone() };
+        self.cache_contents(Some(&info), &channel.contents);
+        Ok(channel)
     }
 
     pub async fn fetch_channel_by_id(&self, channel_id: i64) -> Result<ChannelResponse, String> {
@@ -108,10 +205,39 @@ impl ApiClient {
     pub async fn fetch_content(&self, content_id: i64) -> Result<ContentData, String> {
         let url = format!("{}/content/{}", self.base_url, content_id);
 
-        let response = reqwest::get(&url).await
+        let response = self.http.get(&url, None).await
             .map_err(|e| format!("Failed to fetch content: {e}"))?;
 
-        Self::handle_response(response).await
+        let content: ContentData = Self::handle_response(response).await?;
+        self.cache_contents(None, std::slice::from_ref(&content));
+        Ok(content)
+    }
+
+    /// Fetches an unlisted content through its share token
+    pub async fn fetch_shared_content(&self, share_token: &str) -> Result<ContentData, String> {
+        let url = format!("{}/share/{}", self.base_url, share_token);
+
+        let response = self.http.get(&url, None).await
+            .map_err(|e| format!("Failed to fetch shared content: {e}"))?;
+
+        let content: ContentData = Self::handle_response(response).await?;
+        self.cache_contents(None, std::slice::from_ref(&content));
+        Ok(content)
+    }
+
+    /// Downloads a content in the `--- MIDI --- / --- ART ---` file format
+    pub async fn download_content_file(&self, content_id: i64) -> Result<String, String> {
+        let url = format!("{}/content/{}.txt", self.base_url, content_id);
+
+        let response = self.http.get(&url, None).await
+            .map_err(|e| format!("Failed to download content: {e}"))?;
+
+        if !response.status().is_success() {
+            return Err(Self::error_message(response, "Content").await);
+        }
+
+        response.text().await
+            .map_err(|e| format!("Failed to read content file: {e}"))
     }
 
     pub async fn register(
@@ -125,8 +251,7 @@ impl ApiClient {
             password,
         };
 
-        let client = reqwest::Client::new();
-        let http_response = client
+        let http_response = self.http.client()
             .post(&url)
             .json(&request)
             .send().await
@@ -148,8 +273,7 @@ impl ApiClient {
             password,
         };
 
-        let client = reqwest::Client::new();
-        let http_response = client
+        let http_response = self.http.client()
             .post(&url)
             .json(&request)
             .send().await
@@ -171,8 +295,7 @@ impl ApiClient {
             password,
         };
 
-        let client = reqwest::Client::new();
-        let http_response = client
+        let http_response = self.http.client()
             .post(&url)
             .json(&request)
             .send().await
@@ -183,41 +306,271 @@ impl ApiClient {
         Ok(response)
     }
 
-    pub async fn upload_content(
-        &self,
-        channel_id: i64,
-        name: String,
-        art: String,
-        midi: String,
-        fps: f32,
-    ) -> Result<CreateContentResponse, String> {
+    pub async fn upload_content(&self, request: &CreateContentRequest) -> Result<CreateContentResponse, String> {
         let url = format!("{}/content", self.base_url);
-        let request = CreateContentRequest {
-            channel_id,
-            name,
-            art,
-            midi,
-            fps,
-        };
 
         let token = self.session_token.as_ref()
             .ok_or("No session token available. Please authenticate first.")?;
 
-        let client = reqwest::Client::new();
-        let response = client
+        let response = self.http.client()
             .post(&url)
             .header("Authorization", format!("Bearer {token}"))
-            .json(&request)
+            .json(request)
             .send().await
             .map_err(|e| format!("Failed to upload content: {e}"))?;
 
         Self::handle_response(response).await
     }
 
+    pub async fn update_profile(&self, request: &UpdateProfileRequest) -> Result<ChannelProfile, String> {
+        let url = format!("{}/channel/me", self.base_url);
+
+        let token = self.session_token.as_ref()
+            .ok_or("No session token available. Please authenticate first.")?;
+
+        let response = self.http.client()
+            .patch(&url)
+            .header("Authorization", format!("Bearer {token}"))
+            .json(request)
+            .send().await
+            .map_err(|e| format!("Failed to update profile: {e}"))?;
+
+        Self::handle_response(response).await
+    }
+
+    pub async fn register_user(&self, user_name: String, password: String) -> Result<UserInfo, String> {
+        let url = format!("{}/auth/users/register", self.base_url);
+        let request = UserRegisterRequest { user_name, password };
+
+        let response = self.http.client()
+            .post(&url)
+            .json(&request)
+            .send().await
+            .map_err(|e| format!("Failed to register user: {e}"))?;
+
+        Self::handle_response(response).await
+    }
+
+    /// Logs a user in to a channel they are a member of
+    pub async fn login_user(
+        &mut self,
+        user_name: String,
+        password: String,
+        channel_name: String,
+    ) -> Result<AuthResponse, String> {
+        let url = format!("{}/auth/users/login", self.base_url);
+        let request = UserLoginRequest { user_name, password, channel_name };
+
+        let http_response = self.http.client()
+            .post(&url)
+            .json(&request)
+            .send().await
+            .map_err(|e| format!("Failed to login: {e}"))?;
+
+        let response: AuthResponse = Self::handle_response(http_response).await?;
+        self.session_token = Some(response.token.clone());
+        Ok(response)
+    }
+
+    pub async fn create_invite(&self, role: Role) -> Result<InviteResponse, String> {
+        let url = format!("{}/channel/me/invites", self.base_url);
+
+        let token = self.session_token.as_ref()
+            .ok_or("No session token available. Please authenticate first.")?;
+
+        let response = self.http.client()
+            .post(&url)
+            .header("Authorization", format!("Bearer {token}"))
+            .json(&CreateInviteRequest { role })
+            .send().await
+            .map_err(|e| format!("Failed to create invite: {e}"))?;
+
+        Self::handle_response(response).await
+    }
+
+    /// Joins the channel of the invite and logs the user in to it
+    pub async fn accept_invite(
+        &mut self,
+        invite_token: &str,
+        user_name: String,
+        password: String,
+    ) -> Result<AuthResponse, String> {
+        let url = format!("{}/invites/{invite_token}/accept", self.base_url);
+        let request = AcceptInviteRequest { user_name, password };
+
+        let http_response = self.http.client()
+            .post(&url)
+            .json(&request)
+            .send().await
+            .map_err(|e| format!("Failed to accept invite: {e}"))?;
+
+        let response: AuthResponse = Self::handle_response(http_response).await?;
+        self.session_token = Some(response.token.clone());
+        Ok(response)
+    }
+
+    pub async fn list_members(&self) -> Result<Vec<MemberInfo>, String> {
+        let url = format!("{}/channel/me/members", self.base_url);
+
+        let token = self.session_token.as_ref()
+            .ok_or("No session token available. Please authenticate first.")?;
+
+        let response = self.http.get(&url, Some(token.as_str())).await
+            .map_err(|e| format!("Failed to fetch members: {e}"))?;
+
+        Self::handle_response(response).await
+    }
+
+    /// Recent logins, failures and lockouts of the authenticated channel, newest first
+    pub async fn fetch_security_log(&self, limit: i64) -> Result<Vec<SecurityEvent>, String> {
+        let url = format!("{}/channel/me/security-log?limit={limit}", self.base_url);
+
+        let token = self.session_token.as_ref()
+            .ok_or("No session token available. Please authenticate first.")?;
+
+        let response = self.http.get(&url, Some(token.as_str())).await
+            .map_err(|e| format!("Failed to fetch security log: {e}"))?;
+
+        Self::handle_response(response).await
+    }
+
+    /// Registers the key the channel's uploads are signed with
+    pub async fn register_key(&self, public_key: String) -> Result<(), String> {
+        let url = format!("{}/channel/me/key", self.base_url);
+
+        let token = self.session_token.as_ref()
+            .ok_or("No session token available. Please authenticate first.")?;
+
+        let response = self.http.client()
+            .put(&url)
+            .header("Authorization", format!("Bearer {token}"))
+            .json(&RegisterKeyRequest { public_key })
+            .send().await
+            .map_err(|e| format!("Failed to register key: {e}"))?;
+
+        if !response.status().is_success() {
+            return Err(Self::error_message(response, "Channel").await);
+        }
+        Ok(())
+    }
+
+    /// Most recent comments first. The session token, if any, is sent along so members
+    /// can read the comments of their channel's hidden contents.
+    pub async fn fetch_comments(&self, content_id: i64, limit: i64) -> Result<Vec<CommentInfo>, String> {
+        let url = format!("{}/content/{content_id}/comments?limit={limit}", self.base_url);
+
+        let response = self.http.get(&url, self.session_token.as_deref()).await
+            .map_err(|e| format!("Failed to fetch comments: {e}"))?;
+
+        Self::handle_response(response).await
+    }
+
+    pub async fn post_comment(&self, content_id: i64, body: String) -> Result<CommentInfo, String> {
+        let url = format!("{}/content/{content_id}/comments", self.base_url);
+
+        let token = self.session_token.as_ref()
+            .ok_or("No session token available. Please authenticate first.")?;
+
+        let response = self.http.client()
+            .post(&url)
+            .header("Authorization", format!("Bearer {token}"))
+            .json(&CreateCommentRequest { body })
+            .send().await
+            .map_err(|e| format!("Failed to post comment: {e}"))?;
+
+        Self::handle_response(response).await
+    }
+
+    pub async fn delete_comment(&self, content_id: i64, comment_id: i64) -> Result<(), String> {
+        let url = format!("{}/content/{content_id}/comments/{comment_id}", self.base_url);
+
+        let token = self.session_token.as_ref()
+            .ok_or("No session token available. Please authenticate first.")?;
+
+        let response = self.http.client()
+            .delete(&url)
+            .header("Authorization", format!("Bearer {token}"))
+            .send().await
+            .map_err(|e| format!("Failed to delete comment: {e}"))?;
+
+        if !response.status().is_success() {
+            return Err(Self::error_message(response, "Comment").await);
+        }
+        Ok(())
+    }
+
+    pub async fn create_webhook(&self, request: &CreateWebhookRequest) -> Result<WebhookInfo, String> {
+        let url = format!("{}/channel/me/webhooks", self.base_url);
+
+        let token = self.session_token.as_ref()
+            .ok_or("No session token available. Please authenticate first.")?;
+
+        let response = self.http.client()
+            .post(&url)
+            .header("Authorization", format!("Bearer {token}"))
+            .json(request)
+            .send().await
+            .map_err(|e| format!("Failed to create webhook: {e}"))?;
+
+        Self::handle_response(response).await
+    }
+
+    pub async fn list_webhooks(&self) -> Result<Vec<WebhookInfo>, String> {
+        let url = format!("{}/channel/me/webhooks", self.base_url);
+
+        let token = self.session_token.as_ref()
+            .ok_or("No session token available. Please authenticate first.")?;
+
+        let response = self.http.get(&url, Some(token.as_str())).await
+            .map_err(|e| format!("Failed to fetch webhooks: {e}"))?;
+
+        Self::handle_response(response).await
+    }
+
+    pub async fn delete_webhook(&self, webhook_id: i64) -> Result<(), String> {
+        let url = format!("{}/channel/me/webhooks/{webhook_id}", self.base_url);
+
+        let token = self.session_token.as_ref()
+            .ok_or("No session token available. Please authenticate first.")?;
+
+        let response = self.http.client()
+            .delete(&url)
+            .header("Authorization", format!("Bearer {token}"))
+            .send().await
+            .map_err(|e| format!("Failed to delete webhook: {e}"))?;
+
+        if !response.status().is_success() {
+            return Err(Self::error_message(response, "Webhook").await);
+        }
+        Ok(())
+    }
+
+    /// Recent webhook deliveries of the authenticated channel, newest first
+    pub async fn fetch_webhook_deliveries(&self, limit: i64) -> Result<Vec<WebhookDelivery>, String> {
+        let url = format!("{}/channel/me/webhooks/deliveries?limit={limit}", self.base_url);
+
+        let token = self.session_token.as_ref()
+            .ok_or("No session token available. Please authenticate first.")?;
+
+        let response = self.http.get(&url, Some(token.as_str())).await
+            .map_err(|e| format!("Failed to fetch webhook deliveries: {e}"))?;
+
+        Self::handle_response(response).await
+    }
+
+    pub async fn fetch_broadcast_now(&self) -> Result<BroadcastNow, String> {
+        let url = format!("{}/broadcast/now", self.base_url);
+
+        let response = self.http.get(&url, None).await
+            .map_err(|e| format!("Failed to fetch broadcast: {e}"))?;
+
+        Self::handle_response(response).await
+    }
+
     pub async fn fetch_servers(&self) -> Result<Vec<String>, String> {
         let url = format!("{}/servers", self.base_url);
 
-        let response = reqwest::get(&url).await
+        let response = self.http.get(&url, None).await
             .map_err(|e| format!("Failed to fetch servers: {e}"))?;
 
         Self::handle_response(response).await
@@ -228,9 +581,31 @@ impl ApiClient {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_broadcast_now_timing() {
+        let json = r#"{"channel":{"id":1,"name":"neko"},"content":{"id":2,"art":"","midi_composition":"4c","fps":10.0},"elapsed_ms":1500,"duration_ms":20000}"#;
+        let now_playing: BroadcastNow = serde_json::from_str(json).unwrap();
+        assert_eq!(now_playing.elapsed(), Duration::from_millis(1500));
+        assert_eq!(now_playing.remaining(), Duration::from_millis(18500));
+    }
+
     #[test]
     fn test_api_client_creation() {
         let client = ApiClient::new("http://localhost:3000".to_string());
         assert_eq!(client.base_url, "http://localhost:3000");
     }
+
+    #[test]
+    fn test_content_without_signature_is_unsigned() {
+        let content: ContentData = serde_json::from_str(r#"{"id":1,"art":"a","midi_composition":"4c","fps":10.0}"#).unwrap();
+        assert_eq!(content.verification(None), Verification::Unsigned);
+
+        let forged = ContentData {
+            signature: Some("c2lnbmF0dXJl".to_string()),
+            public_key: Some("not a key".to_string()),
+            ..content
+        };
+        assert_eq!(forged.verification(None), Verification::Untrusted);
+        assert_eq!(forged.verification(Some("not a key")), Verification::Invalid);
+    }
 }
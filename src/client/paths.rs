@@ -0,0 +1,166 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Puts config, auth, signing key and cache all in one directory
+pub const CONFIG_DIR_VAR: &str = "TAMA_CONFIG_DIR";
+
+/// Where the client keeps its files, following the XDG base directory spec
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dirs {
+    /// `config.json`
+    pub config: PathBuf,
+    /// `auth.json` and the signing key
+    pub data: PathBuf,
+    /// Offline content cache
+    pub cache: PathBuf,
+}
+
+impl Dirs {
+    pub fn from_env() -> Self {
+        Self::resolve(|name| std::env::var(name).ok())
+    }
+
+    fn resolve(var: impl Fn(&str) -> Option<String>) -> Self {
+        let var = |name: &str| var(name).filter(|value| !value.is_empty());
+
+        if let Some(dir) = var(CONFIG_DIR_VAR) {
+            let dir = PathBuf::from(dir);
+            return Self {
+                config: dir.clone(),
+                data: dir.clone(),
+                cache: dir.join("cache"),
+            };
+        }
+
+        let home = var("HOME").map(PathBuf::from);
+        // Relative XDG paths are invalid per the spec and get ignored
+        let base = |xdg_var: &str, home_fallback: &str| {
+            var(xdg_var)
+                .map(PathBuf::from)
+                .filter(|path| path.is_absolute())
+                .or_else(|| home.as_ref().map(|home| home.join(home_fallback)))
+                .or_else(|| var("APPDATA").map(PathBuf::from))
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("tama")
+        };
+
+        Self {
+            config: base("XDG_CONFIG_HOME", ".config"),
+            data: base("XDG_DATA_HOME", ".local/share"),
+            cache: base("XDG_CACHE_HOME", ".cache"),
+        }
+    }
+}
+
+/// Creates `dir` readable by its owner only, leaving existing directories as they are
+pub fn create_private_dir(dir: &Path) -> std::io::Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder.create(dir)
+}
+
+/// Writes `contents` to `path` with 0600 permissions, tightening them if the file already existed
+pub fn write_private_file(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options.open(path)?;
+    restrict_permissions(path)?;
+    std::io::Write::write_all(&mut file, contents)
+}
+
+/// Makes `path` readable and writable by its owner only
+pub fn restrict_permissions(path: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+/// Moves a file left in the working directory by older versions to its new home.
+/// Returns whether anything was moved; files already at the new location win.
+pub fn migrate_file(legacy: &Path, new: &Path) -> Result<bool, String> {
+    if !legacy.is_file() || new.exists() {
+        return Ok(false);
+    }
+
+    if let Some(parent) = new.parent() {
+        create_private_dir(parent)
+            .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+    }
+
+    // Renaming fails across file systems, so fall back to copying
+    if fs::rename(legacy, new).is_err() {
+        fs::copy(legacy, new)
+            .map_err(|e| format!("Failed to copy {} to {}: {e}", legacy.display(), new.display()))?;
+        fs::remove_file(legacy)
+            .map_err(|e| format!("Failed to remove {}: {e}", legacy.display()))?;
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn resolve(vars: &[(&str, &str)]) -> Dirs {
+        let vars: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        Dirs::resolve(|name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn test_xdg_dirs() {
+        let dirs = resolve(&[("HOME", "/home/neko")]);
+        assert_eq!(dirs.config, PathBuf::from("/home/neko/.config/tama"));
+        assert_eq!(dirs.data, PathBuf::from("/home/neko/.local/share/tama"));
+        assert_eq!(dirs.cache, PathBuf::from("/home/neko/.cache/tama"));
+
+        let dirs = resolve(&[("HOME", "/home/neko"), ("XDG_CONFIG_HOME", "/etc/xdg"), ("XDG_DATA_HOME", "relative")]);
+        assert_eq!(dirs.config, PathBuf::from("/etc/xdg/tama"));
+        assert_eq!(dirs.data, PathBuf::from("/home/neko/.local/share/tama"));
+
+        let dirs = resolve(&[("HOME", "/home/neko"), (CONFIG_DIR_VAR, "/srv/tama")]);
+        assert_eq!(dirs.config, PathBuf::from("/srv/tama"));
+        assert_eq!(dirs.data, PathBuf::from("/srv/tama"));
+        assert_eq!(dirs.cache, PathBuf::from("/srv/tama/cache"));
+
+        assert_eq!(resolve(&[(CONFIG_DIR_VAR, "")]).config, PathBuf::from("./tama"));
+    }
+
+    #[test]
+    fn test_migrate_file() {
+        let dir = std::env::temp_dir().join(format!("tama-paths-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let legacy = dir.join("auth.json");
+        let new = dir.join("data").join("tama").join("auth.json");
+
+        assert!(!migrate_file(&legacy, &new).unwrap());
+
+        fs::write(&legacy, "old").unwrap();
+        assert!(migrate_file(&legacy, &new).unwrap());
+        assert!(!legacy.exists());
+        assert_eq!(fs::read_to_string(&new).unwrap(), "old");
+
+        // Never overwrite what is already at the new location
+        fs::write(&legacy, "older").unwrap();
+        assert!(!migrate_file(&legacy, &new).unwrap());
+        assert_eq!(fs::read_to_string(&new).unwrap(), "old");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
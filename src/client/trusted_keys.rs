@@ -0,0 +1,164 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::paths::{self, Dirs};
+use super::ContentData;
+use crate::signing::Verification;
+
+/// Signing keys of other servers' channels, stored in `trusted_keys.json`.
+///
+/// Servers send a channel's public key along with its contents, so any peer could re-sign
+/// a channel with a key of its own. Each channel's key is pinned the first time one of its
+/// contents checks out, and later contents are only verified against the pinned key.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct TrustedKeys {
+    /// Public key PEM by `channel@origin server`
+    keys: BTreeMap<String, String>,
+}
+
+impl TrustedKeys {
+    pub fn default_path() -> PathBuf {
+        Dirs::from_env().data.join("trusted_keys.json")
+    }
+
+    /// No keys if there is no file yet
+    pub fn load() -> Result<Self, String> {
+        Self::load_from_path(&Self::default_path())
+    }
+
+    pub fn load_from_path(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read trusted keys: {e}"))?;
+
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse trusted keys: {e}"))
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        self.save_to_path(&Self::default_path())
+    }
+
+    pub fn save_to_path(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            paths::create_private_dir(dir)
+                .map_err(|e| format!("Failed to create data directory: {e}"))?;
+        }
+
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize trusted keys: {e}"))?;
+
+        paths::write_private_file(path, content.as_bytes())
+            .map_err(|e| format!("Failed to write trusted keys: {e}"))
+    }
+
+    /// Checks a content `server_url` served as part of `channel_name`, pinning the channel's key
+    /// on first sight. Contents fetched on their own have no channel to pin a key to.
+    pub fn verify(&mut self, server_url: &str, channel_name: Option<&str>, content: &ContentData) -> Verification {
+        if content.signature.is_none() {
+            return Verification::Unsigned;
+        }
+        let Some(channel_name) = channel_name else {
+            return Verification::Untrusted;
+        };
+
+        let key = channel_key(server_url, channel_name, content);
+        if let Some(pinned) = self.keys.get(&key) {
+            return content.verification(Some(pinned));
+        }
+
+        let Some(public_key) = &content.public_key else {
+            return Verification::Untrusted;
+        };
+        let verification = content.verification(Some(public_key));
+        if verification.is_verified() {
+            self.keys.insert(key, public_key.clone());
+        }
+        verification
+    }
+}
+
+/// `channel@origin server`. Mirrors list a channel as `channel@host`, their contents
+/// point back to the server the channel lives on.
+fn channel_key(server_url: &str, channel_name: &str, content: &ContentData) -> String {
+    match &content.origin {
+        Some(origin) => {
+            let name = channel_name.rsplit_once('@').map_or(channel_name, |(name, _)| name);
+            format!("{name}@{}", origin.server_url.trim_end_matches('/'))
+        }
+        None => format!("{channel_name}@{}", server_url.trim_end_matches('/')),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::ContentProvenance;
+    use crate::signing;
+
+    fn signed(key: &rsa::RsaPrivateKey) -> ContentData {
+        let (art, midi_composition, fps) = ("⠁⠁", "4c", 10.0);
+        ContentData {
+            id: 1,
+            name: String::new(),
+            art: art.to_string(),
+            midi_composition: midi_composition.to_string(),
+            fps,
+            signature: Some(signing::sign_content(key, art, midi_composition, fps).unwrap()),
+            public_key: Some(signing::public_key_to_pem(key).unwrap()),
+            origin: None,
+        }
+    }
+
+    #[test]
+    fn test_key_is_pinned_on_first_sight() {
+        let owner = signing::generate_private_key().unwrap();
+        let impostor = signing::generate_private_key().unwrap();
+        let mut keys = TrustedKeys::default();
+
+        assert_eq!(keys.verify("https://a.example", Some("neko"), &signed(&owner)), Verification::Verified);
+
+        // A peer re-signing the channel with its own key, and sending that key along
+        assert_eq!(keys.verify("https://a.example/", Some("neko"), &signed(&impostor)), Verification::Invalid);
+        assert_eq!(keys.verify("https://a.example", Some("neko"), &signed(&owner)), Verification::Verified);
+
+        assert_eq!(keys.verify("https://a.example", None, &signed(&owner)), Verification::Untrusted);
+        let unsigned = ContentData { signature: None, ..signed(&owner) };
+        assert_eq!(keys.verify("https://a.example", Some("neko"), &unsigned), Verification::Unsigned);
+    }
+
+    #[test]
+    fn test_mirrored_contents_use_the_origin_channel_key() {
+        let owner = signing::generate_private_key().unwrap();
+        let impostor = signing::generate_private_key().unwrap();
+        let mut keys = TrustedKeys::default();
+        keys.verify("https://a.example", Some("neko"), &signed(&owner));
+
+        let origin = Some(ContentProvenance { server_url: "https://a.example".to_string(), content_id: 1 });
+        let mirrored = ContentData { origin: origin.clone(), ..signed(&owner) };
+        assert_eq!(keys.verify("https://b.example", Some("neko@a.example"), &mirrored), Verification::Verified);
+
+        let forged = ContentData { origin, ..signed(&impostor) };
+        assert_eq!(keys.verify("https://b.example", Some("neko@a.example"), &forged), Verification::Invalid);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir().join(format!("tama-trusted-keys-{}.json", uuid::Uuid::new_v4()));
+        let owner = signing::generate_private_key().unwrap();
+
+        assert_eq!(TrustedKeys::load_from_path(&path).unwrap(), TrustedKeys::default());
+
+        let mut keys = TrustedKeys::default();
+        keys.verify("https://a.example", Some("neko"), &signed(&owner));
+        keys.save_to_path(&path).unwrap();
+        assert_eq!(TrustedKeys::load_from_path(&path).unwrap(), keys);
+
+        fs::remove_file(&path).ok();
+    }
+}
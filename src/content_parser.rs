@@ -44,6 +44,47 @@ fn parse_fps_from_header(header: &str) -> f32 {
     10.0
 }
 
+/// `header` as is if it already carries `fps`, otherwise rewritten to carry it,
+/// e.g. `Ascii Art Animation, 16x11, 5fps`
+fn header_with_fps(header: &str, fps: f32) -> String {
+    if parse_fps_from_header(header) == fps {
+        return header.to_string();
+    }
+
+    let dimensions = header
+        .strip_prefix("Ascii Art Animation")
+        .unwrap_or(header)
+        .trim_start_matches([',', ' '])
+        .split(',')
+        .next()
+        .unwrap_or("")
+        .trim();
+
+    format!("Ascii Art Animation, {dimensions}, {fps}fps")
+}
+
+/// Serializes content into the `--- MIDI --- / --- ART ---` format read by `parse_content`.
+/// The fields are written as they are, only the art header is rewritten if it doesn't carry `fps`.
+pub fn serialize_content(content: &ContentFile) -> String {
+    let (header, frames) = match content.art.split_once('\n') {
+        Some((header, frames)) => (header, Some(frames)),
+        None => (content.art.as_str(), None),
+    };
+
+    let mut output = String::new();
+    output.push_str("--- MIDI ---\n");
+    output.push_str(&content.midi_composition);
+    output.push_str("\n--- ART ---\n");
+    output.push_str(&header_with_fps(header, content.fps));
+    if let Some(frames) = frames {
+        output.push('\n');
+        output.push_str(frames);
+    }
+    output.push('\n');
+
+    output
+}
+
 pub fn parse_content_file(path: &str) -> Result<ContentFile, ParseError> {
     let content = fs::read_to_string(path)?;
     parse_content(&content)
@@ -208,6 +249,66 @@ Ascii Art Animation, 16x11
         assert_eq!(parsed.fps, 10.0);
     }
 
+    #[test]
+    fn test_serialize_round_trip() {
+        let content = ContentFile {
+            midi_composition: "8c4t 8e4t 8g4t".to_string(),
+            art: "Ascii Art Animation, 4x2\n⠁⠁⠁⠁\n⠂⠂⠂⠂".to_string(),
+            fps: 7.5,
+        };
+
+        let serialized = serialize_content(&content);
+        assert!(serialized.contains("Ascii Art Animation, 4x2, 7.5fps"));
+
+        let parsed = parse_content(&serialized).unwrap();
+        assert_eq!(parsed.midi_composition, content.midi_composition);
+        assert_eq!(parsed.fps, 7.5);
+        assert_eq!(parsed.art, "Ascii Art Animation, 4x2, 7.5fps\n⠁⠁⠁⠁\n⠂⠂⠂⠂");
+    }
+
+    #[test]
+    fn test_serialize_replaces_existing_fps() {
+        let content = ContentFile {
+            midi_composition: "4c".to_string(),
+            art: "Ascii Art Animation, 4x1, 2fps\n⠁⠁⠁⠁".to_string(),
+            fps: 12.0,
+        };
+
+        let parsed = parse_content(&serialize_content(&content)).unwrap();
+        assert_eq!(parsed.fps, 12.0);
+        assert!(parsed.art.starts_with("Ascii Art Animation, 4x1, 12fps\n"));
+    }
+
+    #[test]
+    fn test_parse_serialize_parse_is_lossless() {
+        let files = [
+            "--- MIDI ---\n  8c4t 8e4t  \n\n  8g4t\n--- ART ---\nAscii Art Animation, 4x1, 5fps\n⠁⠁⠁⠁  \n\n",
+            "--- MIDI ---\n4c\n--- ART ---\nAscii Art Animation 4x1\n⠁⠁⠁⠁",
+            "--- MIDI ---\n4c\n--- ART ---\nAscii Art Animation,4x1,3fps\n⠁⠁⠁⠁\n",
+        ];
+
+        for file in files {
+            let parsed = parse_content(file).unwrap();
+            let reparsed = parse_content(&serialize_content(&parsed)).unwrap();
+            assert_eq!(reparsed.midi_composition, parsed.midi_composition);
+            assert_eq!(reparsed.art, parsed.art);
+            assert_eq!(reparsed.fps, parsed.fps);
+        }
+    }
+
+    #[test]
+    fn test_serialize_adds_fps_to_headers_without_prefix() {
+        let content = ContentFile {
+            midi_composition: "4c".to_string(),
+            art: "Ascii Art Animation 4x1\n⠁⠁⠁⠁\n".to_string(),
+            fps: 5.0,
+        };
+
+        let parsed = parse_content(&serialize_content(&content)).unwrap();
+        assert_eq!(parsed.fps, 5.0);
+        assert_eq!(parsed.art, "Ascii Art Animation, 4x1, 5fps\n⠁⠁⠁⠁\n");
+    }
+
     #[test]
     fn test_parse_fps_with_trailing_characters() {
         let content = r#"--- MIDI ---
@@ -1,8 +1,13 @@
 pub mod api;
 pub mod ascii_art_converter;
+#[cfg(feature = "audio")]
 pub mod audio;
+#[cfg(feature = "tui")]
 pub mod channel;
+#[cfg(feature = "client")]
 pub mod client;
 pub mod content_parser;
 pub mod midi_composer;
+pub mod signing;
+#[cfg(feature = "tui")]
 pub mod ui;
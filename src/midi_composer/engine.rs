@@ -1,4 +1,7 @@
+#[cfg(feature = "audio")]
 use rodio::{buffer::SamplesBuffer, OutputStream, OutputStreamHandle, Sink, Source};
+#[cfg(feature = "audio")]
+use std::time::Duration;
 
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub enum Waveform {
@@ -307,24 +310,42 @@ impl MidiParser {
     }
 }
 
-pub struct MidiEngine {
+pub const SAMPLE_RATE: u32 = 48000;
+
+/// Seconds the channels take when played together
+pub fn channels_duration(channels: &[Vec<Note>]) -> f32 {
+    channels
+        .iter()
+        .map(|notes| notes.iter().map(|note| note.duration).sum::<f32>())
+        .fold(0.0_f32, f32::max)
+}
+
+#[cfg(feature = "audio")]
+struct AudioOutput {
     _stream: OutputStream,
     stream_handle: OutputStreamHandle,
+}
+
+/// Parses and synthesizes compositions. Playing them needs the `audio` feature.
+pub struct MidiEngine {
+    #[cfg(feature = "audio")]
+    output: Option<AudioOutput>,
     bpm: u16,
+    #[cfg(feature = "audio")]
     current_sink: Option<Sink>,
 }
 
 impl MidiEngine {
-    pub fn new(bpm: u16) -> Result<Self, String> {
-        let (stream, stream_handle) = OutputStream::try_default()
-            .map_err(|e| format!("Failed to create audio output stream: {e}"))?;
-
-        Ok(Self {
-            _stream: stream,
-            stream_handle,
+    /// Engine that can parse and synthesize compositions but not play them,
+    /// for rendering to files on machines without an audio device.
+    pub fn without_audio(bpm: u16) -> Self {
+        Self {
+            #[cfg(feature = "audio")]
+            output: None,
             bpm,
+            #[cfg(feature = "audio")]
             current_sink: None,
-        })
+        }
     }
 
     /// Validates MIDI composition syntax without requiring audio output.
@@ -335,6 +356,13 @@ impl MidiEngine {
         parser.parse_notes(input)
     }
 
+    /// Seconds a composition lasts when played once, the length of its longest channel
+    pub fn composition_duration(input: &str) -> Result<f32, String> {
+        let mut engine = MidiEngine::without_audio(120);
+        let channels = engine.parse_composition(input)?;
+        Ok(channels_duration(&channels))
+    }
+
     pub fn parse_note(&self, input: &str) -> Result<Note, String> {
         let input = input.trim();
         if input.is_empty() {
@@ -658,54 +686,6 @@ impl MidiEngine {
         440.0 * 2.0_f32.powf((midi_note as f32 - 69.0) / 12.0)
     }
 
-    pub fn play_notes(&mut self, notes: &[Note]) -> Result<(), String> {
-        let sample_rate = 48000_u32;
-        let mut all_samples = Vec::new();
-
-        for note in notes {
-            let note_samples = self.generate_note_samples(note);
-            all_samples.extend(note_samples);
-        }
-
-        let source = SamplesBuffer::new(1, sample_rate, all_samples);
-        let sink = Sink::try_new(&self.stream_handle)
-            .map_err(|e| format!("Failed to create audio sink: {e}"))?;
-
-        sink.append(source);
-        sink.sleep_until_end();
-
-        Ok(())
-    }
-
-    pub fn play_notes_looping(&mut self, notes: &[Note]) -> Result<(), String> {
-        self.stop();
-
-        let sample_rate = 48000_u32;
-        let mut all_samples = Vec::new();
-
-        for note in notes {
-            let note_samples = self.generate_note_samples(note);
-            all_samples.extend(note_samples);
-        }
-
-        let source = SamplesBuffer::new(1, sample_rate, all_samples);
-        let looping_source = source.repeat_infinite();
-
-        let sink = Sink::try_new(&self.stream_handle)
-            .map_err(|e| format!("Failed to create audio sink: {e}"))?;
-
-        sink.append(looping_source);
-        self.current_sink = Some(sink);
-
-        Ok(())
-    }
-
-    pub fn stop(&mut self) {
-        if let Some(sink) = self.current_sink.take() {
-            sink.stop();
-        }
-    }
-
     fn apply_adsr_envelope(sample_index: usize, total_samples: usize) -> f32 {
         let t = sample_index as f32 / total_samples as f32;
 
@@ -741,8 +721,32 @@ impl MidiEngine {
         frequency * (1.0 + vibrato_offset)
     }
 
+    /// Samples of `notes` played one after the other
+    pub fn generate_samples(&self, notes: &[Note]) -> Vec<f32> {
+        notes.iter().flat_map(|note| self.generate_note_samples(note)).collect()
+    }
+
+    /// Samples of all channels played together, mixed down to mono
+    pub fn mix_channels(&self, channels: &[Vec<Note>]) -> Vec<f32> {
+        let channel_samples: Vec<Vec<f32>> = channels
+            .iter()
+            .map(|notes| self.generate_samples(notes))
+            .collect();
+        let max_length = channel_samples.iter().map(Vec::len).max().unwrap_or(0);
+
+        let mut mixed_samples = vec![0.0_f32; max_length];
+
+        for channel_buffer in &channel_samples {
+            for (i, &sample) in channel_buffer.iter().enumerate() {
+                mixed_samples[i] += sample;
+            }
+        }
+
+        mixed_samples
+    }
+
     pub fn generate_note_samples(&self, note: &Note) -> Vec<f32> {
-        let sample_rate = 48000;
+        let sample_rate = SAMPLE_RATE;
 
         if !note.arpeggio_notes.is_empty() {
             let mut all_samples = Vec::new();
@@ -787,7 +791,7 @@ impl MidiEngine {
         adsr: bool,
         vibrato: bool,
     ) -> Vec<f32> {
-        let sample_rate = 48000;
+        let sample_rate = SAMPLE_RATE;
         let num_samples = (sample_rate as f32 * duration) as usize;
         let amplitude = 0.2 * volume;
 
@@ -841,38 +845,60 @@ impl MidiEngine {
             .collect()
     }
 
-    pub fn play_channels(&mut self, channels: &[Vec<Note>]) -> Result<(), String> {
-        if channels.is_empty() {
-            return Err("No channels to play".to_string());
+    /// Parses a full composition, including `--bpm`, `--volume`, `--adsr`, `--vibrato`
+    /// and `--channel` flags, into one list of notes per channel.
+    /// A `--bpm` flag changes the tempo of this engine.
+    pub fn parse_composition(&mut self, input: &str) -> Result<Vec<Vec<Note>>, String> {
+        use crate::midi_composer::channels::parse_channels;
+
+        let (channels, bpm) = parse_channels(input)?;
+
+        if let Some(bpm_val) = bpm {
+            self.bpm = bpm_val;
         }
 
-        let sample_rate = 48000_u32;
-        let mut channel_samples: Vec<Vec<f32>> = Vec::new();
-        let mut max_length = 0;
+        channels
+            .iter()
+            .map(|channel| {
+                let mut notes = self.parse_notes(&channel.composition)?;
 
-        for channel_notes in channels {
-            let mut channel_buffer = Vec::new();
+                for note in &mut notes {
+                    if channel.volume.is_some() && note.volume == 1.0 {
+                        note.volume = channel.volume.unwrap_or(1.0);
+                    }
+                    note.adsr = channel.adsr;
+                    note.vibrato = channel.vibrato;
+                }
 
-            for note in channel_notes {
-                let note_samples = self.generate_note_samples(note);
-                channel_buffer.extend(note_samples);
-            }
+                Ok(notes)
+            })
+            .collect()
+    }
+}
 
-            max_length = max_length.max(channel_buffer.len());
-            channel_samples.push(channel_buffer);
-        }
+/// Playback through the default output device
+#[cfg(feature = "audio")]
+impl MidiEngine {
+    pub fn new(bpm: u16) -> Result<Self, String> {
+        let (stream, stream_handle) = OutputStream::try_default()
+            .map_err(|e| format!("Failed to create audio output stream: {e}"))?;
 
-        let mut mixed_samples = vec![0.0_f32; max_length];
+        Ok(Self {
+            output: Some(AudioOutput { _stream: stream, stream_handle }),
+            bpm,
+            current_sink: None,
+        })
+    }
 
-        for channel_buffer in &channel_samples {
-            for (i, &sample) in channel_buffer.iter().enumerate() {
-                mixed_samples[i] += sample;
-            }
-        }
+    fn new_sink(&self) -> Result<Sink, String> {
+        let output = self.output.as_ref().ok_or("Audio output is not available")?;
+        Sink::try_new(&output.stream_handle)
+            .map_err(|e| format!("Failed to create audio sink: {e}"))
+    }
 
-        let source = SamplesBuffer::new(1, sample_rate, mixed_samples);
-        let sink = Sink::try_new(&self.stream_handle)
-            .map_err(|e| format!("Failed to create audio sink: {e}"))?;
+    pub fn play_notes(&mut self, notes: &[Note]) -> Result<(), String> {
+        let source = SamplesBuffer::new(1, SAMPLE_RATE, self.generate_samples(notes));
+        let sink = self.new_sink()?;
 
         sink.append(source);
         sink.sleep_until_end();
@@ -880,75 +906,74 @@ impl MidiEngine {
         Ok(())
     }
 
-    pub fn parse_and_play(&mut self, input: &str) -> Result<(), String> {
-        use crate::midi_composer::channels::parse_channels;
+    pub fn play_notes_looping(&mut self, notes: &[Note]) -> Result<(), String> {
+        self.play_notes_looping_from(notes, Duration::ZERO)
+    }
 
-        let (channels, bpm) = parse_channels(input)?;
+    /// Loops `notes` starting `offset` into the loop, as if playback had started `offset` ago
+    pub fn play_notes_looping_from(&mut self, notes: &[Note], offset: Duration) -> Result<(), String> {
+        self.stop();
 
-        if let Some(bpm_val) = bpm {
-            *self = Self::new(bpm_val)?;
+        let mut samples = self.generate_samples(notes);
+        if !samples.is_empty() {
+            let skipped = (offset.as_secs_f64() * SAMPLE_RATE as f64) as usize % samples.len();
+            samples.rotate_left(skipped);
         }
 
-        if channels.len() == 1 && !input.contains("--channel") {
-            let channel = &channels[0];
-            let mut notes = self.parse_notes(&channel.composition)?;
-
-            for note in &mut notes {
-                if channel.volume.is_some() && note.volume == 1.0 {
-                    note.volume = channel.volume.unwrap_or(1.0);
-                }
-                note.adsr = channel.adsr;
-                note.vibrato = channel.vibrato;
-            }
+        let source = SamplesBuffer::new(1, SAMPLE_RATE, samples);
+        let looping_source = source.repeat_infinite();
 
-            self.play_notes(&notes)
-        } else {
-            let mut all_channel_notes = Vec::new();
+        let sink = self.new_sink()?;
 
-            for channel in &channels {
-                let mut notes = self.parse_notes(&channel.composition)?;
+        sink.append(looping_source);
+        self.current_sink = Some(sink);
 
-                for note in &mut notes {
-                    if channel.volume.is_some() && note.volume == 1.0 {
-                        note.volume = channel.volume.unwrap_or(1.0);
-                    }
-                    note.adsr = channel.adsr;
-                    note.vibrato = channel.vibrato;
-                }
-                all_channel_notes.push(notes);
-            }
+        Ok(())
+    }
 
-            self.play_channels(&all_channel_notes)
+    pub fn stop(&mut self) {
+        if let Some(sink) = self.current_sink.take() {
+            sink.stop();
         }
     }
 
-    pub fn parse_and_play_looping(&mut self, input: &str) -> Result<(), String> {
-        use crate::midi_composer::channels::parse_channels;
+    pub fn play_channels(&mut self, channels: &[Vec<Note>]) -> Result<(), String> {
+        if channels.is_empty() {
+            return Err("No channels to play".to_string());
+        }
 
-        let (channels, bpm) = parse_channels(input)?;
+        let source = SamplesBuffer::new(1, SAMPLE_RATE, self.mix_channels(channels));
+        let sink = self.new_sink()?;
 
-        if let Some(bpm_val) = bpm {
-            *self = Self::new(bpm_val)?;
-        }
+        sink.append(source);
+        sink.sleep_until_end();
+
+        Ok(())
+    }
+
+    pub fn parse_and_play(&mut self, input: &str) -> Result<(), String> {
+        let channels = self.parse_composition(input)?;
 
         if channels.len() == 1 && !input.contains("--channel") {
-            let channel = &channels[0];
-            let mut notes = self.parse_notes(&channel.composition)?;
+            self.play_notes(&channels[0])
+        } else {
+            self.play_channels(&channels)
+        }
+    }
 
-            for note in &mut notes {
-                if channel.volume.is_some() && note.volume == 1.0 {
-                    note.volume = channel.volume.unwrap_or(1.0);
-                }
-                note.adsr = channel.adsr;
-                note.vibrato = channel.vibrato;
-            }
+    pub fn parse_and_play_looping(&mut self, input: &str) -> Result<(), String> {
+        self.parse_and_play_looping_from(input, Duration::ZERO)
+    }
 
-            self.play_notes_looping(&notes)
+    pub fn parse_and_play_looping_from(&mut self, input: &str, offset: Duration) -> Result<(), String> {
+        let channels = self.parse_composition(input)?;
+
+        if channels.len() == 1 && !input.contains("--channel") {
+            self.play_notes_looping_from(&channels[0], offset)
         } else {
             Err("Looping playback is only supported for single-channel compositions".to_string())
         }
     }
-
 }
 
 #[cfg(test)]
@@ -956,7 +981,7 @@ mod tests {
     use super::*;
 
     fn test_engine() -> MidiEngine {
-        MidiEngine::new(120).unwrap()
+        MidiEngine::without_audio(120)
     }
 
     #[test]
@@ -1280,4 +1305,11 @@ mod tests {
         assert_eq!(notes[3].pitch, Some(68));
     }
 
+    #[test]
+    fn test_composition_duration() {
+        assert_eq!(MidiEngine::composition_duration("4c 4d 2e").unwrap(), 2.0);
+        assert_eq!(MidiEngine::composition_duration("--bpm 60 4c 4d").unwrap(), 2.0);
+        assert!(MidiEngine::composition_duration("4x").is_err());
+    }
+
 }
@@ -1,4 +1,6 @@
 mod engine;
 mod channels;
+mod wav;
 
-pub use engine::MidiEngine;
+pub use engine::{MidiEngine, SAMPLE_RATE};
+pub use wav::{encode_wav, render_composition_wav};
@@ -0,0 +1,90 @@
+use super::engine::{channels_duration, MidiEngine, SAMPLE_RATE};
+use std::io::Cursor;
+
+const DEFAULT_BPM: u16 = 120;
+
+/// Renders a composition to a mono 16-bit WAV file, exactly as `parse_and_play` would play it.
+/// Compositions longer than `max_seconds` are rejected before any samples are generated.
+pub fn render_composition_wav(composition: &str, max_seconds: f32) -> Result<Vec<u8>, String> {
+    let mut engine = MidiEngine::without_audio(DEFAULT_BPM);
+    let channels = engine.parse_composition(composition)?;
+
+    let duration = channels_duration(&channels);
+
+    if duration > max_seconds {
+        return Err(format!("Composition is too long to render ({duration:.1}s, max {max_seconds:.0}s)"));
+    }
+
+    encode_wav(&engine.mix_channels(&channels))
+}
+
+pub fn encode_wav(samples: &[f32]) -> Result<Vec<u8>, String> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+
+    let mut wav = Vec::new();
+    let mut writer = hound::WavWriter::new(Cursor::new(&mut wav), spec)
+        .map_err(|e| format!("Failed to create WAV writer: {e}"))?;
+
+    for &sample in samples {
+        // Mixed channels can exceed full scale, clip instead of wrapping around
+        let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        writer.write_sample(value)
+            .map_err(|e| format!("Failed to write WAV sample: {e}"))?;
+    }
+
+    writer.finalize()
+        .map_err(|e| format!("Failed to finalize WAV file: {e}"))?;
+
+    Ok(wav)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_samples(wav: Vec<u8>) -> (hound::WavSpec, Vec<i16>) {
+        let mut reader = hound::WavReader::new(Cursor::new(wav)).unwrap();
+        let samples = reader.samples::<i16>().map(Result::unwrap).collect();
+        (reader.spec(), samples)
+    }
+
+    #[test]
+    fn test_render_matches_engine_synthesis() {
+        let wav = render_composition_wav("4c 4e", 60.0).unwrap();
+        let (spec, samples) = read_samples(wav);
+
+        let engine = MidiEngine::without_audio(DEFAULT_BPM);
+        let notes = engine.parse_notes("4c 4e").unwrap();
+        let expected = engine.generate_samples(&notes);
+
+        assert_eq!(spec.sample_rate, SAMPLE_RATE);
+        assert_eq!(spec.channels, 1);
+        assert_eq!(samples.len(), expected.len());
+        assert_eq!(samples[10], (expected[10] * i16::MAX as f32) as i16);
+    }
+
+    #[test]
+    fn test_render_honors_bpm_flag() {
+        let (_, at_120) = read_samples(render_composition_wav("4c", 60.0).unwrap());
+        let (_, at_60) = read_samples(render_composition_wav("--bpm 60 4c", 60.0).unwrap());
+
+        assert_eq!(at_60.len(), at_120.len() * 2);
+    }
+
+    #[test]
+    fn test_render_mixes_channels_to_longest() {
+        let (_, samples) = read_samples(render_composition_wav("--channel 4c --channel 2e", 60.0).unwrap());
+        assert_eq!(samples.len(), SAMPLE_RATE as usize);
+    }
+
+    #[test]
+    fn test_render_rejects_long_compositions() {
+        assert!(render_composition_wav("1c 1c 1c", 5.0).is_err());
+        assert!(render_composition_wav("4x", 60.0).is_err());
+    }
+}
@@ -0,0 +1,346 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use tama::api::{ChannelProfile, Visibility};
+use tama::content_parser::{self, ContentFile};
+
+use crate::storage::{ChannelImport, ChannelImportStatus, ContentOrigin, Listing, NewContent, Storage};
+
+const ARCHIVE_FORMAT: &str = "tama-archive";
+const ARCHIVE_VERSION: u32 = 1;
+
+/// Portable snapshot of a server, used to move an instance or seed a federated peer
+#[derive(Serialize, Deserialize)]
+pub struct Archive {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: i64,
+    pub channels: Vec<ArchivedChannel>,
+    pub servers: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ArchivedChannel {
+    pub id: i64,
+    pub name: String,
+    pub password_hash: String,
+    pub created_at: i64,
+    /// Missing in archives exported before profiles existed
+    #[serde(default)]
+    pub profile: ChannelProfile,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+    pub contents: Vec<ArchivedContent>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ArchivedContent {
+    pub id: i64,
+    pub name: String,
+    pub created_at: i64,
+    /// Content in the `--- MIDI --- / --- ART ---` file format
+    pub content: String,
+    /// Archives exported before visibility existed only hold public contents
+    #[serde(default)]
+    pub visibility: Visibility,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publish_at: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub share_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+    /// Set for contents mirrored from a peer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<ArchivedOrigin>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ArchivedOrigin {
+    pub server_url: String,
+    pub content_id: i64,
+    pub content_hash: String,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ImportSummary {
+    pub channels_created: usize,
+    pub channels_existing: usize,
+    /// Names taken locally by a channel with a different password, skipped with their contents
+    pub conflicts: Vec<String>,
+    pub contents_created: usize,
+    pub contents_existing: usize,
+    pub contents_invalid: usize,
+    pub servers_added: usize,
+}
+
+pub async fn export_archive(storage: &dyn Storage) -> Result<Archive, String> {
+    let mut channels = Vec::new();
+
+    for channel in storage.list_channels().await.map_err(|e| e.to_string())? {
+        let contents = storage
+            .list_channel_contents(channel.id, Listing::All, i64::MAX, 0)
+            .await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|record| ArchivedContent {
+                id: record.id,
+                content: content_parser::serialize_content(&ContentFile {
+                    midi_composition: record.midi_composition,
+                    art: record.art,
+                    fps: record.fps,
+                }),
+                name: record.name,
+                created_at: record.created_at,
+                visibility: record.visibility,
+                publish_at: record.publish_at,
+                share_token: record.share_token,
+                signature: record.signature,
+                public_key: record.public_key,
+                origin: record.origin.map(|origin| ArchivedOrigin {
+                    server_url: origin.server_url,
+                    content_id: origin.content_id,
+                    content_hash: origin.content_hash,
+                }),
+            })
+            .collect();
+
+        let profile = storage.find_profile(channel.id).await.map_err(|e| e.to_string())?;
+        let public_key = storage.find_public_key(channel.id).await.map_err(|e| e.to_string())?;
+
+        channels.push(ArchivedChannel {
+            id: channel.id,
+            name: channel.name,
+            password_hash: channel.password_hash,
+            created_at: channel.created_at,
+            profile,
+            public_key,
+            contents,
+        });
+    }
+
+    let mut servers = storage.list_servers().await.map_err(|e| e.to_string())?;
+    servers.sort();
+
+    Ok(Archive {
+        format: ARCHIVE_FORMAT.to_string(),
+        version: ARCHIVE_VERSION,
+        exported_at: chrono::Utc::now().timestamp(),
+        channels,
+        servers,
+    })
+}
+
+/// Imports an archive, assigning fresh ids. Channels are matched by name and password hash,
+/// contents by channel, creation time and payload, so importing the same archive twice is a no-op.
+/// A channel whose name is taken by someone else locally is skipped and reported as a conflict.
+/// Everything is written in one go: an import that fails leaves the server as it was.
+pub async fn import_archive(storage: &dyn Storage, archive: &Archive) -> Result<ImportSummary, String> {
+    if archive.format != ARCHIVE_FORMAT {
+        return Err(format!("Not a Tama archive (format '{}')", archive.format));
+    }
+
+    if archive.version > ARCHIVE_VERSION {
+        return Err(format!(
+            "Unsupported archive version {} (this server reads up to {ARCHIVE_VERSION})",
+            archive.version
+        ));
+    }
+
+    let mut summary = ImportSummary::default();
+    let mut imports = Vec::new();
+
+    for channel in &archive.channels {
+        let mut contents = Vec::new();
+
+        for content in &channel.contents {
+            let file = match content_parser::parse_content(&content.content) {
+                Ok(file) => file,
+                Err(e) => {
+                    tracing::warn!(
+                        "Skipping content {} of channel '{}': {:?}",
+                        content.id, channel.name, e
+                    );
+                    summary.contents_invalid += 1;
+                    continue;
+                }
+            };
+
+            contents.push(NewContent {
+                channel_id: 0,
+                name: content.name.clone(),
+                art: file.art,
+                midi_composition: file.midi_composition,
+                fps: file.fps,
+                created_at: content.created_at,
+                visibility: content.visibility,
+                publish_at: content.publish_at,
+                share_token: content.share_token.clone(),
+                signature: content.signature.clone(),
+                public_key: content.public_key.clone(),
+                origin: content.origin.as_ref().map(|origin| ContentOrigin {
+                    server_url: origin.server_url.clone(),
+                    content_id: origin.content_id,
+                    content_hash: origin.content_hash.clone(),
+                }),
+            });
+        }
+
+        imports.push(ChannelImport {
+            name: channel.name.clone(),
+            password_hash: channel.password_hash.clone(),
+            created_at: channel.created_at,
+            profile: channel.profile.clone(),
+            public_key: channel.public_key.clone(),
+            contents,
+        });
+    }
+
+    let imported = storage
+        .import_archive(imports, archive.servers.clone(), archive.exported_at)
+        .await
+        .map_err(|e| format!("Failed to import archive, nothing was imported: {e}"))?;
+
+    for (channel, result) in archive.channels.iter().zip(imported.channels) {
+        match result.status {
+            ChannelImportStatus::Created => summary.channels_created += 1,
+            ChannelImportStatus::Existing => summary.channels_existing += 1,
+            ChannelImportStatus::Conflict => {
+                tracing::warn!("Skipping channel '{}': the name belongs to another local channel", channel.name);
+                summary.conflicts.push(channel.name.clone());
+            }
+        }
+        summary.contents_created += result.contents_created;
+        summary.contents_existing += result.contents_existing;
+    }
+    summary.servers_added = imported.servers_added;
+
+    Ok(summary)
+}
+
+pub async fn export_to_file(storage: &dyn Storage, path: &Path) -> Result<Archive, String> {
+    let archive = export_archive(storage).await?;
+
+    let json = serde_json::to_string_pretty(&archive)
+        .map_err(|e| format!("Failed to serialize archive: {e}"))?;
+
+    fs::write(path, json)
+        .map_err(|e| format!("Failed to write archive file: {e}"))?;
+
+    Ok(archive)
+}
+
+pub async fn import_from_file(storage: &dyn Storage, path: &Path) -> Result<ImportSummary, String> {
+    let json = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read archive file: {e}"))?;
+
+    let archive: Archive = serde_json::from_str(&json)
+        .map_err(|e| format!("Failed to parse archive file: {e}"))?;
+
+    import_archive(storage, &archive).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{ChannelStore, ContentStore, MemoryStorage, ServerStore};
+
+    async fn seeded_storage() -> MemoryStorage {
+        let storage = MemoryStorage::new();
+        let channel = storage.create_channel("neko", "$argon2id$hash", 100).await.unwrap();
+        storage.create_content(NewContent {
+            channel_id: channel.id,
+            name: "idle".to_string(),
+            art: "Ascii Art Animation, 2x1, 5fps\n⠁⠁".to_string(),
+            midi_composition: "4c 4e".to_string(),
+            fps: 5.0,
+            created_at: 200,
+            ..Default::default()
+        }).await.unwrap();
+        storage.add_server("https://tama.example").await.unwrap();
+        storage
+    }
+
+    #[tokio::test]
+    async fn test_export_includes_everything() {
+        let storage = seeded_storage().await;
+
+        let archive = export_archive(&storage).await.unwrap();
+        assert_eq!(archive.version, ARCHIVE_VERSION);
+        assert_eq!(archive.channels.len(), 1);
+        assert_eq!(archive.channels[0].password_hash, "$argon2id$hash");
+        assert_eq!(archive.channels[0].contents.len(), 1);
+        assert!(archive.channels[0].contents[0].content.starts_with("--- MIDI ---"));
+        assert_eq!(archive.servers, vec!["https://tama.example".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_import_remaps_ids_and_is_idempotent() {
+        let source = seeded_storage().await;
+        let archive = export_archive(&source).await.unwrap();
+
+        let target = MemoryStorage::new();
+        target.create_channel("other", "x", 1).await.unwrap();
+
+        let summary = import_archive(&target, &archive).await.unwrap();
+        assert_eq!(summary.channels_created, 1);
+        assert_eq!(summary.contents_created, 1);
+        assert_eq!(summary.servers_added, 1);
+
+        let channel = target.find_channel_by_name("neko").await.unwrap().unwrap();
+        assert_eq!(channel.id, 2);
+        assert_eq!(target.list_channel_contents(channel.id, Listing::All, 10, 0).await.unwrap().len(), 1);
+
+        let again = import_archive(&target, &archive).await.unwrap();
+        assert_eq!(again, ImportSummary {
+            channels_existing: 1,
+            contents_existing: 1,
+            ..Default::default()
+        });
+    }
+
+    #[tokio::test]
+    async fn test_import_skips_channels_owned_by_someone_else() {
+        let archive = export_archive(&seeded_storage().await).await.unwrap();
+
+        let target = MemoryStorage::new();
+        let local = target.create_channel("neko", "$argon2id$someone-else", 1).await.unwrap();
+
+        let summary = import_archive(&target, &archive).await.unwrap();
+        assert_eq!(summary, ImportSummary {
+            conflicts: vec!["neko".to_string()],
+            servers_added: 1,
+            ..Default::default()
+        });
+        assert!(target.list_channel_contents(local.id, Listing::All, 10, 0).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_newer_version() {
+        let storage = MemoryStorage::new();
+        let archive = Archive {
+            format: ARCHIVE_FORMAT.to_string(),
+            version: ARCHIVE_VERSION + 1,
+            exported_at: 0,
+            channels: vec![],
+            servers: vec![],
+        };
+
+        assert!(import_archive(&storage, &archive).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_through_file() {
+        let storage = seeded_storage().await;
+
+        let path = std::env::temp_dir().join(format!("tama-archive-{}.json", uuid::Uuid::new_v4()));
+        export_to_file(&storage, &path).await.unwrap();
+
+        let target = MemoryStorage::new();
+        let summary = import_from_file(&target, &path).await.unwrap();
+        assert_eq!(summary.contents_created, 1);
+
+        fs::remove_file(&path).ok();
+    }
+}
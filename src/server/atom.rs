@@ -0,0 +1,206 @@
+use crate::{
+    preview::{configured_base_url, escape_html},
+    storage::{ContentRecord, Listing},
+    AppState,
+};
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, SecondsFormat};
+use tama::ascii_art_converter::AsciiArtSheet;
+
+const FEED_LIMIT: i64 = 30;
+
+struct AtomEntry<'a> {
+    content: &'a ContentRecord,
+    channel_name: &'a str,
+}
+
+pub async fn get_server_feed(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Response, StatusCode> {
+    tracing::debug!("[GET /feed.atom] Request received");
+    let now = chrono::Utc::now().timestamp();
+    let entries = state.storage.latest_contents(Listing::PublicAt(now), FEED_LIMIT).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let base_url = configured_base_url(&headers);
+    let atom_entries: Vec<AtomEntry> = entries
+        .iter()
+        .map(|entry| AtomEntry { content: &entry.content, channel_name: &entry.channel_name })
+        .collect();
+
+    let xml = render_feed(
+        "Tama",
+        &format!("{base_url}/feed.atom"),
+        &base_url,
+        &base_url,
+        &atom_entries,
+    );
+    Ok(atom_response(xml))
+}
+
+pub async fn get_channel_feed(
+    Path(channel_identifier): Path<String>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Response, StatusCode> {
+    tracing::debug!("[GET /channel/{channel_identifier}/feed.atom] Request received");
+    let channel = state.storage.find_channel(&channel_identifier).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let now = chrono::Utc::now().timestamp();
+    let contents = state.storage.latest_channel_contents(channel.id, Listing::PublicAt(now), FEED_LIMIT).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let base_url = configured_base_url(&headers);
+    let atom_entries: Vec<AtomEntry> = contents
+        .iter()
+        .map(|content| AtomEntry { content, channel_name: &channel.name })
+        .collect();
+
+    let xml = render_feed(
+        &format!("{} on Tama", channel.name),
+        &format!("{base_url}/channel/{}/feed.atom", channel.id),
+        &format!("{base_url}/channel/{}", channel.id),
+        &base_url,
+        &atom_entries,
+    );
+    Ok(atom_response(xml))
+}
+
+fn atom_response(xml: String) -> Response {
+    ([(header::CONTENT_TYPE, "application/atom+xml; charset=utf-8")], xml).into_response()
+}
+
+fn timestamp(unix_seconds: i64) -> String {
+    DateTime::from_timestamp(unix_seconds, 0)
+        .unwrap_or_default()
+        .to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn render_feed(title: &str, self_url: &str, alternate_url: &str, base_url: &str, entries: &[AtomEntry]) -> String {
+    // An empty feed still needs an `updated`, the epoch is as good as any
+    let updated = entries.iter().map(|e| e.content.published_at()).max().unwrap_or(0);
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    xml.push_str(&format!("  <title>{}</title>\n", escape_html(title)));
+    xml.push_str(&format!("  <id>{}</id>\n", escape_html(self_url)));
+    xml.push_str(&format!("  <link rel=\"self\" href=\"{}\" />\n", escape_html(self_url)));
+    xml.push_str(&format!("  <link rel=\"alternate\" href=\"{}\" />\n", escape_html(alternate_url)));
+    xml.push_str(&format!("  <updated>{}</updated>\n", timestamp(updated)));
+
+    for entry in entries {
+        xml.push_str(&render_entry(entry, base_url));
+    }
+
+    xml.push_str("</feed>\n");
+    xml
+}
+
+fn render_entry(entry: &AtomEntry, base_url: &str) -> String {
+    let content = entry.content;
+    let view_url = format!("{base_url}/view/content/{}", content.id);
+    let preview_url = format!("{base_url}/content/{}/preview.png", content.id);
+    let first_frame = AsciiArtSheet::from_string(&content.art)
+        .ok()
+        .and_then(|sheet| sheet.frames.into_iter().next())
+        .unwrap_or_default();
+
+    // The body is HTML carried as escaped text, so it is escaped twice in total
+    let body = format!(
+        "<pre>{}</pre>\n<p><a href=\"{}\"><img src=\"{}\" alt=\"{}\" /></a></p>",
+        escape_html(&first_frame),
+        escape_html(&view_url),
+        escape_html(&preview_url),
+        escape_html(&content.name),
+    );
+
+    format!(
+        "  <entry>\n    <title>{}</title>\n    <id>{}</id>\n    <link rel=\"alternate\" href=\"{}\" />\n    <updated>{}</updated>\n    <author><name>{}</name></author>\n    <content type=\"html\">{}</content>\n  </entry>\n",
+        escape_html(&content.name),
+        escape_html(&view_url),
+        escape_html(&view_url),
+        timestamp(content.published_at()),
+        escape_html(entry.channel_name),
+        escape_html(&body),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{ChannelStore, ContentStore, MemoryStorage, NewContent};
+    use std::sync::Arc;
+
+    async fn state_with_contents() -> AppState {
+        let storage = MemoryStorage::new();
+        let neko = storage.create_channel("neko", "hash", 1).await.unwrap();
+        let tama = storage.create_channel("tama", "hash", 1).await.unwrap();
+
+        for (channel_id, name, created_at) in [(neko.id, "idle & <sleep>", 10), (tama.id, "walk", 20)] {
+            storage.create_content(NewContent {
+                channel_id,
+                name: name.to_string(),
+                art: "Ascii Art Animation, 2x1\n⠁⠁\n⠂⠂".to_string(),
+                midi_composition: "4c".to_string(),
+                fps: 10.0,
+                created_at,
+                ..Default::default()
+            }).await.unwrap();
+        }
+
+        AppState::for_tests(Arc::new(storage))
+    }
+
+    fn host_headers() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::HOST, "tama.example".parse().unwrap());
+        headers
+    }
+
+    async fn body(response: Response) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_server_feed_lists_newest_first() {
+        let state = state_with_contents().await;
+
+        let response = get_server_feed(host_headers(), State(state)).await.unwrap();
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/atom+xml; charset=utf-8");
+
+        let xml = body(response).await;
+        assert!(xml.find("<title>walk</title>").unwrap() < xml.find("<title>idle").unwrap());
+        assert!(xml.contains("<updated>1970-01-01T00:00:20Z</updated>"));
+    }
+
+    #[tokio::test]
+    async fn test_channel_feed_entry_contents() {
+        let state = state_with_contents().await;
+
+        let response = get_channel_feed(Path("neko".to_string()), host_headers(), State(state)).await.unwrap();
+        let xml = body(response).await;
+
+        assert!(xml.contains("/view/content/1\" />"));
+        assert!(xml.contains("<title>idle &amp; &lt;sleep&gt;</title>"));
+        assert!(xml.contains("&lt;pre&gt;⠁⠁&lt;/pre&gt;"));
+        assert!(xml.contains("/content/1/preview.png"));
+        assert!(!xml.contains("walk"));
+    }
+
+    #[tokio::test]
+    async fn test_channel_feed_not_found() {
+        let state = state_with_contents().await;
+
+        let result = get_channel_feed(Path("nobody".to_string()), host_headers(), State(state)).await;
+        assert_eq!(result.err(), Some(StatusCode::NOT_FOUND));
+    }
+}
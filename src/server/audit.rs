@@ -0,0 +1,168 @@
+use axum::http::StatusCode;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tama::api::AuthEventKind;
+
+use crate::storage::{LoginFailures, NewAuthEvent, Storage};
+
+/// Failed logins allowed before the lockout kicks in
+const FREE_FAILURES: usize = 5;
+const BASE_LOCKOUT_SECONDS: i64 = 30;
+const MAX_LOCKOUT_SECONDS: i64 = 60 * 60;
+/// Failures older than this no longer count, a day of the longest lockout
+const LOCKOUT_WINDOW_SECONDS: i64 = 24 * 60 * 60;
+/// Events are kept this long for the security log, well past the lockout window
+const RETENTION_SECONDS: i64 = 90 * 24 * 60 * 60;
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Persists an auth event. The audit log is best effort, a failed write never fails the login.
+pub async fn record(
+    storage: &dyn Storage,
+    channel_name: &str,
+    kind: AuthEventKind,
+    ip: IpAddr,
+    user_name: Option<&str>,
+) {
+    let event = NewAuthEvent {
+        channel_name: channel_name.to_string(),
+        kind,
+        ip: Some(ip.to_string()),
+        user_name: user_name.map(str::to_string),
+        created_at: chrono::Utc::now().timestamp(),
+    };
+
+    if let Err(e) = storage.record_auth_event(&event).await {
+        tracing::error!("Failed to record auth event {} for '{}': {}", kind.as_str(), channel_name, e);
+    }
+}
+
+/// Refuses the login with 429 while it is locked out, whatever the caller's IP.
+/// Channel password logins are locked out per channel name, member logins per user name
+/// whatever channel they ask for, so switching channels doesn't buy more guesses.
+pub async fn check_lockout(
+    storage: &dyn Storage,
+    channel_name: &str,
+    ip: IpAddr,
+    user_name: Option<&str>,
+) -> Result<(), StatusCode> {
+    let now = chrono::Utc::now().timestamp();
+    let failures = storage.login_failures(channel_name, user_name, now - LOCKOUT_WINDOW_SECONDS).await
+        .map_err(|e| {
+            tracing::error!("Failed to read auth events: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    match locked_until(failures) {
+        Some(until) if now < until => {
+            tracing::warn!("Login refused: '{}' is locked out for {}s more (ip {})", user_name.unwrap_or(channel_name), until - now, ip);
+            // One entry per lockout, hammering a locked out name doesn't fill the log
+            if !failures.refused {
+                record(storage, channel_name, AuthEventKind::LockedOut, ip, user_name).await;
+            }
+            Err(StatusCode::TOO_MANY_REQUESTS)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Deletes events past the retention now and then every hour
+pub fn start_pruning(storage: Arc<dyn Storage>) {
+    tokio::spawn(async move {
+        loop {
+            let before = chrono::Utc::now().timestamp() - RETENTION_SECONDS;
+            match storage.prune_auth_events(before).await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("Pruned {} old auth events", count),
+                Err(e) => tracing::warn!("Failed to prune auth events: {}", e),
+            }
+            tokio::time::sleep(PRUNE_INTERVAL).await;
+        }
+    });
+}
+
+/// End of the lockout after the failures since the last successful login.
+/// Every failure past `FREE_FAILURES` doubles the lockout, up to `MAX_LOCKOUT_SECONDS`.
+fn locked_until(failures: LoginFailures) -> Option<i64> {
+    if failures.count < FREE_FAILURES {
+        return None;
+    }
+
+    let doublings = (failures.count - FREE_FAILURES).min(16) as u32;
+    let lockout = (BASE_LOCKOUT_SECONDS << doublings).min(MAX_LOCKOUT_SECONDS);
+    failures.last_at.map(|at| at + lockout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{AuditStore, MemoryStorage};
+    use std::net::Ipv4Addr;
+
+    fn failures(count: usize, last_at: i64) -> LoginFailures {
+        LoginFailures { count, last_at: Some(last_at), refused: false }
+    }
+
+    #[test]
+    fn test_lockout_grows_exponentially() {
+        assert_eq!(locked_until(failures(4, 100)), None);
+        assert_eq!(locked_until(failures(5, 100)), Some(130));
+        assert_eq!(locked_until(failures(6, 100)), Some(160));
+        assert_eq!(locked_until(failures(8, 100)), Some(340));
+        assert_eq!(locked_until(failures(40, 100)), Some(100 + MAX_LOCKOUT_SECONDS));
+    }
+
+    #[tokio::test]
+    async fn test_check_lockout_records_refusals() {
+        let storage = MemoryStorage::new();
+        let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+        for _ in 0..FREE_FAILURES {
+            assert!(check_lockout(&storage, "neko", ip, None).await.is_ok());
+            record(&storage, "neko", AuthEventKind::LoginFailure, ip, None).await;
+        }
+
+        assert_eq!(check_lockout(&storage, "neko", ip, None).await, Err(StatusCode::TOO_MANY_REQUESTS));
+        assert_eq!(storage.list_auth_events("neko", 1).await.unwrap()[0].kind, AuthEventKind::LockedOut);
+        assert!(check_lockout(&storage, "other", ip, None).await.is_ok());
+
+        // Later refusals of the same lockout aren't recorded again
+        assert_eq!(check_lockout(&storage, "neko", ip, None).await, Err(StatusCode::TOO_MANY_REQUESTS));
+        let events = storage.list_auth_events("neko", 10).await.unwrap();
+        assert_eq!(events.iter().filter(|e| e.kind == AuthEventKind::LockedOut).count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_hammering_a_locked_out_name_keeps_it_locked() {
+        let storage = MemoryStorage::new();
+        let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+        for _ in 0..FREE_FAILURES {
+            record(&storage, "neko", AuthEventKind::LoginFailure, ip, None).await;
+        }
+
+        // Refusals and failures of throwaway members fill the log, the failures still count
+        for i in 0..200 {
+            assert_eq!(check_lockout(&storage, "neko", ip, None).await, Err(StatusCode::TOO_MANY_REQUESTS));
+            record(&storage, "neko", AuthEventKind::LoginFailure, ip, Some(&format!("throwaway{i}"))).await;
+        }
+        assert_eq!(check_lockout(&storage, "neko", ip, None).await, Err(StatusCode::TOO_MANY_REQUESTS));
+    }
+
+    #[tokio::test]
+    async fn test_members_are_locked_out_by_user_name() {
+        let storage = MemoryStorage::new();
+        let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+        for i in 0..FREE_FAILURES {
+            record(&storage, &format!("channel{i}"), AuthEventKind::LoginFailure, ip, Some("alice")).await;
+        }
+
+        assert_eq!(check_lockout(&storage, "neko", ip, Some("alice")).await, Err(StatusCode::TOO_MANY_REQUESTS));
+        assert!(check_lockout(&storage, "neko", ip, Some("bob")).await.is_ok());
+        assert!(check_lockout(&storage, "neko", ip, None).await.is_ok());
+
+        record(&storage, "neko", AuthEventKind::LoginSuccess, ip, Some("alice")).await;
+        assert!(check_lockout(&storage, "other", ip, Some("alice")).await.is_ok());
+    }
+}
@@ -1,9 +1,19 @@
 use axum::http::{HeaderMap, StatusCode};
+use tama::api::Role;
 
-pub fn authenticate_request(
+use crate::storage::{Storage, StorageResult};
+
+/// Who is behind a request: the channel its token is scoped to and, for member logins, the user
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Caller {
+    pub channel_id: i64,
+    pub user_id: Option<i64>,
+}
+
+pub fn authenticate_caller(
     headers: &HeaderMap,
     jwt_secret: &str,
-) -> Result<i64, StatusCode> {
+) -> Result<Caller, StatusCode> {
     let token = headers
         .get(tama::api::HEADER_AUTH)
         .and_then(|h| h.to_str().ok())
@@ -16,7 +26,16 @@ pub fn authenticate_request(
     let channel_id = claims.sub.parse::<i64>()
         .map_err(|_| StatusCode::UNAUTHORIZED)?;
 
-    Ok(channel_id)
+    Ok(Caller { channel_id, user_id: claims.uid })
+}
+
+/// Role of the caller on `channel_id`. Channel password logins own their channel, users
+/// have the role of their membership as it is now, so removals apply to existing tokens.
+pub async fn caller_role(storage: &dyn Storage, caller: &Caller, channel_id: i64) -> StorageResult<Option<Role>> {
+    match caller.user_id {
+        None => Ok((caller.channel_id == channel_id).then_some(Role::Owner)),
+        Some(user_id) => storage.find_member_role(channel_id, user_id).await,
+    }
 }
 
 #[cfg(test)]
@@ -37,9 +56,28 @@ mod tests {
             format!("Bearer {}", token).parse().unwrap(),
         );
 
-        let result = authenticate_request(&headers, secret);
+        let result = authenticate_caller(&headers, secret);
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), 123);
+        assert_eq!(result.unwrap(), Caller { channel_id: 123, user_id: None });
+    }
+
+    #[tokio::test]
+    async fn test_caller_role() {
+        use crate::storage::{ChannelStore, MemberStore, MemoryStorage};
+
+        let storage = MemoryStorage::new();
+        let team = storage.create_channel("team", "hash", 1).await.unwrap();
+        let other = storage.create_channel("other", "hash", 1).await.unwrap();
+        let alice = storage.create_user("alice", "hash", 1).await.unwrap();
+        storage.set_member(team.id, alice.id, Role::Editor, 2).await.unwrap();
+
+        let channel_login = Caller { channel_id: team.id, user_id: None };
+        assert_eq!(caller_role(&storage, &channel_login, team.id).await.unwrap(), Some(Role::Owner));
+        assert_eq!(caller_role(&storage, &channel_login, other.id).await.unwrap(), None);
+
+        let member = Caller { channel_id: team.id, user_id: Some(alice.id) };
+        assert_eq!(caller_role(&storage, &member, team.id).await.unwrap(), Some(Role::Editor));
+        assert_eq!(caller_role(&storage, &member, other.id).await.unwrap(), None);
     }
 
     #[test]
@@ -51,7 +89,7 @@ mod tests {
             "Bearer invalid.jwt.token".parse().unwrap(),
         );
 
-        let result = authenticate_request(&headers, secret);
+        let result = authenticate_caller(&headers, secret);
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), StatusCode::UNAUTHORIZED);
     }
@@ -65,7 +103,7 @@ mod tests {
             "some_token".parse().unwrap(),
         );
 
-        let result = authenticate_request(&headers, secret);
+        let result = authenticate_caller(&headers, secret);
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), StatusCode::UNAUTHORIZED);
     }
@@ -75,7 +113,7 @@ mod tests {
         let secret = "test-secret";
         let headers = HeaderMap::new();
 
-        let result = authenticate_request(&headers, secret);
+        let result = authenticate_caller(&headers, secret);
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), StatusCode::UNAUTHORIZED);
     }
@@ -1,24 +1,37 @@
 use axum::{
-    extract::{Json, State},
-    http::StatusCode,
+    extract::{ConnectInfo, Json, Query, State},
+    http::{HeaderMap, StatusCode},
 };
-use rusqlite::params;
+use serde::Deserialize;
+use std::net::SocketAddr;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::AppState;
-use tama::api::{AuthResponse, ChannelInfo, LoginRequest, RegisterRequest};
+use crate::storage::StorageError;
+use crate::{audit, auth, AppState};
+use tama::api::{AuthEventKind, AuthResponse, ChannelInfo, LoginRequest, RegisterRequest, Role, SecurityEvent};
+
+const MAX_SECURITY_LOG_LIMIT: i64 = 500;
+
+/// Names that would be shadowed by static routes under `/channel/`
+const RESERVED_CHANNEL_NAMES: [&str; 1] = ["me"];
+
+pub fn is_valid_channel_name(channel_name: &str) -> bool {
+    !channel_name.is_empty()
+        && channel_name.len() <= 250
+        && !channel_name.contains(char::is_whitespace)
+        // `<name>@<host>` is how mirrored channels are named
+        && !channel_name.contains('@')
+        && !RESERVED_CHANNEL_NAMES.contains(&channel_name)
+}
 
 pub async fn register(
     State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     Json(request): Json<RegisterRequest>,
 ) -> Result<Json<AuthResponse>, StatusCode> {
     let channel_name = request.channel_name.trim().to_lowercase();
 
-    if channel_name.is_empty() || channel_name.len() > 250 {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
-    if channel_name.contains(char::is_whitespace) {
+    if !is_valid_channel_name(&channel_name) {
         return Err(StatusCode::BAD_REQUEST);
     }
 
@@ -28,35 +41,22 @@ pub async fn register(
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
-    let db = state.db.get()
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-    let channel_exists: Result<i64, _> = db.query_row(
-        "SELECT id FROM channels WHERE name = ?1",
-        params![channel_name],
-        |row| row.get(0),
-    );
-
-    if channel_exists.is_ok() {
-        tracing::warn!("Channel registration failed: channel '{}' already exists", channel_name);
-        return Err(StatusCode::CONFLICT);
-    }
-
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap()
         .as_secs() as i64;
 
-    db.execute(
-        "INSERT INTO channels (name, password_hash, created_at) VALUES (?1, ?2, ?3)",
-        params![channel_name, password_hash, now],
-    )
-    .map_err(|e| {
-        tracing::error!("Failed to insert channel: {}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-
-    let channel_id = db.last_insert_rowid();
+    let channel_id = match state.storage.create_channel(&channel_name, &password_hash, now).await {
+        Ok(channel) => channel.id,
+        Err(StorageError::AlreadyExists) => {
+            tracing::warn!("Channel registration failed: channel '{}' already exists", channel_name);
+            return Err(StatusCode::CONFLICT);
+        }
+        Err(e) => {
+            tracing::error!("Failed to insert channel: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
 
     let token = crate::jwt::create_jwt(channel_id, &channel_name, &state.jwt_secret)
         .map_err(|e| {
@@ -67,6 +67,7 @@ pub async fn register(
     let expires_at = now + (30 * 24 * 60 * 60);
 
     tracing::info!("Channel registered: id={}, name={}", channel_id, channel_name);
+    audit::record(state.storage.as_ref(), &channel_name, AuthEventKind::Register, addr.ip(), None).await;
 
     Ok(Json(AuthResponse {
         token,
@@ -75,28 +76,34 @@ pub async fn register(
             id: channel_id,
             name: channel_name,
         },
+        user: None,
+        role: Some(Role::Owner),
     }))
 }
 
 pub async fn login(
     State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     Json(request): Json<LoginRequest>,
 ) -> Result<Json<AuthResponse>, StatusCode> {
     let channel_name = request.channel_name.trim().to_lowercase();
 
-    let db = state.db.get()
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    // Mirrored channels have no password, their owners log in on the origin server
+    if !is_valid_channel_name(&channel_name) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
 
-    let channel_record: Result<(i64, String, String), _> = db.query_row(
-        "SELECT id, name, password_hash FROM channels WHERE name = ?1",
-        params![channel_name],
-        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
-    );
+    audit::check_lockout(state.storage.as_ref(), &channel_name, addr.ip(), None).await?;
 
-    let (channel_id, channel_name, password_hash) = channel_record.map_err(|_| {
+    let channel_record = state.storage.find_channel_by_name(&channel_name).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let Some(channel) = channel_record else {
+        // Unknown names have nothing to lock out, recording them would only grow the log
         tracing::warn!("Login failed: channel '{}' not found", channel_name);
-        StatusCode::UNAUTHORIZED
-    })?;
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+    let (channel_id, channel_name, password_hash) = (channel.id, channel.name, channel.password_hash);
 
     let password_valid = crate::password::verify_password(&request.password, &password_hash)
         .map_err(|e| {
@@ -106,6 +113,7 @@ pub async fn login(
 
     if !password_valid {
         tracing::warn!("Login failed: invalid password for channel '{}'", channel_name);
+        audit::record(state.storage.as_ref(), &channel_name, AuthEventKind::LoginFailure, addr.ip(), None).await;
         return Err(StatusCode::UNAUTHORIZED);
     }
 
@@ -122,6 +130,7 @@ pub async fn login(
     let expires_at = now + (30 * 24 * 60 * 60);
 
     tracing::info!("Channel logged in: id={}, name={}", channel_id, channel_name);
+    audit::record(state.storage.as_ref(), &channel_name, AuthEventKind::LoginSuccess, addr.ip(), None).await;
 
     Ok(Json(AuthResponse {
         token,
@@ -130,34 +139,30 @@ pub async fn login(
             id: channel_id,
             name: channel_name,
         },
+        user: None,
+        role: Some(Role::Owner),
     }))
 }
 
 pub async fn login_or_signup(
     State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     Json(request): Json<LoginRequest>,
 ) -> Result<Json<AuthResponse>, StatusCode> {
     let channel_name = request.channel_name.trim().to_lowercase();
 
-    if channel_name.is_empty() || channel_name.len() > 250 {
+    if !is_valid_channel_name(&channel_name) {
         return Err(StatusCode::BAD_REQUEST);
     }
 
-    if channel_name.contains(char::is_whitespace) {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
-    let db = state.db.get()
+    let channel_record = state.storage.find_channel_by_name(&channel_name).await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    let channel_record: Result<(i64, String, String), _> = db.query_row(
-        "SELECT id, name, password_hash FROM channels WHERE name = ?1",
-        params![channel_name],
-        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
-    );
-
     let (channel_id, channel_name) = match channel_record {
-        Ok((id, name, password_hash)) => {
+        Some(channel) => {
+            audit::check_lockout(state.storage.as_ref(), &channel.name, addr.ip(), None).await?;
+
+            let (id, name, password_hash) = (channel.id, channel.name, channel.password_hash);
             let password_valid = crate::password::verify_password(&request.password, &password_hash)
                 .map_err(|e| {
                     tracing::error!("Password verification error: {}", e);
@@ -166,13 +171,15 @@ pub async fn login_or_signup(
 
             if !password_valid {
                 tracing::warn!("Login failed: invalid password for channel '{}'", name);
+                audit::record(state.storage.as_ref(), &name, AuthEventKind::LoginFailure, addr.ip(), None).await;
                 return Err(StatusCode::UNAUTHORIZED);
             }
 
             tracing::info!("Channel logged in: id={}, name={}", id, name);
+            audit::record(state.storage.as_ref(), &name, AuthEventKind::LoginSuccess, addr.ip(), None).await;
             (id, name)
         }
-        Err(_) => {
+        None => {
             let password_hash = crate::password::hash_password(&request.password)
                 .map_err(|e| {
                     tracing::error!("Failed to hash password: {}", e);
@@ -184,18 +191,21 @@ pub async fn login_or_signup(
                 .unwrap()
                 .as_secs() as i64;
 
-            db.execute(
-                "INSERT INTO channels (name, password_hash, created_at) VALUES (?1, ?2, ?3)",
-                params![channel_name, password_hash, now],
-            )
-            .map_err(|e| {
-                tracing::error!("Failed to insert channel: {}", e);
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?;
-
-            let channel_id = db.last_insert_rowid();
+            let channel_id = match state.storage.create_channel(&channel_name, &password_hash, now).await {
+                Ok(channel) => channel.id,
+                // Someone else signed up with the name since we looked it up
+                Err(StorageError::AlreadyExists) => {
+                    tracing::warn!("Signup failed: channel '{}' was registered concurrently", channel_name);
+                    return Err(StatusCode::CONFLICT);
+                }
+                Err(e) => {
+                    tracing::error!("Failed to insert channel: {}", e);
+                    return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                }
+            };
 
             tracing::info!("Channel registered: id={}, name={}", channel_id, channel_name);
+            audit::record(state.storage.as_ref(), &channel_name, AuthEventKind::Register, addr.ip(), None).await;
             (channel_id, channel_name)
         }
     };
@@ -219,5 +229,113 @@ pub async fn login_or_signup(
             id: channel_id,
             name: channel_name,
         },
+        user: None,
+        role: Some(Role::Owner),
     }))
 }
+
+#[derive(Deserialize)]
+pub struct SecurityLogParams {
+    #[serde(default = "default_security_log_limit")]
+    pub limit: i64,
+}
+
+fn default_security_log_limit() -> i64 {
+    50
+}
+
+/// Recent auth events of the caller's channel, newest first. Owners only.
+pub async fn get_security_log(
+    State(state): State<AppState>,
+    Query(params): Query<SecurityLogParams>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<SecurityEvent>>, StatusCode> {
+    let caller = auth::authenticate_caller(&headers, &state.jwt_secret)?;
+
+    let role = auth::caller_role(state.storage.as_ref(), &caller, caller.channel_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if !role.is_some_and(|role| role.can_manage()) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let channel = state.storage.find_channel_by_id(caller.channel_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let limit = params.limit.clamp(1, MAX_SECURITY_LOG_LIMIT);
+    let events = state.storage.list_auth_events(&channel.name, limit).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .into_iter()
+        .map(|event| SecurityEvent {
+            kind: event.kind,
+            created_at: event.created_at,
+            ip: event.ip,
+            user_name: event.user_name,
+        })
+        .collect();
+
+    Ok(Json(events))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{bearer};
+    use crate::storage::{ChannelStore, MemoryStorage};
+    use std::sync::Arc;
+
+    fn peer() -> ConnectInfo<SocketAddr> {
+        ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 4000)))
+    }
+
+    fn login_request(password: &str) -> Json<LoginRequest> {
+        Json(LoginRequest { channel_name: "neko".to_string(), password: password.to_string() })
+    }
+
+    #[tokio::test]
+    async fn test_mirrored_channel_names_are_reserved() {
+        assert!(is_valid_channel_name("neko"));
+        assert!(!is_valid_channel_name("neko@tama.example"));
+
+        let storage = Arc::new(MemoryStorage::new());
+        storage.create_channel("neko@tama.example", "", 1).await.unwrap();
+        let state = AppState::for_tests(storage);
+
+        let request = Json(LoginRequest { channel_name: "neko@tama.example".to_string(), password: String::new() });
+        assert_eq!(login(State(state), peer(), request).await.unwrap_err(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_failed_logins_lock_out_the_channel_and_are_logged() {
+        let state = AppState::for_tests(Arc::new(MemoryStorage::new()));
+        let Json(auth) = register(State(state.clone()), peer(), Json(RegisterRequest {
+            channel_name: "neko".to_string(),
+            password: "secret".to_string(),
+        })).await.unwrap();
+
+        for _ in 0..5 {
+            let result = login(State(state.clone()), peer(), login_request("wrong")).await;
+            assert_eq!(result.unwrap_err(), StatusCode::UNAUTHORIZED);
+        }
+
+        // Even the right password is refused while locked out
+        let result = login(State(state.clone()), peer(), login_request("secret")).await;
+        assert_eq!(result.unwrap_err(), StatusCode::TOO_MANY_REQUESTS);
+
+        let Json(log) = get_security_log(State(state), Query(SecurityLogParams { limit: 10 }), bearer(&auth.token)).await.unwrap();
+
+        let kinds: Vec<_> = log.iter().map(|event| event.kind).collect();
+        assert_eq!(kinds[0], AuthEventKind::LockedOut);
+        assert_eq!(kinds.iter().filter(|kind| **kind == AuthEventKind::LoginFailure).count(), 5);
+        assert_eq!(kinds.last(), Some(&AuthEventKind::Register));
+        assert_eq!(log[0].ip.as_deref(), Some("127.0.0.1"));
+    }
+
+    #[tokio::test]
+    async fn test_security_log_requires_auth() {
+        let state = AppState::for_tests(Arc::new(MemoryStorage::new()));
+        let result = get_security_log(State(state), Query(SecurityLogParams { limit: 10 }), HeaderMap::new()).await;
+        assert_eq!(result.unwrap_err(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,310 @@
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tama::midi_composer::MidiEngine;
+
+use crate::error::internal_error;
+use crate::server_logic::{ChannelInfo, ContentData};
+use crate::storage::{FeedEntry, Listing};
+use crate::AppState;
+
+/// Same contents `/feed` serves
+const SCHEDULE_SIZE: i64 = 30;
+/// Short compositions loop until they have aired at least this long
+const MIN_AIRTIME_MS: i64 = 20_000;
+const MAX_AIRTIME_MS: i64 = 5 * 60 * 1000;
+
+/// Response of `GET /broadcast/now`
+#[derive(Serialize, Deserialize)]
+pub struct BroadcastNow {
+    pub channel: ChannelInfo,
+    pub content: ContentData,
+    /// How long the content has been on air
+    pub elapsed_ms: i64,
+    /// How long it stays on air in total
+    pub duration_ms: i64,
+}
+
+/// Time a content stays on air: whole loops of its composition, so that the music
+/// ends where it started. Compositions that can't be parsed air for `MIN_AIRTIME_MS`.
+fn airtime_ms(midi_composition: &str) -> i64 {
+    let loop_ms = MidiEngine::composition_duration(midi_composition)
+        .map(|seconds| (seconds * 1000.0).round() as i64)
+        .unwrap_or(0);
+
+    if loop_ms <= 0 {
+        return MIN_AIRTIME_MS;
+    }
+
+    let loops = (MIN_AIRTIME_MS + loop_ms - 1) / loop_ms;
+    (loops * loop_ms).min(MAX_AIRTIME_MS)
+}
+
+/// Index of the slot on air `offset_ms` into the schedule and how far into it we are.
+/// The schedule repeats back to back.
+fn locate(airtimes: &[i64], offset_ms: i64) -> Option<(usize, i64)> {
+    let cycle_ms: i64 = airtimes.iter().sum();
+    if cycle_ms <= 0 {
+        return None;
+    }
+
+    let mut position = offset_ms.rem_euclid(cycle_ms);
+    for (index, airtime) in airtimes.iter().enumerate() {
+        if position < *airtime {
+            return Some((index, position));
+        }
+        position -= airtime;
+    }
+    None
+}
+
+struct Slot {
+    entry: FeedEntry,
+    airtime_ms: i64,
+}
+
+/// The schedule on air, kept between requests and rebuilt when the feed changes
+#[derive(Default)]
+pub struct Broadcast {
+    schedule: Mutex<Option<Schedule>>,
+}
+
+struct Schedule {
+    /// Feed cache generation the slots were built at
+    generation: u64,
+    /// Unix time at which the next scheduled content goes live
+    expires_at: Option<i64>,
+    /// Unix time in ms at which the first slot aired, or would have
+    anchor_ms: i64,
+    slots: Vec<Slot>,
+}
+
+impl Schedule {
+    /// Slots for `entries`, in the order of `previous` with new contents joining the end of the
+    /// cycle. What was on air keeps playing, if it left the feed the next content starts now.
+    /// A first schedule is anchored at the Unix epoch and ordered by content id.
+    fn rebuild(previous: Option<Schedule>, mut entries: Vec<FeedEntry>, now_ms: i64) -> (i64, Vec<Slot>) {
+        entries.sort_by_key(|entry| entry.content.id);
+        let Some(previous) = previous else {
+            let slots = entries.into_iter().map(Slot::new).collect();
+            return (0, slots);
+        };
+
+        let on_air = previous.locate(now_ms);
+        let mut known: HashMap<i64, i64> = previous.slots.iter().map(|slot| (slot.entry.content.id, slot.airtime_ms)).collect();
+        let mut slots: Vec<Slot> = Vec::with_capacity(entries.len());
+        let mut added = Vec::new();
+        for entry in entries {
+            match known.remove(&entry.content.id) {
+                Some(airtime_ms) => slots.push(Slot { entry, airtime_ms }),
+                None => added.push(Slot::new(entry)),
+            }
+        }
+        let order: HashMap<i64, usize> = previous.slots.iter().enumerate().map(|(index, slot)| (slot.entry.content.id, index)).collect();
+        slots.sort_by_key(|slot| order[&slot.entry.content.id]);
+        slots.extend(added);
+
+        // Keep the content on air where it was, or start the one after it
+        let resume = on_air.and_then(|(index, elapsed_ms)| {
+            previous.slots[index..].iter().chain(&previous.slots[..index]).enumerate().find_map(|(step, old)| {
+                let position = slots.iter().position(|slot| slot.entry.content.id == old.entry.content.id)?;
+                Some((position, if step == 0 { elapsed_ms } else { 0 }))
+            })
+        });
+        let anchor_ms = match resume {
+            Some((position, elapsed_ms)) => {
+                now_ms - elapsed_ms - slots[..position].iter().map(|slot| slot.airtime_ms).sum::<i64>()
+            }
+            None => now_ms,
+        };
+        (anchor_ms, slots)
+    }
+
+    fn locate(&self, now_ms: i64) -> Option<(usize, i64)> {
+        let airtimes: Vec<i64> = self.slots.iter().map(|slot| slot.airtime_ms).collect();
+        locate(&airtimes, now_ms - self.anchor_ms)
+    }
+
+    fn now_playing(&self, now_ms: i64) -> Option<BroadcastNow> {
+        let (index, elapsed_ms) = self.locate(now_ms)?;
+        let slot = &self.slots[index];
+
+        Some(BroadcastNow {
+            channel: ChannelInfo { id: slot.entry.channel_id, name: slot.entry.channel_name.clone() },
+            content: ContentData::from(slot.entry.content.clone()),
+            elapsed_ms,
+            duration_ms: slot.airtime_ms,
+        })
+    }
+}
+
+impl Slot {
+    fn new(entry: FeedEntry) -> Self {
+        let airtime_ms = airtime_ms(&entry.content.midi_composition);
+        Self { entry, airtime_ms }
+    }
+}
+
+impl Broadcast {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn is_current(&self, generation: u64, now: i64) -> bool {
+        self.schedule.lock().is_ok_and(|schedule| {
+            schedule.as_ref().is_some_and(|schedule| {
+                schedule.generation == generation && schedule.expires_at.is_none_or(|expires_at| now < expires_at)
+            })
+        })
+    }
+
+    fn update(&self, generation: u64, expires_at: Option<i64>, entries: Vec<FeedEntry>, now_ms: i64) {
+        if let Ok(mut schedule) = self.schedule.lock() {
+            let (anchor_ms, slots) = Schedule::rebuild(schedule.take(), entries, now_ms);
+            *schedule = Some(Schedule { generation, expires_at, anchor_ms, slots });
+        }
+    }
+
+    fn now_playing(&self, now_ms: i64) -> Option<BroadcastNow> {
+        self.schedule.lock().ok()?.as_ref()?.now_playing(now_ms)
+    }
+}
+
+pub async fn get_broadcast_now(State(state): State<AppState>) -> Result<Response, (StatusCode, String)> {
+    let now = chrono::Utc::now();
+
+    // Same contents as the feed, so the schedule only changes when the feed does
+    let generation = state.feed_cache.generation();
+    if !state.broadcast.is_current(generation, now.timestamp()) {
+        let entries = state.storage.latest_contents(Listing::PublicAt(now.timestamp()), SCHEDULE_SIZE).await
+            .map_err(|e| internal_error("Failed to load the broadcast schedule", e))?;
+        let expires_at = state.storage.next_scheduled_publish(now.timestamp()).await
+            .map_err(|e| internal_error("Failed to load the broadcast schedule", e))?;
+        state.broadcast.update(generation, expires_at, entries, now.timestamp_millis());
+    }
+
+    let now_playing = state.broadcast.now_playing(now.timestamp_millis())
+        .ok_or((StatusCode::NOT_FOUND, "Nothing on air".to_string()))?;
+
+    // The offset is only right at the time of the request
+    Ok(([(header::CACHE_CONTROL, "no-store")], Json(now_playing)).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{ChannelStore, ContentRecord, ContentStore, MemoryStorage, NewContent};
+    use std::sync::Arc;
+
+    fn entry(id: i64, midi_composition: &str) -> FeedEntry {
+        FeedEntry {
+            channel_id: 1,
+            channel_name: "neko".to_string(),
+            content: ContentRecord {
+                id,
+                midi_composition: midi_composition.to_string(),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_airtime_is_whole_loops() {
+        // 1.5s loop, 14 loops to reach 20s
+        assert_eq!(airtime_ms("4c 4d 4e"), 21_000);
+        // 2s loop divides 20s
+        assert_eq!(airtime_ms("1c"), 20_000);
+        assert_eq!(airtime_ms("not midi"), MIN_AIRTIME_MS);
+        assert_eq!(airtime_ms(&"1c ".repeat(200)), MAX_AIRTIME_MS);
+    }
+
+    #[test]
+    fn test_locate() {
+        let airtimes = [10, 20, 30];
+        assert_eq!(locate(&airtimes, 0), Some((0, 0)));
+        assert_eq!(locate(&airtimes, 9), Some((0, 9)));
+        assert_eq!(locate(&airtimes, 10), Some((1, 0)));
+        assert_eq!(locate(&airtimes, 59), Some((2, 29)));
+        assert_eq!(locate(&airtimes, 60), Some((0, 0)));
+        assert_eq!(locate(&airtimes, 135), Some((1, 5)));
+        assert_eq!(locate(&[], 5), None);
+    }
+
+    fn schedule(previous: Option<Schedule>, entries: Vec<FeedEntry>, now_ms: i64) -> Schedule {
+        let (anchor_ms, slots) = Schedule::rebuild(previous, entries, now_ms);
+        Schedule { generation: 0, expires_at: None, anchor_ms, slots }
+    }
+
+    fn on_air(schedule: &Schedule, now_ms: i64) -> (i64, i64) {
+        let now_playing = schedule.now_playing(now_ms).unwrap();
+        (now_playing.content.id, now_playing.elapsed_ms)
+    }
+
+    #[test]
+    fn test_schedule_is_independent_of_feed_order() {
+        let now_ms = 25_000;
+        let newest_first = schedule(None, vec![entry(2, "1c"), entry(1, "1c")], now_ms);
+        let oldest_first = schedule(None, vec![entry(1, "1c"), entry(2, "1c")], now_ms);
+
+        assert_eq!(on_air(&newest_first, now_ms), (2, 5_000));
+        assert_eq!(on_air(&oldest_first, now_ms), (2, 5_000));
+        assert_eq!(newest_first.now_playing(now_ms).unwrap().duration_ms, 20_000);
+
+        assert!(schedule(None, vec![], now_ms).now_playing(now_ms).is_none());
+    }
+
+    #[test]
+    fn test_new_contents_join_the_end_of_the_cycle() {
+        let first = schedule(None, vec![entry(1, "1c"), entry(2, "1c")], 25_000);
+
+        // Content 2 keeps playing, 3 airs after it, then the cycle starts over
+        let second = schedule(Some(first), vec![entry(1, "1c"), entry(2, "1c"), entry(3, "1c")], 26_000);
+        assert_eq!(on_air(&second, 26_000), (2, 6_000));
+        assert_eq!(on_air(&second, 40_000), (3, 0));
+        assert_eq!(on_air(&second, 60_000), (1, 0));
+
+        // A content going live late still joins the end, whatever its id
+        let third = schedule(Some(second), vec![entry(0, "1c"), entry(1, "1c"), entry(2, "1c"), entry(3, "1c")], 61_000);
+        assert_eq!(on_air(&third, 61_000), (1, 1_000));
+        assert_eq!(on_air(&third, 120_000), (0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_schedule_follows_the_feed_cache() {
+        let storage = Arc::new(MemoryStorage::new());
+        let channel = storage.create_channel("neko", "hash", 1).await.unwrap();
+        let new_content = |id: i64| NewContent {
+            channel_id: channel.id,
+            name: format!("song{id}"),
+            midi_composition: "1c".to_string(),
+            ..Default::default()
+        };
+        storage.create_content(new_content(1)).await.unwrap();
+        let state = AppState::for_tests(storage.clone());
+
+        get_broadcast_now(State(state.clone())).await.unwrap();
+        storage.create_content(new_content(2)).await.unwrap();
+        get_broadcast_now(State(state.clone())).await.unwrap();
+        let slots = |state: &AppState| state.broadcast.schedule.lock().unwrap().as_ref().unwrap().slots.len();
+        assert_eq!(slots(&state), 1);
+
+        state.feed_cache.invalidate();
+        get_broadcast_now(State(state.clone())).await.unwrap();
+        assert_eq!(slots(&state), 2);
+    }
+
+    #[test]
+    fn test_removed_content_hands_over_to_the_next_one() {
+        let first = schedule(None, vec![entry(1, "1c"), entry(2, "1c"), entry(3, "1c")], 25_000);
+        assert_eq!(on_air(&first, 25_000), (2, 5_000));
+
+        let second = schedule(Some(first), vec![entry(1, "1c"), entry(3, "1c")], 26_000);
+        assert_eq!(on_air(&second, 26_000), (3, 0));
+        assert_eq!(on_air(&second, 46_000), (1, 0));
+    }
+}
@@ -2,50 +2,78 @@ use axum::{
     extract::{Json, State},
     http::{HeaderMap, StatusCode},
 };
-use rusqlite::params;
 
+use crate::error::{database_error, internal_error};
+use crate::storage::NewContent;
 use crate::{auth, AppState};
-use tama::api::{CreateContentRequest, CreateContentResponse};
+use tama::api::{CreateContentRequest, CreateContentResponse, RegisterKeyRequest, Visibility};
+use tama::ascii_art_converter::AsciiArtSheet;
+use tama::signing;
 
 const MAX_CONTENT_NAME_LENGTH: usize = 200;
 const MAX_ART_SIZE: usize = 100_000; // 100KB
 const MAX_MIDI_SIZE: usize = 50_000; // 50KB
 const MIN_FPS: f32 = 0.1;
 const MAX_FPS: f32 = 120.0;
+const MAX_SCHEDULE_AHEAD_SECONDS: i64 = 365 * 24 * 60 * 60;
+
+/// Publish time to store, past times publish right away
+fn validate_publish_at(publish_at: Option<i64>, now: i64) -> Result<Option<i64>, String> {
+    match publish_at {
+        Some(publish_at) if publish_at > now + MAX_SCHEDULE_AHEAD_SECONDS => {
+            Err("Contents can be scheduled at most one year ahead".to_string())
+        }
+        Some(publish_at) if publish_at > now => Ok(Some(publish_at)),
+        _ => Ok(None),
+    }
+}
 
-fn validate_content_upload(request: &CreateContentRequest) -> Result<(), &'static str> {
+fn validate_content_upload(request: &CreateContentRequest) -> Result<(), String> {
     // Validate content name
+    if request.name.trim().is_empty() {
+        return Err("Content name cannot be empty".to_string());
+    }
+
     if request.name.len() > MAX_CONTENT_NAME_LENGTH {
-        return Err("Content name is too long");
+        return Err("Content name is too long".to_string());
     }
 
+    validate_content_payload(&request.art, &request.midi, request.fps)
+}
+
+/// Checks what plays, shared by uploads and mirrored contents
+pub(crate) fn validate_content_payload(art: &str, midi: &str, fps: f32) -> Result<(), String> {
     // Validate FPS range
-    if request.fps < MIN_FPS || request.fps > MAX_FPS {
-        return Err("FPS must be between 0.1 and 120.0");
+    if !(MIN_FPS..=MAX_FPS).contains(&fps) {
+        return Err("FPS must be between 0.1 and 120.0".to_string());
     }
 
     // Validate art size
-    if request.art.len() > MAX_ART_SIZE {
-        return Err("Art content is too large (max 100KB)");
+    if art.len() > MAX_ART_SIZE {
+        return Err("Art content is too large (max 100KB)".to_string());
     }
 
-    if request.art.trim().is_empty() {
-        return Err("Art content cannot be empty");
+    if art.trim().is_empty() {
+        return Err("Art content cannot be empty".to_string());
     }
 
+    // Catch malformed art here rather than in every client's player
+    AsciiArtSheet::validate(art)
+        .map_err(|e| format!("Invalid ASCII art: {e}"))?;
+
     // Validate MIDI composition size
-    if request.midi.len() > MAX_MIDI_SIZE {
-        return Err("MIDI composition is too large (max 50KB)");
+    if midi.len() > MAX_MIDI_SIZE {
+        return Err("MIDI composition is too large (max 50KB)".to_string());
     }
 
-    if request.midi.trim().is_empty() {
-        return Err("MIDI composition cannot be empty");
+    if midi.trim().is_empty() {
+        return Err("MIDI composition cannot be empty".to_string());
     }
 
     // Validate that MIDI can be parsed (without requiring audio output)
     use tama::midi_composer::MidiEngine;
-    MidiEngine::validate_midi_composition(&request.midi)
-        .map_err(|_| "Invalid MIDI composition format")?;
+    MidiEngine::validate_midi_composition(midi)
+        .map_err(|_| "Invalid MIDI composition format".to_string())?;
 
     Ok(())
 }
@@ -55,67 +83,137 @@ pub async fn create_content(
     headers: HeaderMap,
     Json(request): Json<CreateContentRequest>,
 ) -> Result<Json<CreateContentResponse>, (StatusCode, String)> {
-    let channel_id = auth::authenticate_request(&headers, &state.jwt_secret)
+    let caller = auth::authenticate_caller(&headers, &state.jwt_secret)
         .map_err(|status| (status, "Authentication failed".to_string()))?;
 
-    if channel_id != request.channel_id {
-        return Err((StatusCode::FORBIDDEN, "Channel ID mismatch".to_string()));
+    let role = auth::caller_role(state.storage.as_ref(), &caller, request.channel_id).await
+        .map_err(database_error)?;
+
+    if !role.is_some_and(|role| role.can_edit()) {
+        return Err((StatusCode::FORBIDDEN, "Uploading requires owner or editor rights on this channel".to_string()));
     }
 
     // Validate content before processing
     validate_content_upload(&request)
-        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
 
-    let db = state.db.get()
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Database connection error: {e}")))?;
+    let channel = state.storage.find_channel_by_id(request.channel_id).await
+        .map_err(database_error)?
+        .ok_or((StatusCode::NOT_FOUND, "Channel not found".to_string()))?;
 
-    let channel_exists: Result<i64, _> = db.query_row(
-        "SELECT id FROM channels WHERE id = ?1",
-        params![request.channel_id],
-        |row| row.get(0),
-    );
+    let now = chrono::Utc::now().timestamp();
+    let publish_at = validate_publish_at(request.publish_at, now)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
 
-    if channel_exists.is_err() {
-        return Err((StatusCode::NOT_FOUND, "Channel not found".to_string()));
-    }
+    let share_token = (request.visibility == Visibility::Unlisted)
+        .then(|| uuid::Uuid::new_v4().simple().to_string());
 
-    let now = chrono::Utc::now().timestamp();
+    let public_key = match &request.signature {
+        Some(signature) => Some(verify_signature(&state, &request, signature).await?),
+        None => None,
+    };
 
-    db.execute(
-        "INSERT INTO contents (channel_id, name, art, midi_composition, fps, created_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-        params![
-            request.channel_id,
+    let content = state.storage.create_content(NewContent {
+        channel_id: request.channel_id,
+        name: request.name.clone(),
+        art: request.art,
+        midi_composition: request.midi,
+        fps: request.fps,
+        created_at: now,
+        visibility: request.visibility,
+        publish_at,
+        share_token,
+        signature: request.signature,
+        public_key,
+        origin: None,
+    })
+    .await
+    .map_err(|e| internal_error("Failed to insert content", e))?;
+
+    state.feed_cache.invalidate();
+    state.webhooks.content_created(&channel, &content);
+
+    let message = match content.publish_at {
+        Some(publish_at) => format!(
+            "Content '{}' uploaded as {}, scheduled for {}",
             request.name,
-            request.art,
-            request.midi,
-            request.fps,
-            now
-        ],
-    )
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to insert content: {e}")))?;
-
-    let content_id = db.last_insert_rowid();
+            content.visibility.as_str(),
+            chrono::DateTime::from_timestamp(publish_at, 0).unwrap_or_default().to_rfc3339()
+        ),
+        None => format!("Content '{}' uploaded as {}", request.name, content.visibility.as_str()),
+    };
 
     Ok(Json(CreateContentResponse {
-        id: content_id,
+        id: content.id,
         channel_id: request.channel_id,
-        message: format!("Content '{}' uploaded successfully", request.name),
+        message,
+        share_token: content.share_token,
     }))
 }
 
+/// Checks the upload's signature against the channel key, returning the key to store with it
+async fn verify_signature(
+    state: &AppState,
+    request: &CreateContentRequest,
+    signature: &str,
+) -> Result<String, (StatusCode, String)> {
+    let public_key_pem = state.storage.find_public_key(request.channel_id).await
+        .map_err(database_error)?
+        .ok_or((StatusCode::BAD_REQUEST, "Signed upload, but the channel has no registered key".to_string()))?;
+
+    let public_key = signing::public_key_from_pem(&public_key_pem)
+        .map_err(|e| internal_error("Stored key is unusable", e))?;
+
+    if !signing::verify_content(&public_key, &request.art, &request.midi, request.fps, signature) {
+        return Err((StatusCode::BAD_REQUEST, "Signature does not match the content or the channel key".to_string()));
+    }
+
+    Ok(public_key_pem)
+}
+
+/// Registers the key the caller's channel signs uploads with. Owners only.
+pub async fn register_key(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<RegisterKeyRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let caller = auth::authenticate_caller(&headers, &state.jwt_secret)
+        .map_err(|status| (status, "Authentication required".to_string()))?;
+
+    let role = auth::caller_role(state.storage.as_ref(), &caller, caller.channel_id).await
+        .map_err(database_error)?;
+
+    if !role.is_some_and(|role| role.can_manage()) {
+        return Err((StatusCode::FORBIDDEN, "Only the channel owner can register a key".to_string()));
+    }
+
+    signing::public_key_from_pem(&request.public_key)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+    let now = chrono::Utc::now().timestamp();
+    state.storage.save_public_key(caller.channel_id, request.public_key.trim(), now).await
+        .map_err(|e| internal_error("Failed to save key", e))?;
+
+    tracing::info!("Public key registered: channel_id={}", caller.channel_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::{bearer, member_headers, owner_headers};
+
+    const VALID_ART: &str = "Ascii Art Animation, 8x2, 10fps\n⠀⠀⠀⠀⠀⠀⠀⠀\n⠀⠀⠀⠀⠀⠀⠀⠀";
 
     #[test]
     fn test_validate_content_upload_valid() {
         let request = CreateContentRequest {
             channel_id: 1,
             name: "Test Content".to_string(),
-            art: "⠀⠀⠀⠀⠀⠀⠀⠀\n⠀⠀⠀⠀⠀⠀⠀⠀".to_string(),
+            art: VALID_ART.to_string(),
             midi: "4c 4e 4g".to_string(),
             fps: 10.0,
+            ..Default::default()
         };
 
         assert!(validate_content_upload(&request).is_ok());
@@ -126,9 +224,10 @@ mod tests {
         let request = CreateContentRequest {
             channel_id: 1,
             name: "".to_string(),
-            art: "⠀⠀⠀⠀⠀⠀⠀⠀".to_string(),
+            art: VALID_ART.to_string(),
             midi: "4c 4e 4g".to_string(),
             fps: 10.0,
+            ..Default::default()
         };
 
         assert!(validate_content_upload(&request).is_err());
@@ -139,9 +238,10 @@ mod tests {
         let request = CreateContentRequest {
             channel_id: 1,
             name: "a".repeat(MAX_CONTENT_NAME_LENGTH + 1),
-            art: "⠀⠀⠀⠀⠀⠀⠀⠀".to_string(),
+            art: VALID_ART.to_string(),
             midi: "4c 4e 4g".to_string(),
             fps: 10.0,
+            ..Default::default()
         };
 
         assert!(validate_content_upload(&request).is_err());
@@ -152,9 +252,10 @@ mod tests {
         let request = CreateContentRequest {
             channel_id: 1,
             name: "Test".to_string(),
-            art: "⠀⠀⠀⠀⠀⠀⠀⠀".to_string(),
+            art: VALID_ART.to_string(),
             midi: "4c 4e 4g".to_string(),
             fps: 0.05,
+            ..Default::default()
         };
 
         assert!(validate_content_upload(&request).is_err());
@@ -165,9 +266,10 @@ mod tests {
         let request = CreateContentRequest {
             channel_id: 1,
             name: "Test".to_string(),
-            art: "⠀⠀⠀⠀⠀⠀⠀⠀".to_string(),
+            art: VALID_ART.to_string(),
             midi: "4c 4e 4g".to_string(),
             fps: 150.0,
+            ..Default::default()
         };
 
         assert!(validate_content_upload(&request).is_err());
@@ -181,6 +283,7 @@ mod tests {
             art: "a".repeat(MAX_ART_SIZE + 1),
             midi: "4c 4e 4g".to_string(),
             fps: 10.0,
+            ..Default::default()
         };
 
         assert!(validate_content_upload(&request).is_err());
@@ -194,19 +297,36 @@ mod tests {
             art: "".to_string(),
             midi: "4c 4e 4g".to_string(),
             fps: 10.0,
+            ..Default::default()
         };
 
         assert!(validate_content_upload(&request).is_err());
     }
 
+    #[test]
+    fn test_validate_content_upload_malformed_art() {
+        let request = CreateContentRequest {
+            channel_id: 1,
+            name: "Test".to_string(),
+            art: "Ascii Art Animation, 8x2\n⠀⠀⠀⠀⠀⠀⠀⠀\n⠀⠀⠀⠀⠀⠀".to_string(),
+            midi: "4c 4e 4g".to_string(),
+            fps: 10.0,
+            ..Default::default()
+        };
+
+        let err = validate_content_upload(&request).unwrap_err();
+        assert_eq!(err, "Invalid ASCII art: Frame 1, line 2 (line 3 of the art): expected 8 characters, found 6");
+    }
+
     #[test]
     fn test_validate_content_upload_midi_too_large() {
         let request = CreateContentRequest {
             channel_id: 1,
             name: "Test".to_string(),
-            art: "⠀⠀⠀⠀⠀⠀⠀⠀".to_string(),
+            art: VALID_ART.to_string(),
             midi: "4c ".repeat(MAX_MIDI_SIZE),
             fps: 10.0,
+            ..Default::default()
         };
 
         assert!(validate_content_upload(&request).is_err());
@@ -217,9 +337,10 @@ mod tests {
         let request = CreateContentRequest {
             channel_id: 1,
             name: "Test".to_string(),
-            art: "⠀⠀⠀⠀⠀⠀⠀⠀".to_string(),
+            art: VALID_ART.to_string(),
             midi: "".to_string(),
             fps: 10.0,
+            ..Default::default()
         };
 
         assert!(validate_content_upload(&request).is_err());
@@ -230,11 +351,181 @@ mod tests {
         let request = CreateContentRequest {
             channel_id: 1,
             name: "Test".to_string(),
-            art: "⠀⠀⠀⠀⠀⠀⠀⠀".to_string(),
+            art: VALID_ART.to_string(),
             midi: "invalid midi notes xyz".to_string(),
             fps: 10.0,
+            ..Default::default()
         };
 
         assert!(validate_content_upload(&request).is_err());
     }
+
+    #[tokio::test]
+    async fn test_create_content_stores_upload() {
+        use crate::storage::{ChannelStore, ContentStore, MemoryStorage};
+        use std::sync::Arc;
+
+        let storage = Arc::new(MemoryStorage::new());
+        let channel = storage.create_channel("neko", "hash", 1).await.unwrap();
+        let state = AppState::for_tests(storage.clone());
+
+        let headers = owner_headers(&state, &channel);
+
+        let request = CreateContentRequest {
+            channel_id: channel.id,
+            name: "Test".to_string(),
+            art: VALID_ART.to_string(),
+            midi: "4c 4e 4g".to_string(),
+            fps: 10.0,
+            ..Default::default()
+        };
+
+        let generation = state.feed_cache.get(0).unwrap_err();
+        state.feed_cache.store(generation, "[]".into(), None);
+
+        let Json(response) = create_content(State(state.clone()), headers, Json(request)).await.unwrap();
+        let stored = storage.find_content(response.id).await.unwrap().unwrap();
+        assert_eq!(stored.channel_id, channel.id);
+        assert_eq!(stored.midi_composition, "4c 4e 4g");
+        assert!(state.feed_cache.get(0).is_err(), "upload should invalidate the feed cache");
+        assert_eq!(stored.visibility, Visibility::Public);
+        assert_eq!(response.share_token, None);
+    }
+
+    #[tokio::test]
+    async fn test_create_unlisted_scheduled_content() {
+        use crate::storage::{ChannelStore, ContentStore, MemoryStorage};
+        use std::sync::Arc;
+
+        let storage = Arc::new(MemoryStorage::new());
+        let channel = storage.create_channel("neko", "hash", 1).await.unwrap();
+        let state = AppState::for_tests(storage.clone());
+
+        let headers = owner_headers(&state, &channel);
+
+        let publish_at = chrono::Utc::now().timestamp() + 3600;
+        let request = CreateContentRequest {
+            channel_id: channel.id,
+            name: "Test".to_string(),
+            art: VALID_ART.to_string(),
+            midi: "4c 4e 4g".to_string(),
+            fps: 10.0,
+            visibility: Visibility::Unlisted,
+            publish_at: Some(publish_at),
+            signature: None,
+        };
+
+        let Json(response) = create_content(State(state), headers, Json(request)).await.unwrap();
+        let stored = storage.find_content(response.id).await.unwrap().unwrap();
+        assert_eq!(stored.visibility, Visibility::Unlisted);
+        assert_eq!(stored.publish_at, Some(publish_at));
+        assert!(response.share_token.is_some());
+        assert_eq!(stored.share_token, response.share_token);
+    }
+
+    #[test]
+    fn test_validate_publish_at() {
+        assert_eq!(validate_publish_at(None, 100), Ok(None));
+        assert_eq!(validate_publish_at(Some(50), 100), Ok(None));
+        assert_eq!(validate_publish_at(Some(150), 100), Ok(Some(150)));
+        assert!(validate_publish_at(Some(100 + MAX_SCHEDULE_AHEAD_SECONDS + 1), 100).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_create_content_rejects_other_channel() {
+        use crate::storage::MemoryStorage;
+        use std::sync::Arc;
+
+        let state = AppState::for_tests(Arc::new(MemoryStorage::new()));
+        let headers = bearer(&crate::jwt::create_jwt(1, "neko", &state.jwt_secret).unwrap());
+
+        let request = CreateContentRequest {
+            channel_id: 2,
+            name: "Test".to_string(),
+            art: VALID_ART.to_string(),
+            midi: "4c 4e 4g".to_string(),
+            fps: 10.0,
+            ..Default::default()
+        };
+
+        let result = create_content(State(state), headers, Json(request)).await;
+        assert_eq!(result.err().map(|(status, _)| status), Some(StatusCode::FORBIDDEN));
+    }
+
+    #[tokio::test]
+    async fn test_create_content_requires_editor_role() {
+        use crate::storage::{ChannelStore, MemberStore, MemoryStorage};
+        use std::sync::Arc;
+        use tama::api::Role;
+
+        let storage = Arc::new(MemoryStorage::new());
+        let channel = storage.create_channel("team", "hash", 1).await.unwrap();
+        let state = AppState::for_tests(storage.clone());
+
+        let upload = |user_id: i64, user_name: &str| {
+            let headers = member_headers(&state, &channel, user_id, user_name);
+            let request = CreateContentRequest {
+                channel_id: channel.id,
+                name: "Test".to_string(),
+                art: VALID_ART.to_string(),
+                midi: "4c 4e 4g".to_string(),
+                fps: 10.0,
+                ..Default::default()
+            };
+            create_content(State(state.clone()), headers, Json(request))
+        };
+
+        let editor = storage.create_user("alice", "hash", 1).await.unwrap();
+        let viewer = storage.create_user("bob", "hash", 1).await.unwrap();
+        storage.set_member(channel.id, editor.id, Role::Editor, 2).await.unwrap();
+        storage.set_member(channel.id, viewer.id, Role::Viewer, 2).await.unwrap();
+
+        assert!(upload(editor.id, "alice").await.is_ok());
+        assert_eq!(upload(viewer.id, "bob").await.err().map(|(status, _)| status), Some(StatusCode::FORBIDDEN));
+
+        storage.remove_member(channel.id, editor.id).await.unwrap();
+        assert_eq!(upload(editor.id, "alice").await.err().map(|(status, _)| status), Some(StatusCode::FORBIDDEN));
+    }
+
+    #[tokio::test]
+    async fn test_signed_upload_is_verified_and_stored() {
+        use crate::storage::{ChannelStore, ContentStore, MemoryStorage};
+        use std::sync::Arc;
+
+        let storage = Arc::new(MemoryStorage::new());
+        let channel = storage.create_channel("neko", "hash", 1).await.unwrap();
+        let state = AppState::for_tests(storage.clone());
+
+        let headers = owner_headers(&state, &channel);
+
+        let private_key = signing::generate_private_key().unwrap();
+        let request = CreateContentRequest {
+            channel_id: channel.id,
+            name: "Test".to_string(),
+            art: VALID_ART.to_string(),
+            midi: "4c 4e 4g".to_string(),
+            fps: 10.0,
+            signature: Some(signing::sign_content(&private_key, VALID_ART, "4c 4e 4g", 10.0).unwrap()),
+            ..Default::default()
+        };
+
+        // No key registered yet
+        let result = create_content(State(state.clone()), headers.clone(), Json(request.clone())).await;
+        assert_eq!(result.err().map(|(status, _)| status), Some(StatusCode::BAD_REQUEST));
+
+        let public_key = signing::public_key_to_pem(&private_key).unwrap();
+        let status = register_key(State(state.clone()), headers.clone(), Json(RegisterKeyRequest { public_key: public_key.clone() }))
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let tampered = CreateContentRequest { midi: "4c 4e 4a".to_string(), ..request.clone() };
+        let result = create_content(State(state.clone()), headers.clone(), Json(tampered)).await;
+        assert_eq!(result.err().map(|(status, _)| status), Some(StatusCode::BAD_REQUEST));
+
+        let Json(response) = create_content(State(state), headers, Json(request.clone())).await.unwrap();
+        let stored = storage.find_content(response.id).await.unwrap().unwrap();
+        assert_eq!(stored.signature, request.signature);
+        assert_eq!(stored.public_key.as_deref(), Some(public_key.trim()));
+    }
 }
@@ -0,0 +1,258 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::{HeaderMap, StatusCode},
+};
+use serde::Deserialize;
+
+use crate::error::{database_error, internal_error};
+use crate::storage::{CommentRecord, NewComment};
+use crate::{auth, visibility, AppState};
+use tama::api::{CommentInfo, CreateCommentRequest};
+
+const MAX_COMMENT_LENGTH: usize = 500;
+const DEFAULT_COMMENTS_LIMIT: i64 = 50;
+const MAX_COMMENTS_LIMIT: i64 = 200;
+
+#[derive(Deserialize)]
+pub struct CommentsParams {
+    #[serde(default = "default_comments_limit")]
+    pub limit: i64,
+}
+
+fn default_comments_limit() -> i64 {
+    DEFAULT_COMMENTS_LIMIT
+}
+
+/// Trimmed comment body. Comments are shown on a single line in terminals,
+/// so control characters, newlines included, are refused.
+fn validate_comment_body(body: &str) -> Result<String, String> {
+    let body = body.trim();
+
+    if body.is_empty() {
+        return Err("Comment cannot be empty".to_string());
+    }
+
+    if body.chars().count() > MAX_COMMENT_LENGTH {
+        return Err(format!("Comment is too long (max {MAX_COMMENT_LENGTH} characters)"));
+    }
+
+    if body.chars().any(char::is_control) {
+        return Err("Comment cannot contain control characters or line breaks".to_string());
+    }
+
+    Ok(body.to_string())
+}
+
+fn comment_info(comment: CommentRecord) -> CommentInfo {
+    CommentInfo {
+        id: comment.id,
+        content_id: comment.content_id,
+        channel_id: comment.author_channel_id,
+        author: comment.author_name,
+        body: comment.body,
+        created_at: comment.created_at,
+    }
+}
+
+/// Most recent comments first, for anyone who may see the content
+pub async fn list_comments(
+    State(state): State<AppState>,
+    Path(content_id): Path<i64>,
+    headers: HeaderMap,
+    Query(params): Query<CommentsParams>,
+) -> Result<Json<Vec<CommentInfo>>, (StatusCode, String)> {
+    let content = visibility::find_visible_content(&state, content_id, &headers).await?;
+    let limit = params.limit.clamp(1, MAX_COMMENTS_LIMIT);
+
+    let comments = state.storage.list_comments(content.id, limit).await
+        .map_err(database_error)?
+        .into_iter()
+        .map(comment_info)
+        .collect();
+
+    Ok(Json(comments))
+}
+
+pub async fn create_comment(
+    State(state): State<AppState>,
+    Path(content_id): Path<i64>,
+    headers: HeaderMap,
+    Json(request): Json<CreateCommentRequest>,
+) -> Result<Json<CommentInfo>, (StatusCode, String)> {
+    let caller = auth::authenticate_caller(&headers, &state.jwt_secret)
+        .map_err(|status| (status, "Authentication required".to_string()))?;
+
+    // Members removed from the channel keep a valid token, but no longer speak for it
+    let role = auth::caller_role(state.storage.as_ref(), &caller, caller.channel_id).await
+        .map_err(database_error)?;
+    if role.is_none() {
+        return Err((StatusCode::FORBIDDEN, "Not a member of this channel".to_string()));
+    }
+
+    let body = validate_comment_body(&request.body)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+    let content = visibility::find_visible_content(&state, content_id, &headers).await?;
+
+    let author_name = match caller.user_id {
+        Some(user_id) => state.storage.find_user_by_id(user_id).await
+            .map_err(database_error)?
+            .map(|user| user.name),
+        None => state.storage.find_channel_by_id(caller.channel_id).await
+            .map_err(database_error)?
+            .map(|channel| channel.name),
+    }
+    .ok_or((StatusCode::UNAUTHORIZED, "Authentication required".to_string()))?;
+
+    let comment = state.storage.create_comment(&NewComment {
+        content_id: content.id,
+        author_channel_id: caller.channel_id,
+        author_user_id: caller.user_id,
+        author_name,
+        body,
+        created_at: chrono::Utc::now().timestamp(),
+    }).await
+        .map_err(|e| internal_error("Failed to save comment", e))?;
+
+    tracing::info!("Comment created: id={}, content_id={}, author={}", comment.id, comment.content_id, comment.author_name);
+    Ok(Json(comment_info(comment)))
+}
+
+/// Comments can be deleted by whoever wrote them and by the owner of the content's channel
+pub async fn delete_comment(
+    State(state): State<AppState>,
+    Path((content_id, comment_id)): Path<(i64, i64)>,
+    headers: HeaderMap,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let caller = auth::authenticate_caller(&headers, &state.jwt_secret)
+        .map_err(|status| (status, "Authentication required".to_string()))?;
+
+    let not_found = || (StatusCode::NOT_FOUND, "Comment not found".to_string());
+    let comment = state.storage.find_comment(comment_id).await
+        .map_err(database_error)?
+        .filter(|comment| comment.content_id == content_id)
+        .ok_or_else(not_found)?;
+
+    let is_author = comment.author_channel_id == caller.channel_id && comment.author_user_id == caller.user_id;
+    let is_content_owner = match state.storage.find_content(content_id).await.map_err(database_error)? {
+        Some(content) => auth::caller_role(state.storage.as_ref(), &caller, content.channel_id).await
+            .map_err(database_error)?
+            .is_some_and(|role| role.can_manage()),
+        None => false,
+    };
+
+    if !is_author && !is_content_owner {
+        return Err((StatusCode::FORBIDDEN, "Only the author or the content owner can delete a comment".to_string()));
+    }
+
+    if !state.storage.delete_comment(comment.id).await.map_err(database_error)? {
+        return Err(not_found());
+    }
+
+    tracing::info!("Comment deleted: id={}, content_id={}", comment.id, content_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{owner_headers};
+    use crate::storage::{ChannelStore, ContentStore, MemoryStorage, NewContent};
+    use std::sync::Arc;
+    use tama::api::Visibility;
+
+    fn comment(body: &str) -> Json<CreateCommentRequest> {
+        Json(CreateCommentRequest { body: body.to_string() })
+    }
+
+    fn params() -> Query<CommentsParams> {
+        Query(CommentsParams { limit: DEFAULT_COMMENTS_LIMIT })
+    }
+
+    /// State with two channels and a public content of the first one
+    async fn setup() -> (AppState, i64, HeaderMap, HeaderMap) {
+        let storage = Arc::new(MemoryStorage::new());
+        let owner = storage.create_channel("neko", "hash", 1).await.unwrap();
+        let guest = storage.create_channel("tora", "hash", 1).await.unwrap();
+        let content = storage.create_content(NewContent {
+            channel_id: owner.id,
+            name: "nap".to_string(),
+            created_at: 1,
+            ..Default::default()
+        }).await.unwrap();
+
+        let state = AppState::for_tests(storage);
+        let (owner, guest) = (owner_headers(&state, &owner), owner_headers(&state, &guest));
+        (state, content.id, owner, guest)
+    }
+
+    #[test]
+    fn test_validate_comment_body() {
+        assert_eq!(validate_comment_body("  nice nap  "), Ok("nice nap".to_string()));
+        assert!(validate_comment_body("   ").is_err());
+        assert!(validate_comment_body("two\nlines").is_err());
+        assert!(validate_comment_body("\u{1b}[2J").is_err());
+        assert!(validate_comment_body(&"a".repeat(MAX_COMMENT_LENGTH)).is_ok());
+        assert!(validate_comment_body(&"a".repeat(MAX_COMMENT_LENGTH + 1)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_post_and_list_comments() {
+        let (state, content_id, _, guest) = setup().await;
+
+        let anonymous = create_comment(State(state.clone()), Path(content_id), HeaderMap::new(), comment("hi")).await;
+        assert_eq!(anonymous.unwrap_err().0, StatusCode::UNAUTHORIZED);
+
+        let missing = create_comment(State(state.clone()), Path(999), guest.clone(), comment("hi")).await;
+        assert_eq!(missing.unwrap_err().0, StatusCode::NOT_FOUND);
+
+        for body in ["first", "second"] {
+            let Json(created) = create_comment(State(state.clone()), Path(content_id), guest.clone(), comment(body)).await.unwrap();
+            assert_eq!(created.author, "tora");
+        }
+
+        let Json(comments) = list_comments(State(state), Path(content_id), HeaderMap::new(), params()).await.unwrap();
+        assert_eq!(comments.iter().map(|c| c.body.as_str()).collect::<Vec<_>>(), vec!["second", "first"]);
+    }
+
+    #[tokio::test]
+    async fn test_comments_follow_content_visibility() {
+        let (state, _, owner, guest) = setup().await;
+        let private = state.storage.create_content(NewContent {
+            channel_id: 1,
+            name: "secret".to_string(),
+            visibility: Visibility::Private,
+            ..Default::default()
+        }).await.unwrap();
+
+        let result = create_comment(State(state.clone()), Path(private.id), guest.clone(), comment("hi")).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::NOT_FOUND);
+        let result = list_comments(State(state.clone()), Path(private.id), guest, params()).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::NOT_FOUND);
+
+        assert!(create_comment(State(state.clone()), Path(private.id), owner.clone(), comment("note to self")).await.is_ok());
+        let Json(comments) = list_comments(State(state), Path(private.id), owner, params()).await.unwrap();
+        assert_eq!(comments.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_delete_comment_by_author_or_content_owner() {
+        let (state, content_id, owner, guest) = setup().await;
+        let storage = state.storage.clone();
+        let third = storage.create_channel("kuro", "hash", 1).await.unwrap();
+        let third = owner_headers(&state, &third);
+
+        let Json(first) = create_comment(State(state.clone()), Path(content_id), guest.clone(), comment("first")).await.unwrap();
+        let Json(second) = create_comment(State(state.clone()), Path(content_id), guest.clone(), comment("second")).await.unwrap();
+
+        let result = delete_comment(State(state.clone()), Path((content_id, first.id)), third).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::FORBIDDEN);
+
+        let result = delete_comment(State(state.clone()), Path((content_id + 1, first.id)), guest.clone()).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::NOT_FOUND);
+
+        assert_eq!(delete_comment(State(state.clone()), Path((content_id, first.id)), guest).await.unwrap(), StatusCode::NO_CONTENT);
+        assert_eq!(delete_comment(State(state.clone()), Path((content_id, second.id)), owner).await.unwrap(), StatusCode::NO_CONTENT);
+        assert!(storage.list_comments(content_id, 10).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,15 @@
+use axum::http::StatusCode;
+use std::fmt::Display;
+
+use crate::storage::StorageError;
+
+/// Logs a storage failure and answers 500, without telling the client what went wrong
+pub fn database_error(e: StorageError) -> (StatusCode, String) {
+    internal_error("Database error", e)
+}
+
+/// Logs an unexpected failure along with what was being done, and answers 500 with a generic message
+pub fn internal_error(context: &str, e: impl Display) -> (StatusCode, String) {
+    tracing::error!("{}: {}", context, e);
+    (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error".to_string())
+}
@@ -0,0 +1,129 @@
+use axum::body::Bytes;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+
+/// Serialized `/feed` response, rebuilt lazily after every change to the contents
+/// and whenever a scheduled content goes live
+#[derive(Default)]
+pub struct FeedCache {
+    body: RwLock<Option<CachedFeed>>,
+    generation: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+struct CachedFeed {
+    body: Bytes,
+    /// Unix time at which the body goes stale
+    expires_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeedCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl FeedCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the body cached as of `now`, or the current generation to pass to `store` on a miss
+    pub fn get(&self, now: i64) -> Result<Bytes, u64> {
+        // Read the generation first so an invalidation racing with the rebuild wins
+        let generation = self.generation.load(Ordering::Acquire);
+
+        let cached = self.body.read().ok().and_then(|cached| {
+            cached
+                .as_ref()
+                .filter(|feed| feed.expires_at.is_none_or(|expires_at| now < expires_at))
+                .map(|feed| feed.body.clone())
+        });
+
+        if let Some(body) = cached {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(body);
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        Err(generation)
+    }
+
+    /// Caches `body` until `expires_at`, unless the feed was invalidated after `generation` was read
+    pub fn store(&self, generation: u64, body: Bytes, expires_at: Option<i64>) {
+        if let Ok(mut cached) = self.body.write()
+            && self.generation.load(Ordering::Acquire) == generation
+        {
+            *cached = Some(CachedFeed { body, expires_at });
+        }
+    }
+
+    /// Call after any commit that changes what `/feed` returns
+    pub fn invalidate(&self) {
+        if let Ok(mut cached) = self.body.write() {
+            self.generation.fetch_add(1, Ordering::AcqRel);
+            *cached = None;
+        }
+    }
+
+    /// Bumped by every `invalidate`, for data derived from the feed
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    pub fn stats(&self) -> FeedCacheStats {
+        FeedCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_miss_then_hit() {
+        let cache = FeedCache::new();
+
+        let generation = cache.get(0).unwrap_err();
+        cache.store(generation, Bytes::from_static(b"[]"), None);
+
+        assert_eq!(cache.get(0).unwrap(), Bytes::from_static(b"[]"));
+        assert_eq!(cache.stats(), FeedCacheStats { hits: 1, misses: 1 });
+    }
+
+    #[test]
+    fn test_invalidate_clears_body() {
+        let cache = FeedCache::new();
+        let generation = cache.get(0).unwrap_err();
+        cache.store(generation, Bytes::from_static(b"[]"), None);
+
+        cache.invalidate();
+        assert!(cache.get(0).is_err());
+    }
+
+    #[test]
+    fn test_stale_rebuild_is_discarded() {
+        let cache = FeedCache::new();
+        let generation = cache.get(0).unwrap_err();
+
+        // An upload commits while the feed is being rebuilt
+        cache.invalidate();
+        cache.store(generation, Bytes::from_static(b"[\"stale\"]"), None);
+
+        assert!(cache.get(0).is_err());
+    }
+
+    #[test]
+    fn test_body_expires() {
+        let cache = FeedCache::new();
+        let generation = cache.get(0).unwrap_err();
+        cache.store(generation, Bytes::from_static(b"[]"), Some(100));
+
+        assert!(cache.get(99).is_ok());
+        assert!(cache.get(100).is_err());
+    }
+}
@@ -4,15 +4,41 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
+    /// Channel the token is scoped to
     pub sub: String,
     pub channel_name: String,
     pub exp: usize,
     pub iat: usize,
+    /// Acting user for member logins, absent for channel password logins
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uid: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_name: Option<String>,
 }
 
 const TOKEN_EXPIRATION_DAYS: u64 = 30;
 
 pub fn create_jwt(channel_id: i64, channel_name: &str, secret: &str) -> Result<String, String> {
+    encode_claims(channel_id, channel_name, None, secret)
+}
+
+/// Token for a user acting on a channel they are a member of
+pub fn create_user_jwt(
+    channel_id: i64,
+    channel_name: &str,
+    user_id: i64,
+    user_name: &str,
+    secret: &str,
+) -> Result<String, String> {
+    encode_claims(channel_id, channel_name, Some((user_id, user_name)), secret)
+}
+
+fn encode_claims(
+    channel_id: i64,
+    channel_name: &str,
+    user: Option<(i64, &str)>,
+    secret: &str,
+) -> Result<String, String> {
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .map_err(|e| format!("System time error: {e}"))?
@@ -25,6 +51,8 @@ pub fn create_jwt(channel_id: i64, channel_name: &str, secret: &str) -> Result<S
         channel_name: channel_name.to_string(),
         exp: expiration as usize,
         iat: now as usize,
+        uid: user.map(|(id, _)| id),
+        user_name: user.map(|(_, name)| name.to_string()),
     };
 
     encode(
@@ -65,6 +93,18 @@ mod tests {
         assert_eq!(claims.channel_name, "testchannel");
     }
 
+    #[test]
+    fn test_user_jwt_carries_acting_user() {
+        let token = create_user_jwt(123, "team", 7, "alice", "test-secret").unwrap();
+        let claims = verify_jwt(&token, "test-secret").unwrap();
+        assert_eq!(claims.sub, "123");
+        assert_eq!(claims.uid, Some(7));
+        assert_eq!(claims.user_name.as_deref(), Some("alice"));
+
+        let channel_token = create_jwt(123, "team", "test-secret").unwrap();
+        assert_eq!(verify_jwt(&channel_token, "test-secret").unwrap().uid, None);
+    }
+
     #[test]
     fn test_verify_jwt_with_wrong_secret() {
         let secret = "test-secret";
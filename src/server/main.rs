@@ -1,26 +1,106 @@
+mod archive;
+mod audit;
+mod atom;
 mod auth;
 mod auth_endpoints;
+mod broadcast;
 mod channel_endpoints;
+mod comment_endpoints;
+mod error;
+mod feed_cache;
 mod jwt;
+mod member_endpoints;
 mod middleware;
+mod mirror;
 mod password;
+mod preview;
+mod profile_endpoints;
 mod rate_limiter;
 mod server_logic;
+mod shutdown;
+mod storage;
+#[cfg(test)]
+mod test_support;
+mod tls_reload;
+mod visibility;
+mod wav;
+mod webhook_endpoints;
+mod webhooks;
 
-use r2d2::Pool;
-use r2d2_sqlite::SqliteConnectionManager;
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 use std::sync::Arc;
 use server_logic::run_server;
-
-pub type DbPool = Pool<SqliteConnectionManager>;
+use storage::{MirrorStore, SqliteStorage, Storage, StorageError};
 
 #[derive(Clone)]
 pub struct AppState {
-    pub db: DbPool,
+    pub storage: Arc<dyn Storage>,
+    pub feed_cache: Arc<feed_cache::FeedCache>,
+    pub wav_cache: Arc<wav::WavCache>,
+    pub broadcast: Arc<broadcast::Broadcast>,
     pub jwt_secret: String,
     pub auth_rate_limiter: Arc<rate_limiter::RateLimiter>,
     pub api_rate_limiter: Arc<rate_limiter::RateLimiter>,
     pub upload_rate_limiter: Arc<rate_limiter::RateLimiter>,
+    pub webhooks: Arc<webhooks::WebhookDispatcher>,
+}
+
+#[cfg(test)]
+impl AppState {
+    pub fn for_tests(storage: Arc<dyn Storage>) -> Self {
+        Self {
+            storage,
+            feed_cache: Arc::new(feed_cache::FeedCache::new()),
+            wav_cache: Arc::new(wav::WavCache::default()),
+            broadcast: Arc::new(broadcast::Broadcast::new()),
+            jwt_secret: "test-secret".to_string(),
+            auth_rate_limiter: Arc::new(rate_limiter::RateLimiter::default()),
+            api_rate_limiter: Arc::new(rate_limiter::RateLimiter::default()),
+            upload_rate_limiter: Arc::new(rate_limiter::RateLimiter::default()),
+            webhooks: Arc::new(webhooks::WebhookDispatcher::disabled()),
+        }
+    }
+}
+
+#[derive(Parser)]
+#[command(name = "server")]
+#[command(about = "Tama server", long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    #[command(about = "Run the server (default)")]
+    Serve,
+    #[command(about = "Export channels, contents and peer servers to an archive")]
+    Export { output: PathBuf },
+    #[command(about = "Import an archive created with `server export`")]
+    Import { input: PathBuf },
+    #[command(about = "Keep local copies of channels of federated peers")]
+    Mirror {
+        #[command(subcommand)]
+        command: MirrorCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum MirrorCommand {
+    #[command(about = "Mirror a channel, served as /channel/<name>@<host> from the next sync")]
+    Add {
+        #[arg(help = "Peer server, e.g. https://tama.example")]
+        server_url: String,
+        #[arg(help = "Channel id or name on the peer")]
+        channel: String,
+    },
+    #[command(about = "List mirrored channels and how their last sync went")]
+    List,
+    #[command(about = "Stop mirroring a channel, contents mirrored so far are kept")]
+    Remove { id: i64 },
+    #[command(about = "Sync every mirror now, instead of waiting for the server to")]
+    Sync,
 }
 
 #[tokio::main]
@@ -32,13 +112,94 @@ async fn main() -> Result<(), String> {
         .compact()
         .init();
 
+    let cli = Cli::parse();
+
     let db_path = std::env::var("DATABASE_PATH").unwrap_or_else(|_| "tama.db".to_string());
-    let port = std::env::var("SERVER_PORT")
-        .ok()
-        .and_then(|p| p.parse::<u16>().ok())
-        .unwrap_or(3000);
-    let jwt_secret = std::env::var("JWT_SECRET")
-        .expect("JWT_SECRET must be set in .env file");
-
-    run_server(&db_path, port, jwt_secret).await
+
+    match cli.command {
+        Some(Commands::Export { output }) => {
+            let storage = SqliteStorage::open(&db_path)?;
+            let archive = archive::export_to_file(&storage, &output).await?;
+            let contents: usize = archive.channels.iter().map(|c| c.contents.len()).sum();
+            println!(
+                "Exported {} channels, {} contents and {} servers to {}",
+                archive.channels.len(),
+                contents,
+                archive.servers.len(),
+                output.display()
+            );
+            Ok(())
+        }
+        Some(Commands::Import { input }) => {
+            let storage = SqliteStorage::open(&db_path)?;
+            let summary = archive::import_from_file(&storage, &input).await?;
+            println!("Imported {}", input.display());
+            println!("  Channels: {} created, {} already present", summary.channels_created, summary.channels_existing);
+            println!("  Contents: {} created, {} already present, {} invalid", summary.contents_created, summary.contents_existing, summary.contents_invalid);
+            println!("  Servers:  {} added", summary.servers_added);
+            if !summary.conflicts.is_empty() {
+                println!("  Skipped, name taken by another local channel: {}", summary.conflicts.join(", "));
+            }
+            Ok(())
+        }
+        Some(Commands::Mirror { command }) => {
+            let storage = SqliteStorage::open(&db_path)?;
+            handle_mirror(&storage, command).await
+        }
+        Some(Commands::Serve) | None => {
+            let port = std::env::var("SERVER_PORT")
+                .ok()
+                .and_then(|p| p.parse::<u16>().ok())
+                .unwrap_or(3000);
+            let jwt_secret = std::env::var("JWT_SECRET")
+                .expect("JWT_SECRET must be set in .env file");
+
+            run_server(&db_path, port, jwt_secret).await
+        }
+    }
+}
+
+async fn handle_mirror(storage: &SqliteStorage, command: MirrorCommand) -> Result<(), String> {
+    match command {
+        MirrorCommand::Add { server_url, channel } => {
+            let server_url = mirror::normalize_server_url(&server_url)?;
+            let now = chrono::Utc::now().timestamp();
+            let created = match storage.create_mirror(&server_url, channel.trim(), now).await {
+                Ok(created) => created,
+                Err(StorageError::AlreadyExists) => return Err(format!("'{channel}' from {server_url} is already mirrored")),
+                Err(e) => return Err(format!("Failed to add mirror: {e}")),
+            };
+            println!("Mirror #{} added: '{}' from {}", created.id, created.remote_channel, created.server_url);
+        }
+        MirrorCommand::List => {
+            let mirrors = storage.list_mirrors().await
+                .map_err(|e| format!("Failed to list mirrors: {e}"))?;
+            if mirrors.is_empty() {
+                println!("No mirrors");
+            }
+            for mirror in mirrors {
+                let synced = mirror.last_synced_at
+                    .and_then(|time| chrono::DateTime::from_timestamp(time, 0))
+                    .map(|time| time.to_rfc3339())
+                    .unwrap_or_else(|| "never".to_string());
+                println!("#{:<4} {} from {}, last synced {synced}", mirror.id, mirror.remote_channel, mirror.server_url);
+                if let Some(error) = mirror.last_error {
+                    println!("      last error: {error}");
+                }
+            }
+        }
+        MirrorCommand::Remove { id } => {
+            let removed = storage.delete_mirror(id).await
+                .map_err(|e| format!("Failed to remove mirror: {e}"))?;
+            if !removed {
+                return Err(format!("No mirror #{id}"));
+            }
+            println!("Mirror #{id} removed");
+        }
+        MirrorCommand::Sync => {
+            mirror::sync_all(storage, &mirror::http_client()).await?;
+            println!("Mirrors synced, see `server mirror list`");
+        }
+    }
+    Ok(())
 }
@@ -0,0 +1,425 @@
+use axum::{
+    extract::{ConnectInfo, Json, Path, State},
+    http::{HeaderMap, StatusCode},
+};
+use std::net::SocketAddr;
+
+use crate::error::{database_error, internal_error};
+use crate::auth_endpoints::is_valid_channel_name;
+use crate::storage::{ChannelRecord, InviteRecord, StorageError, UserRecord};
+use crate::{audit, auth, AppState};
+use tama::api::{
+    AcceptInviteRequest, AuthEventKind, AuthResponse, ChannelInfo, CreateInviteRequest, InviteResponse,
+    MemberInfo, Role, UserInfo, UserLoginRequest, UserRegisterRequest,
+};
+
+const TOKEN_LIFETIME_SECONDS: i64 = 30 * 24 * 60 * 60;
+const INVITE_LIFETIME_SECONDS: i64 = 7 * 24 * 60 * 60;
+
+pub async fn register_user(
+    State(state): State<AppState>,
+    Json(request): Json<UserRegisterRequest>,
+) -> Result<Json<UserInfo>, (StatusCode, String)> {
+    let user_name = request.user_name.trim().to_lowercase();
+
+    // Same rules as channel names, users and channels live in separate namespaces
+    if !is_valid_channel_name(&user_name) {
+        return Err((StatusCode::BAD_REQUEST, "Invalid user name".to_string()));
+    }
+
+    let password_hash = crate::password::hash_password(&request.password)
+        .map_err(|e| internal_error("Failed to hash password", e))?;
+
+    let now = chrono::Utc::now().timestamp();
+    let user = match state.storage.create_user(&user_name, &password_hash, now).await {
+        Ok(user) => user,
+        Err(StorageError::AlreadyExists) => {
+            return Err((StatusCode::CONFLICT, format!("User '{user_name}' already exists")));
+        }
+        Err(e) => return Err(database_error(e)),
+    };
+
+    tracing::info!("User registered: id={}, name={}", user.id, user.name);
+    Ok(Json(UserInfo { id: user.id, name: user.name }))
+}
+
+pub async fn login_user(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(request): Json<UserLoginRequest>,
+) -> Result<Json<AuthResponse>, (StatusCode, String)> {
+    let channel_name = request.channel_name.trim().to_lowercase();
+    let user_name = request.user_name.trim().to_lowercase();
+
+    let user = verify_member(&state, &channel_name, &user_name, &request.password, addr).await?;
+
+    let channel = state.storage.find_channel_by_name(&channel_name).await
+        .map_err(database_error)?
+        .ok_or((StatusCode::NOT_FOUND, "Channel not found".to_string()))?;
+
+    let role = state.storage.find_member_role(channel.id, user.id).await
+        .map_err(database_error)?
+        .ok_or((StatusCode::FORBIDDEN, format!("'{}' is not a member of '{}'", user.name, channel.name)))?;
+
+    tracing::info!("User logged in: user={}, channel={}, role={}", user.name, channel.name, role.as_str());
+    audit::record(state.storage.as_ref(), &channel.name, AuthEventKind::LoginSuccess, addr.ip(), Some(&user.name)).await;
+    member_auth_response(&state, channel, user, role).map(Json)
+}
+
+pub async fn create_invite(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<CreateInviteRequest>,
+) -> Result<Json<InviteResponse>, (StatusCode, String)> {
+    let caller = auth::authenticate_caller(&headers, &state.jwt_secret)
+        .map_err(|status| (status, "Authentication required".to_string()))?;
+
+    let role = auth::caller_role(state.storage.as_ref(), &caller, caller.channel_id).await
+        .map_err(database_error)?;
+
+    if !role.is_some_and(|role| role.can_manage()) {
+        return Err((StatusCode::FORBIDDEN, "Only the channel owner can invite members".to_string()));
+    }
+
+    let channel = state.storage.find_channel_by_id(caller.channel_id).await
+        .map_err(database_error)?
+        .ok_or((StatusCode::NOT_FOUND, "Channel not found".to_string()))?;
+
+    let now = chrono::Utc::now().timestamp();
+    let invite = InviteRecord {
+        token: uuid::Uuid::new_v4().simple().to_string(),
+        channel_id: channel.id,
+        role: request.role,
+        created_at: now,
+        expires_at: now + INVITE_LIFETIME_SECONDS,
+    };
+
+    state.storage.create_invite(&invite).await
+        .map_err(|e| internal_error("Failed to save invite", e))?;
+
+    tracing::info!("Invite created: channel={}, role={}", channel.name, invite.role.as_str());
+
+    Ok(Json(InviteResponse {
+        token: invite.token,
+        channel: ChannelInfo { id: channel.id, name: channel.name },
+        role: invite.role,
+        expires_at: invite.expires_at,
+    }))
+}
+
+pub async fn accept_invite(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(token): Path<String>,
+    Json(request): Json<AcceptInviteRequest>,
+) -> Result<Json<AuthResponse>, (StatusCode, String)> {
+    let invite_not_found = || (StatusCode::NOT_FOUND, "Invite not found or already used".to_string());
+    let pending = state.storage.find_invite(&token).await
+        .map_err(database_error)?
+        .ok_or_else(invite_not_found)?;
+
+    let now = chrono::Utc::now().timestamp();
+    if pending.expires_at <= now {
+        return Err((StatusCode::GONE, "Invite expired".to_string()));
+    }
+
+    let channel = state.storage.find_channel_by_id(pending.channel_id).await
+        .map_err(database_error)?
+        .ok_or_else(invite_not_found)?;
+
+    // Check the credentials before taking the invite, so a typo doesn't burn it
+    let user_name = request.user_name.trim().to_lowercase();
+    let user = verify_member(&state, &channel.name, &user_name, &request.password, addr).await?;
+
+    let invite = state.storage.take_invite(&token).await
+        .map_err(database_error)?
+        .ok_or_else(invite_not_found)?;
+
+    // An invite never takes rights away from someone who already is a member
+    let current = state.storage.find_member_role(channel.id, user.id).await
+        .map_err(database_error)?;
+    let role = match current {
+        Some(current) if rank(current) >= rank(invite.role) => current,
+        _ => invite.role,
+    };
+
+    state.storage.set_member(channel.id, user.id, role, now).await
+        .map_err(|e| internal_error("Failed to add member", e))?;
+
+    tracing::info!("Invite accepted: user={}, channel={}, role={}", user.name, channel.name, role.as_str());
+    audit::record(state.storage.as_ref(), &channel.name, AuthEventKind::TokenIssued, addr.ip(), Some(&user.name)).await;
+    member_auth_response(&state, channel, user, role).map(Json)
+}
+
+pub async fn list_members(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<MemberInfo>>, (StatusCode, String)> {
+    let caller = auth::authenticate_caller(&headers, &state.jwt_secret)
+        .map_err(|status| (status, "Authentication required".to_string()))?;
+
+    let role = auth::caller_role(state.storage.as_ref(), &caller, caller.channel_id).await
+        .map_err(database_error)?;
+
+    if role.is_none() {
+        return Err((StatusCode::FORBIDDEN, "Not a member of this channel".to_string()));
+    }
+
+    let members = state.storage.list_members(caller.channel_id).await
+        .map_err(database_error)?
+        .into_iter()
+        .map(|member| MemberInfo {
+            user: UserInfo { id: member.user_id, name: member.user_name },
+            role: member.role,
+            joined_at: member.joined_at,
+        })
+        .collect();
+
+    Ok(Json(members))
+}
+
+pub async fn remove_member(
+    State(state): State<AppState>,
+    Path(user_id): Path<i64>,
+    headers: HeaderMap,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let caller = auth::authenticate_caller(&headers, &state.jwt_secret)
+        .map_err(|status| (status, "Authentication required".to_string()))?;
+
+    let role = auth::caller_role(state.storage.as_ref(), &caller, caller.channel_id).await
+        .map_err(database_error)?;
+
+    if !role.is_some_and(|role| role.can_manage()) {
+        return Err((StatusCode::FORBIDDEN, "Only the channel owner can remove members".to_string()));
+    }
+
+    let removed = state.storage.remove_member(caller.channel_id, user_id).await
+        .map_err(database_error)?;
+
+    if !removed {
+        return Err((StatusCode::NOT_FOUND, "Member not found".to_string()));
+    }
+
+    tracing::info!("Member removed: channel_id={}, user_id={}", caller.channel_id, user_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Checks a member's credentials under the lockout, recording failed attempts in the channel's log.
+/// Unknown user names have nothing to lock out and aren't recorded.
+async fn verify_member(
+    state: &AppState,
+    channel_name: &str,
+    user_name: &str,
+    password: &str,
+    addr: SocketAddr,
+) -> Result<UserRecord, (StatusCode, String)> {
+    let invalid = || (StatusCode::UNAUTHORIZED, "Invalid user name or password".to_string());
+    let user_name = user_name.trim().to_lowercase();
+
+    audit::check_lockout(state.storage.as_ref(), channel_name, addr.ip(), Some(&user_name)).await
+        .map_err(|status| (status, "Too many failed logins, try again later".to_string()))?;
+
+    let user = state.storage.find_user_by_name(&user_name).await
+        .map_err(database_error)?
+        .ok_or_else(invalid)?;
+
+    let password_valid = crate::password::verify_password(password, &user.password_hash)
+        .map_err(|e| internal_error("Password verification error", e))?;
+
+    if !password_valid {
+        tracing::warn!("Login failed: invalid password for user '{}'", user_name);
+        audit::record(state.storage.as_ref(), channel_name, AuthEventKind::LoginFailure, addr.ip(), Some(&user_name)).await;
+        return Err(invalid());
+    }
+
+    Ok(user)
+}
+
+fn member_auth_response(
+    state: &AppState,
+    channel: ChannelRecord,
+    user: UserRecord,
+    role: Role,
+) -> Result<AuthResponse, (StatusCode, String)> {
+    let token = crate::jwt::create_user_jwt(channel.id, &channel.name, user.id, &user.name, &state.jwt_secret)
+        .map_err(|e| internal_error("Failed to create JWT", e))?;
+
+    Ok(AuthResponse {
+        token,
+        expires_at: chrono::Utc::now().timestamp() + TOKEN_LIFETIME_SECONDS,
+        channel: ChannelInfo { id: channel.id, name: channel.name },
+        user: Some(UserInfo { id: user.id, name: user.name }),
+        role: Some(role),
+    })
+}
+
+fn rank(role: Role) -> u8 {
+    match role {
+        Role::Owner => 2,
+        Role::Editor => 1,
+        Role::Viewer => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{bearer, owner_headers};
+    use crate::storage::{AuditStore, ChannelStore, MemberStore, MemoryStorage};
+    use std::sync::Arc;
+
+    fn peer() -> ConnectInfo<SocketAddr> {
+        ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 4000)))
+    }
+
+    async fn setup() -> (AppState, Arc<MemoryStorage>, HeaderMap) {
+        let storage = Arc::new(MemoryStorage::new());
+        let channel = storage.create_channel("team", "hash", 1).await.unwrap();
+        let state = AppState::for_tests(storage.clone());
+        let headers = owner_headers(&state, &channel);
+        (state, storage, headers)
+    }
+
+    async fn register(state: &AppState, user_name: &str) {
+        let _ = register_user(State(state.clone()), Json(UserRegisterRequest {
+            user_name: user_name.to_string(),
+            password: "secret".to_string(),
+        })).await.unwrap();
+    }
+
+    async fn invite(state: &AppState, headers: &HeaderMap, role: Role) -> String {
+        let Json(invite) = create_invite(State(state.clone()), headers.clone(), Json(CreateInviteRequest { role }))
+            .await
+            .unwrap();
+        invite.token
+    }
+
+    fn accept_request(user_name: &str) -> Json<AcceptInviteRequest> {
+        Json(AcceptInviteRequest { user_name: user_name.to_string(), password: "secret".to_string() })
+    }
+
+    #[tokio::test]
+    async fn test_invite_flow() {
+        let (state, storage, owner) = setup().await;
+        register(&state, "alice").await;
+
+        let token = invite(&state, &owner, Role::Editor).await;
+        let Json(auth) = accept_invite(State(state.clone()), peer(), Path(token.clone()), accept_request("alice")).await.unwrap();
+        assert_eq!(auth.role, Some(Role::Editor));
+        assert_eq!(auth.user.as_ref().map(|user| user.name.as_str()), Some("alice"));
+
+        // Invites are single use
+        let again = accept_invite(State(state.clone()), peer(), Path(token), accept_request("alice")).await;
+        assert_eq!(again.unwrap_err().0, StatusCode::NOT_FOUND);
+
+        let Json(members) = list_members(State(state.clone()), bearer(&auth.token)).await.unwrap();
+        assert_eq!(members.len(), 1);
+        assert_eq!(members[0].role, Role::Editor);
+
+        // Editors can't manage the channel
+        let forbidden = create_invite(State(state.clone()), bearer(&auth.token), Json(CreateInviteRequest { role: Role::Owner })).await;
+        assert_eq!(forbidden.unwrap_err().0, StatusCode::FORBIDDEN);
+
+        let user_id = auth.user.unwrap().id;
+        assert_eq!(remove_member(State(state.clone()), Path(user_id), owner).await.unwrap(), StatusCode::NO_CONTENT);
+        assert_eq!(storage.find_member_role(auth.channel.id, user_id).await.unwrap(), None);
+
+        // Removal applies to tokens issued before it
+        let revoked = list_members(State(state), bearer(&auth.token)).await;
+        assert_eq!(revoked.unwrap_err().0, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_accept_invite_keeps_higher_role_and_checks_password() {
+        let (state, storage, owner) = setup().await;
+        register(&state, "alice").await;
+
+        let token = invite(&state, &owner, Role::Viewer).await;
+        let wrong_password = accept_invite(State(state.clone()), peer(), Path(token.clone()), Json(AcceptInviteRequest {
+            user_name: "alice".to_string(),
+            password: "wrong".to_string(),
+        })).await;
+        assert_eq!(wrong_password.unwrap_err().0, StatusCode::UNAUTHORIZED);
+
+        let alice = storage.find_user_by_name("alice").await.unwrap().unwrap();
+        storage.set_member(1, alice.id, Role::Editor, 5).await.unwrap();
+
+        let Json(auth) = accept_invite(State(state), peer(), Path(token), accept_request("alice")).await.unwrap();
+        assert_eq!(auth.role, Some(Role::Editor));
+    }
+
+    #[tokio::test]
+    async fn test_expired_invite_is_refused_before_anything_else() {
+        let (state, storage, _) = setup().await;
+        storage.create_invite(&InviteRecord {
+            token: "stale".to_string(),
+            channel_id: 1,
+            role: Role::Editor,
+            created_at: 1,
+            expires_at: 2,
+        }).await.unwrap();
+
+        let expired = accept_invite(State(state), peer(), Path("stale".to_string()), accept_request("nobody")).await;
+        assert_eq!(expired.unwrap_err().0, StatusCode::GONE);
+        assert!(storage.find_invite("stale").await.unwrap().is_some());
+        assert!(storage.list_auth_events("team", 1).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_login_user_requires_membership() {
+        let (state, _, owner) = setup().await;
+        register(&state, "alice").await;
+
+        let login = |state: AppState| login_user(State(state), peer(), Json(UserLoginRequest {
+            user_name: "alice".to_string(),
+            password: "secret".to_string(),
+            channel_name: "team".to_string(),
+        }));
+
+        assert_eq!(login(state.clone()).await.unwrap_err().0, StatusCode::FORBIDDEN);
+
+        let token = invite(&state, &owner, Role::Viewer).await;
+        let _ = accept_invite(State(state.clone()), peer(), Path(token), accept_request("alice")).await.unwrap();
+
+        let Json(auth) = login(state).await.unwrap();
+        assert_eq!(auth.role, Some(Role::Viewer));
+    }
+
+    #[tokio::test]
+    async fn test_member_lockout_follows_the_user_across_channels_and_invites() {
+        let (state, storage, owner) = setup().await;
+        register(&state, "alice").await;
+        let token = invite(&state, &owner, Role::Viewer).await;
+
+        for i in 0..5 {
+            let failed = login_user(State(state.clone()), peer(), Json(UserLoginRequest {
+                user_name: "alice".to_string(),
+                password: "wrong".to_string(),
+                channel_name: format!("channel{i}"),
+            })).await;
+            assert_eq!(failed.unwrap_err().0, StatusCode::UNAUTHORIZED);
+        }
+
+        let locked = accept_invite(State(state.clone()), peer(), Path(token.clone()), accept_request("alice")).await;
+        assert_eq!(locked.unwrap_err().0, StatusCode::TOO_MANY_REQUESTS);
+
+        // The refused attempt is in the inviting channel's log and the invite is still there
+        let events = storage.list_auth_events("team", 1).await.unwrap();
+        assert_eq!((events[0].kind, events[0].user_name.as_deref()), (AuthEventKind::LockedOut, Some("alice")));
+        assert!(storage.find_invite(&token).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_accept_invite_records_failures() {
+        let (state, storage, owner) = setup().await;
+        register(&state, "alice").await;
+        let token = invite(&state, &owner, Role::Viewer).await;
+
+        let failed = accept_invite(State(state), peer(), Path(token), Json(AcceptInviteRequest {
+            user_name: "alice".to_string(),
+            password: "wrong".to_string(),
+        })).await;
+        assert_eq!(failed.unwrap_err().0, StatusCode::UNAUTHORIZED);
+        assert_eq!(storage.list_auth_events("team", 1).await.unwrap()[0].kind, AuthEventKind::LoginFailure);
+    }
+}
@@ -59,15 +59,13 @@ pub async fn validate_content_size(
 ) -> Result<Response, StatusCode> {
     const MAX_CONTENT_LENGTH: usize = 10 * 1024 * 1024; // 10 MB
 
-    if let Some(content_length) = request.headers().get("content-length") {
-        if let Ok(length_str) = content_length.to_str() {
-            if let Ok(length) = length_str.parse::<usize>() {
-                if length > MAX_CONTENT_LENGTH {
-                    tracing::warn!("Request rejected: content-length {} exceeds max {}", length, MAX_CONTENT_LENGTH);
-                    return Err(StatusCode::PAYLOAD_TOO_LARGE);
-                }
-            }
-        }
+    if let Some(content_length) = request.headers().get("content-length")
+        && let Ok(length_str) = content_length.to_str()
+        && let Ok(length) = length_str.parse::<usize>()
+        && length > MAX_CONTENT_LENGTH
+    {
+        tracing::warn!("Request rejected: content-length {} exceeds max {}", length, MAX_CONTENT_LENGTH);
+        return Err(StatusCode::PAYLOAD_TOO_LARGE);
     }
 
     Ok(next.run(request).await)
@@ -0,0 +1,332 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::channel_endpoints::validate_content_payload;
+use crate::server_logic::{ChannelResponse, ContentData};
+use crate::storage::{ContentOrigin, MirrorRecord, NewContent, Storage, StorageError};
+use tama::api::Visibility;
+use tama::signing;
+
+const DEFAULT_SYNC_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// Largest page `GET /channel/:id` serves
+const PAGE_SIZE: i64 = 100;
+/// Stops runaway syncs against peers that ignore the offset
+const MAX_PAGES: i64 = 100;
+
+/// Time between two syncs of every mirror, from `MIRROR_SYNC_INTERVAL` in seconds
+pub fn sync_interval_from_env() -> Duration {
+    std::env::var("MIRROR_SYNC_INTERVAL")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .filter(|seconds| *seconds > 0)
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_SYNC_INTERVAL)
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct SyncSummary {
+    pub contents_created: usize,
+    pub contents_existing: usize,
+    pub contents_invalid: usize,
+}
+
+/// Normalized peer URL, without trailing slash
+pub fn normalize_server_url(server_url: &str) -> Result<String, String> {
+    let url = reqwest::Url::parse(server_url.trim())
+        .map_err(|e| format!("Invalid server URL: {e}"))?;
+
+    if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+        return Err("Server URL must be an http or https URL".to_string());
+    }
+
+    Ok(url.as_str().trim_end_matches('/').to_string())
+}
+
+/// Local name of a mirrored channel, `<name>@<host>` with the port if the URL has one
+pub fn mirror_channel_name(remote_name: &str, server_url: &str) -> Result<String, String> {
+    let url = reqwest::Url::parse(server_url)
+        .map_err(|e| format!("Invalid server URL: {e}"))?;
+    let host = url.host_str().ok_or("Server URL has no host")?;
+
+    Ok(match url.port() {
+        Some(port) => format!("{remote_name}@{host}:{port}"),
+        None => format!("{remote_name}@{host}"),
+    })
+}
+
+/// Hex digest of what makes a content play, identical copies share it
+pub fn content_hash(art: &str, midi_composition: &str, fps: f32) -> String {
+    signing::content_digest(art, midi_composition, fps)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+pub fn http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .unwrap_or_default()
+}
+
+async fn fetch_page(client: &reqwest::Client, mirror: &MirrorRecord, offset: i64) -> Result<ChannelResponse, String> {
+    let url = format!(
+        "{}/channel/{}?limit={PAGE_SIZE}&offset={offset}",
+        mirror.server_url, mirror.remote_channel
+    );
+
+    let response = client.get(&url).send().await
+        .map_err(|e| format!("Failed to fetch {url}: {e}"))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Failed to fetch {url}: {}", response.status()));
+    }
+
+    response.json().await
+        .map_err(|e| format!("Invalid response from {url}: {e}"))
+}
+
+/// Local channel holding the mirror's contents, created on first sync
+async fn local_channel(storage: &dyn Storage, mirror: &MirrorRecord, remote_name: &str) -> Result<i64, String> {
+    if let Some(channel_id) = mirror.channel_id {
+        return Ok(channel_id);
+    }
+
+    let name = mirror_channel_name(remote_name, &mirror.server_url)?;
+    let now = chrono::Utc::now().timestamp();
+
+    // Names with '@' can't log in, so the channel needs no password
+    match storage.create_channel(&name, "", now).await {
+        Ok(channel) => Ok(channel.id),
+        Err(StorageError::AlreadyExists) => storage.find_channel_by_name(&name).await
+            .map_err(|e| format!("Failed to look up channel '{name}': {e}"))?
+            .map(|channel| channel.id)
+            .ok_or_else(|| format!("Channel '{name}' disappeared")),
+        Err(e) => Err(format!("Failed to create channel '{name}': {e}")),
+    }
+}
+
+/// Stores one remote content unless the channel already holds the same one
+async fn store_content(
+    storage: &dyn Storage,
+    mirror: &MirrorRecord,
+    channel_id: i64,
+    content: ContentData,
+    summary: &mut SyncSummary,
+) -> Result<(), String> {
+    if let Err(e) = validate_content_payload(&content.art, &content.midi_composition, content.fps) {
+        tracing::warn!("Skipping content {} mirrored from {}: {e}", content.id, mirror.server_url);
+        summary.contents_invalid += 1;
+        return Ok(());
+    }
+
+    let content_hash = content_hash(&content.art, &content.midi_composition, content.fps);
+    let existing = storage.find_content_by_hash(channel_id, &content_hash).await
+        .map_err(|e| format!("Failed to look up content {}: {e}", content.id))?;
+
+    if existing.is_some() {
+        summary.contents_existing += 1;
+        return Ok(());
+    }
+
+    // Mirrors of mirrors point back to where the content was first published
+    let (server_url, content_id) = match content.origin {
+        Some(origin) => (origin.server_url, origin.content_id),
+        None => (mirror.server_url.clone(), content.id),
+    };
+
+    storage.create_content(NewContent {
+        channel_id,
+        name: content.name,
+        art: content.art,
+        midi_composition: content.midi_composition,
+        fps: content.fps,
+        created_at: chrono::Utc::now().timestamp(),
+        visibility: Visibility::Public,
+        signature: content.signature,
+        public_key: content.public_key,
+        origin: Some(ContentOrigin { server_url, content_id, content_hash }),
+        ..Default::default()
+    })
+    .await
+    .map_err(|e| format!("Failed to insert content {}: {e}", content.id))?;
+
+    summary.contents_created += 1;
+    Ok(())
+}
+
+/// Pulls every page of the remote channel. Contents the peer removed are kept.
+pub async fn sync_mirror(storage: &dyn Storage, client: &reqwest::Client, mirror: &MirrorRecord) -> Result<SyncSummary, String> {
+    let mut summary = SyncSummary::default();
+    let mut local_channel_id = None;
+
+    for page in 0..MAX_PAGES {
+        let response = fetch_page(client, mirror, page * PAGE_SIZE).await?;
+        let page_len = response.contents.len() as i64;
+
+        let channel_id = match local_channel_id {
+            Some(channel_id) => channel_id,
+            None => {
+                let id = local_channel(storage, mirror, &response.name).await?;
+                if !response.profile.is_empty() {
+                    storage.save_profile(id, &response.profile).await
+                        .map_err(|e| format!("Failed to save profile: {e}"))?;
+                }
+                *local_channel_id.insert(id)
+            }
+        };
+
+        for content in response.contents {
+            store_content(storage, mirror, channel_id, content, &mut summary).await?;
+        }
+
+        if page_len < PAGE_SIZE {
+            break;
+        }
+    }
+
+    if let Some(channel_id) = local_channel_id {
+        storage.mark_mirror_synced(mirror.id, channel_id, chrono::Utc::now().timestamp()).await
+            .map_err(|e| format!("Failed to update mirror: {e}"))?;
+    }
+    Ok(summary)
+}
+
+/// Syncs every mirror once, recording failures on the mirror rather than giving up
+pub async fn sync_all(storage: &dyn Storage, client: &reqwest::Client) -> Result<(), String> {
+    let mirrors = storage.list_mirrors().await
+        .map_err(|e| format!("Failed to list mirrors: {e}"))?;
+
+    for mirror in mirrors {
+        match sync_mirror(storage, client, &mirror).await {
+            Ok(summary) => tracing::info!(
+                "Mirrored '{}' from {}: {} new, {} already present, {} invalid",
+                mirror.remote_channel, mirror.server_url,
+                summary.contents_created, summary.contents_existing, summary.contents_invalid
+            ),
+            Err(e) => {
+                tracing::warn!("Failed to mirror '{}' from {}: {e}", mirror.remote_channel, mirror.server_url);
+                if let Err(e) = storage.mark_mirror_failed(mirror.id, &e).await {
+                    tracing::warn!("Failed to update mirror {}: {e}", mirror.id);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Syncs all mirrors now and then every `interval`
+pub fn start(storage: Arc<dyn Storage>, interval: Duration) {
+    tokio::spawn(async move {
+        let client = http_client();
+        loop {
+            if let Err(e) = sync_all(storage.as_ref(), &client).await {
+                tracing::warn!("{e}");
+            }
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{ChannelStore, ContentStore, Listing, MemoryStorage, MirrorStore};
+    use axum::{extract::{Path, Query}, routing::get, Json, Router};
+    use std::collections::HashMap;
+    use tama::api::ChannelProfile;
+
+    const ART: &str = "Ascii Art Animation, 2x1, 5fps\n⠁⠁";
+
+    fn content(id: i64, midi: &str) -> ContentData {
+        ContentData {
+            id,
+            name: format!("nap {id}"),
+            art: ART.to_string(),
+            midi_composition: midi.to_string(),
+            fps: 5.0,
+            signature: None,
+            public_key: None,
+            origin: None,
+        }
+    }
+
+    /// Peer serving channel 'neko' with 150 contents, so two pages. Content 150
+    /// is a re-upload of content 1 and content 149 is not valid.
+    async fn start_peer() -> String {
+        let app = Router::new().route("/channel/:channel_id", get(|Path(channel): Path<String>, Query(query): Query<HashMap<String, i64>>| async move {
+            assert_eq!(channel, "neko");
+            let offset = query["offset"];
+            let contents = (1..=150)
+                .map(|id| match id {
+                    150 => content(id, "4c "),
+                    149 => content(id, "not midi"),
+                    id => content(id, &"4c ".repeat(id as usize)),
+                })
+                .skip(offset as usize)
+                .take(query["limit"] as usize)
+                .collect();
+
+            Json(ChannelResponse {
+                id: 1,
+                name: "neko".to_string(),
+                profile: ChannelProfile { bio: Some("Naps".to_string()), ..Default::default() },
+                contents,
+            })
+        }));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        url
+    }
+
+    #[test]
+    fn test_mirror_channel_name() {
+        assert_eq!(mirror_channel_name("neko", "https://tama.example").unwrap(), "neko@tama.example");
+        assert_eq!(mirror_channel_name("neko", "http://localhost:3001").unwrap(), "neko@localhost:3001");
+        assert_eq!(normalize_server_url("https://tama.example/").unwrap(), "https://tama.example");
+        assert!(normalize_server_url("tama.example").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sync_pages_and_dedupes() {
+        let server_url = start_peer().await;
+        let storage = MemoryStorage::new();
+        let mirror = storage.create_mirror(&server_url, "neko", 1).await.unwrap();
+        let client = http_client();
+
+        let summary = sync_mirror(&storage, &client, &mirror).await.unwrap();
+        assert_eq!(summary, SyncSummary { contents_created: 148, contents_existing: 1, contents_invalid: 1 });
+
+        let name = mirror_channel_name("neko", &server_url).unwrap();
+        let channel = storage.find_channel_by_name(&name).await.unwrap().unwrap();
+        assert_eq!(storage.find_profile(channel.id).await.unwrap().bio.as_deref(), Some("Naps"));
+
+        let contents = storage.list_channel_contents(channel.id, Listing::PublicAt(i64::MAX), 200, 0).await.unwrap();
+        let origin = contents[0].origin.as_ref().unwrap();
+        assert_eq!((origin.server_url.as_str(), origin.content_id), (server_url.as_str(), 1));
+        assert_eq!(contents[0].name, "nap 1");
+        assert!(storage.latest_contents(Listing::PublicAt(i64::MAX), 10).await.unwrap().is_empty());
+
+        let mirror = storage.list_mirrors().await.unwrap().remove(0);
+        assert_eq!(mirror.channel_id, Some(channel.id));
+        let summary = sync_mirror(&storage, &client, &mirror).await.unwrap();
+        assert_eq!((summary.contents_created, summary.contents_existing), (0, 149));
+    }
+
+    #[tokio::test]
+    async fn test_failures_are_recorded_on_the_mirror() {
+        // Nothing listens on the port a dropped listener was bound to
+        let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+        let storage = MemoryStorage::new();
+        storage.create_mirror(&format!("http://{closed}"), "neko", 1).await.unwrap();
+
+        sync_all(&storage, &http_client()).await.unwrap();
+
+        let mirror = storage.list_mirrors().await.unwrap().remove(0);
+        assert!(mirror.last_error.unwrap().starts_with("Failed to fetch"));
+        assert_eq!(mirror.channel_id, None);
+    }
+}
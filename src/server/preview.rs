@@ -0,0 +1,308 @@
+use crate::{storage::ContentRecord, visibility, AppState};
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Response},
+};
+use image::{imageops, ImageBuffer, ImageFormat, Luma};
+use std::io::Cursor;
+use tama::ascii_art_converter::{AsciiArtSheet, ImageConverter};
+
+/// Braille pixels are scaled up so unfurled previews are not a few dozen pixels wide
+const PREVIEW_SCALE: u32 = 8;
+const MAX_PREVIEW_SIZE: u32 = 1024;
+const INDEX_HTML_PATH: &str = "static/index.html";
+
+const BACKGROUND: u8 = 255;
+const FOREGROUND: u8 = 0;
+
+type PreviewResult<T> = Result<T, (StatusCode, String)>;
+
+pub async fn get_preview_png(
+    Path(content_id): Path<i64>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> PreviewResult<Response> {
+    tracing::debug!("[GET /content/{content_id}/preview.png] Request received");
+    let content = visibility::find_visible_content(&state, content_id, &headers).await?;
+
+    let png = render_png(&content.art).map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, e))?;
+    Ok(image_response(&content, "image/png", png))
+}
+
+pub async fn get_preview_gif(
+    Path(content_id): Path<i64>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> PreviewResult<Response> {
+    tracing::debug!("[GET /content/{content_id}/preview.gif] Request received");
+    let content = visibility::find_visible_content(&state, content_id, &headers).await?;
+
+    let gif = render_gif(&content.art, content.fps).map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, e))?;
+    Ok(image_response(&content, "image/gif", gif))
+}
+
+/// Serves the web UI for `/view/content/:id` with Open Graph tags, so pasted links unfurl
+pub async fn view_content_page(
+    Path(content_id): Path<i64>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> PreviewResult<Html<String>> {
+    let html = tokio::fs::read_to_string(INDEX_HTML_PATH)
+        .await
+        .map_err(|e| (StatusCode::NOT_FOUND, format!("Failed to read {INDEX_HTML_PATH}: {e}")))?;
+
+    // Unknown and hidden ids still get the plain page, the web UI shows its own error
+    let Ok(content) = visibility::find_visible_content(&state, content_id, &headers).await else {
+        return Ok(Html(html));
+    };
+
+    let channel_name = state.storage.find_channel_by_id(content.channel_id).await
+        .ok()
+        .flatten()
+        .map(|channel| channel.name)
+        .unwrap_or_default();
+
+    let base_url = configured_base_url(&headers);
+    let tags = open_graph_tags(&content, &channel_name, &base_url);
+    Ok(Html(inject_into_head(&html, &tags)))
+}
+
+fn image_response(content: &ContentRecord, content_type: &'static str, body: Vec<u8>) -> Response {
+    let cache_control = visibility::cache_control(content, chrono::Utc::now().timestamp());
+    (
+        [
+            (header::CONTENT_TYPE, content_type),
+            (header::CACHE_CONTROL, cache_control),
+        ],
+        body,
+    ).into_response()
+}
+
+fn parse_sheet(art: &str) -> Result<AsciiArtSheet, String> {
+    let sheet = AsciiArtSheet::from_string(art)?;
+
+    if sheet.width == 0 || sheet.height == 0 {
+        return Err("Art has no pixels to render".to_string());
+    }
+
+    let (width, height) = pixel_size(&sheet);
+    if width > MAX_PREVIEW_SIZE || height > MAX_PREVIEW_SIZE {
+        return Err(format!("Art is too large to preview ({}x{})", sheet.width, sheet.height));
+    }
+
+    Ok(sheet)
+}
+
+fn pixel_size(sheet: &AsciiArtSheet) -> (u32, u32) {
+    ((sheet.width * 2) as u32, (sheet.height * 4) as u32)
+}
+
+fn preview_scale(sheet: &AsciiArtSheet) -> u32 {
+    let (width, height) = pixel_size(sheet);
+    (MAX_PREVIEW_SIZE / width.max(height)).clamp(1, PREVIEW_SCALE)
+}
+
+fn render_frame(sheet: &AsciiArtSheet, frame: &str) -> ImageBuffer<Luma<u8>, Vec<u8>> {
+    let img = ImageConverter::braille_string_to_image(frame, sheet.width, sheet.height);
+    let scale = preview_scale(sheet);
+    imageops::resize(&img, img.width() * scale, img.height() * scale, imageops::FilterType::Nearest)
+}
+
+pub fn render_png(art: &str) -> Result<Vec<u8>, String> {
+    let sheet = parse_sheet(art)?;
+    let first_frame = sheet.frames.first().map(String::as_str).unwrap_or_default();
+
+    let mut png = Vec::new();
+    render_frame(&sheet, first_frame)
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode PNG: {e}"))?;
+
+    Ok(png)
+}
+
+pub fn render_gif(art: &str, fps: f32) -> Result<Vec<u8>, String> {
+    let sheet = parse_sheet(art)?;
+    let (width, height) = pixel_size(&sheet);
+    let scale = preview_scale(&sheet);
+
+    // GIF delays are in hundredths of a second, most viewers clamp anything below 2
+    let delay = (100.0 / fps.max(0.1)).round().clamp(2.0, u16::MAX as f32) as u16;
+    let palette = [BACKGROUND, BACKGROUND, BACKGROUND, FOREGROUND, FOREGROUND, FOREGROUND];
+
+    let mut gif = Vec::new();
+    {
+        let mut encoder = gif::Encoder::new(&mut gif, (width * scale) as u16, (height * scale) as u16, &palette)
+            .map_err(|e| format!("Failed to create GIF encoder: {e}"))?;
+        encoder.set_repeat(gif::Repeat::Infinite)
+            .map_err(|e| format!("Failed to write GIF header: {e}"))?;
+
+        for frame in &sheet.frames {
+            let img = render_frame(&sheet, frame);
+            let indices: Vec<u8> = img.pixels().map(|p| u8::from(p[0] == FOREGROUND)).collect();
+
+            let mut gif_frame = gif::Frame::from_indexed_pixels(img.width() as u16, img.height() as u16, indices, None);
+            gif_frame.delay = delay;
+            encoder.write_frame(&gif_frame)
+                .map_err(|e| format!("Failed to write GIF frame: {e}"))?;
+        }
+    }
+
+    Ok(gif)
+}
+
+/// Set to `true` behind a reverse proxy that overwrites `Host` and `X-Forwarded-*`,
+/// so that links can be built from them when `PUBLIC_URL` is not set
+pub const TRUST_PROXY_VAR: &str = "TRUST_PROXY_HEADERS";
+
+/// Absolute URL of this server in link previews and Atom feeds, from the environment
+pub fn configured_base_url(headers: &HeaderMap) -> String {
+    let trust_proxy = std::env::var(TRUST_PROXY_VAR).is_ok_and(|value| value == "true");
+    let port = std::env::var("SERVER_PORT").unwrap_or_else(|_| "3000".to_string());
+
+    public_base_url(
+        std::env::var("PUBLIC_URL").ok().as_deref(),
+        trust_proxy,
+        &format!("http://localhost:{port}"),
+        headers,
+    )
+}
+
+/// `PUBLIC_URL` wins. Pages and feeds are cached publicly, so the request's headers are only
+/// used when a trusted proxy sets them; anyone could otherwise send a forged `Host`.
+pub fn public_base_url(configured: Option<&str>, trust_proxy: bool, fallback: &str, headers: &HeaderMap) -> String {
+    if let Some(url) = configured {
+        return url.trim_end_matches('/').to_string();
+    }
+    if !trust_proxy {
+        return fallback.to_string();
+    }
+
+    let header_value = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let scheme = header_value("x-forwarded-proto").unwrap_or("http");
+    let host = header_value("x-forwarded-host")
+        .or_else(|| header_value(header::HOST.as_str()))
+        .unwrap_or("localhost");
+
+    format!("{scheme}://{host}")
+}
+
+fn open_graph_tags(content: &ContentRecord, channel_name: &str, base_url: &str) -> String {
+    let title = if channel_name.is_empty() {
+        content.name.clone()
+    } else {
+        format!("{} by {}", content.name, channel_name)
+    };
+    let frames = AsciiArtSheet::from_string(&content.art)
+        .map(|sheet| sheet.frame_count())
+        .unwrap_or(1);
+    let description = format!("ASCII art animation with {frames} frame(s) at {} fps on Tama", content.fps);
+    let page_url = format!("{base_url}/view/content/{}", content.id);
+    let image_url = format!("{base_url}/content/{}/preview.png", content.id);
+
+    [
+        ("property", "og:type", "website".to_string()),
+        ("property", "og:site_name", "Tama".to_string()),
+        ("property", "og:title", title),
+        ("property", "og:description", description),
+        ("property", "og:url", page_url),
+        ("property", "og:image", image_url),
+        ("name", "twitter:card", "summary_large_image".to_string()),
+    ]
+    .iter()
+    .map(|(attribute, key, value)| {
+        format!("    <meta {attribute}=\"{key}\" content=\"{}\" />\n", escape_html(value))
+    })
+    .collect()
+}
+
+fn inject_into_head(html: &str, tags: &str) -> String {
+    match html.find("</head>") {
+        Some(index) => format!("{}{}{}", &html[..index], tags, &html[index..]),
+        None => html.to_string(),
+    }
+}
+
+pub fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ART: &str = "Ascii Art Animation, 2x1, 5fps\n⣿⠀\n⠀⣿";
+
+    #[test]
+    fn test_render_png_rasterizes_first_frame() {
+        let png = render_png(ART).unwrap();
+        let img = image::load_from_memory(&png).unwrap().to_luma8();
+
+        assert_eq!(img.dimensions(), (4 * PREVIEW_SCALE, 4 * PREVIEW_SCALE));
+        assert_eq!(img.get_pixel(0, 0)[0], FOREGROUND);
+        assert_eq!(img.get_pixel(3 * PREVIEW_SCALE, 0)[0], BACKGROUND);
+    }
+
+    #[test]
+    fn test_render_gif_contains_every_frame() {
+        let gif = render_gif(ART, 5.0).unwrap();
+
+        let mut decoder = gif::DecodeOptions::new().read_info(Cursor::new(gif)).unwrap();
+        let mut delays = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            delays.push(frame.delay);
+        }
+
+        assert_eq!(delays, vec![20, 20]);
+    }
+
+    #[test]
+    fn test_render_rejects_invalid_art() {
+        assert!(render_png("not art").is_err());
+        assert!(render_png("Ascii Art Animation, 2000x10\n⠀").is_err());
+    }
+
+    #[test]
+    fn test_open_graph_tags_are_escaped_and_injected() {
+        let content = ContentRecord {
+            id: 7,
+            channel_id: 1,
+            name: "<Neko> \"idle\"".to_string(),
+            art: ART.to_string(),
+            midi_composition: "4c".to_string(),
+            fps: 5.0,
+            created_at: 0,
+            ..Default::default()
+        };
+
+        let tags = open_graph_tags(&content, "neko", "https://tama.example");
+        let html = inject_into_head("<html><head><title>Tama</title></head></html>", &tags);
+
+        assert!(html.contains("content=\"&lt;Neko&gt; &quot;idle&quot; by neko\""));
+        assert!(html.contains("content=\"https://tama.example/content/7/preview.png\""));
+        assert!(html.contains("with 2 frame(s)"));
+        assert!(html.find("og:title").unwrap() < html.find("</head>").unwrap());
+    }
+
+    #[test]
+    fn test_public_base_url_from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::HOST, "tama.example".parse().unwrap());
+        headers.insert("x-forwarded-proto", "https".parse().unwrap());
+
+        let fallback = "http://localhost:3000";
+        assert_eq!(public_base_url(None, true, fallback, &headers), "https://tama.example");
+        assert_eq!(public_base_url(Some("https://tv.example/"), true, fallback, &headers), "https://tv.example");
+
+        // Without a trusted proxy, a forged Host must not end up in cached pages
+        headers.insert(header::HOST, "attacker.example".parse().unwrap());
+        assert_eq!(public_base_url(None, false, fallback, &headers), fallback);
+
+        headers.insert("x-forwarded-host", "tv.example".parse().unwrap());
+        assert_eq!(public_base_url(None, true, fallback, &headers), "https://tv.example");
+    }
+}
@@ -0,0 +1,217 @@
+use axum::{
+    extract::{Json, State},
+    http::{HeaderMap, StatusCode},
+};
+
+use crate::error::{database_error, internal_error};
+use crate::{auth, AppState};
+use tama::api::{ChannelProfile, UpdateProfileRequest};
+use tama::ascii_art_converter::AsciiArtSheet;
+
+const MAX_DISPLAY_NAME_LENGTH: usize = 50;
+const MAX_BIO_LENGTH: usize = 280;
+const MAX_LINKS: usize = 5;
+const MAX_LINK_LENGTH: usize = 200;
+/// Avatars sit next to the profile text on the card, so they are a fraction of the TV
+const MAX_AVATAR_WIDTH: usize = 16;
+const MAX_AVATAR_HEIGHT: usize = 4;
+
+pub async fn update_profile(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<UpdateProfileRequest>,
+) -> Result<Json<ChannelProfile>, (StatusCode, String)> {
+    let caller = auth::authenticate_caller(&headers, &state.jwt_secret)
+        .map_err(|status| (status, "Authentication required".to_string()))?;
+
+    let role = auth::caller_role(state.storage.as_ref(), &caller, caller.channel_id).await
+        .map_err(database_error)?;
+
+    if !role.is_some_and(|role| role.can_manage()) {
+        return Err((StatusCode::FORBIDDEN, "Only the channel owner can edit its profile".to_string()));
+    }
+
+    let channel = state.storage.find_channel_by_id(caller.channel_id).await
+        .map_err(database_error)?
+        .ok_or((StatusCode::NOT_FOUND, "Channel not found".to_string()))?;
+
+    let current = state.storage.find_profile(channel.id).await
+        .map_err(database_error)?;
+
+    let profile = apply_update(current, request);
+    validate_profile(&profile).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+    state.storage.save_profile(channel.id, &profile).await
+        .map_err(|e| internal_error("Failed to save profile", e))?;
+
+    tracing::info!("Profile updated: channel_id={}", channel.id);
+    Ok(Json(profile))
+}
+
+fn apply_update(mut profile: ChannelProfile, request: UpdateProfileRequest) -> ChannelProfile {
+    if let Some(display_name) = request.display_name {
+        profile.display_name = non_empty(display_name);
+    }
+    if let Some(bio) = request.bio {
+        profile.bio = non_empty(bio);
+    }
+    if let Some(avatar) = request.avatar {
+        profile.avatar = non_empty(avatar);
+    }
+    if let Some(links) = request.links {
+        profile.links = links.into_iter().filter_map(non_empty).collect();
+    }
+    profile
+}
+
+fn non_empty(value: String) -> Option<String> {
+    let trimmed = value.trim();
+    (!trimmed.is_empty()).then(|| trimmed.to_string())
+}
+
+fn validate_profile(profile: &ChannelProfile) -> Result<(), String> {
+    if let Some(display_name) = &profile.display_name {
+        if display_name.chars().count() > MAX_DISPLAY_NAME_LENGTH {
+            return Err(format!("Display name is too long (max {MAX_DISPLAY_NAME_LENGTH} characters)"));
+        }
+        if display_name.chars().any(char::is_control) {
+            return Err("Display name cannot contain control characters".to_string());
+        }
+    }
+
+    if let Some(bio) = &profile.bio {
+        if bio.chars().count() > MAX_BIO_LENGTH {
+            return Err(format!("Bio is too long (max {MAX_BIO_LENGTH} characters)"));
+        }
+        if bio.chars().any(|c| c.is_control() && c != '\n') {
+            return Err("Bio cannot contain control characters".to_string());
+        }
+    }
+
+    if let Some(avatar) = &profile.avatar {
+        validate_avatar(avatar)?;
+    }
+
+    if profile.links.len() > MAX_LINKS {
+        return Err(format!("Too many links (max {MAX_LINKS})"));
+    }
+
+    for link in &profile.links {
+        if link.len() > MAX_LINK_LENGTH {
+            return Err(format!("Link is too long (max {MAX_LINK_LENGTH} characters): {link}"));
+        }
+        if !(link.starts_with("https://") || link.starts_with("http://")) {
+            return Err(format!("Links must start with http:// or https://: {link}"));
+        }
+        if link.chars().any(|c| c.is_whitespace() || c.is_control()) {
+            return Err(format!("Links cannot contain whitespace: {link}"));
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_avatar(avatar: &str) -> Result<(), String> {
+    let sheet = AsciiArtSheet::validate(avatar)
+        .map_err(|e| format!("Invalid avatar: {e}"))?;
+
+    if sheet.frame_count() != 1 {
+        return Err(format!("Avatar must have a single frame, found {}", sheet.frame_count()));
+    }
+
+    if sheet.width > MAX_AVATAR_WIDTH || sheet.height > MAX_AVATAR_HEIGHT {
+        return Err(format!(
+            "Avatar is {}x{}, at most {MAX_AVATAR_WIDTH}x{MAX_AVATAR_HEIGHT} is allowed",
+            sheet.width, sheet.height
+        ));
+    }
+
+    let is_braille = |c: char| ('\u{2800}'..='\u{28FF}').contains(&c);
+    if !sheet.frames[0].lines().all(|line| line.chars().all(is_braille)) {
+        return Err("Avatar can only contain braille characters".to_string());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{owner_headers};
+    use crate::storage::{ChannelStore, MemoryStorage};
+    use std::sync::Arc;
+
+    const AVATAR: &str = "Ascii Art Animation, 4x2\n⣿⠉⠉⣿\n⣿⣀⣀⣿";
+
+    #[test]
+    fn test_apply_update_keeps_missing_fields_and_clears_empty_ones() {
+        let current = ChannelProfile {
+            display_name: Some("Neko".to_string()),
+            bio: Some("Sleeps a lot".to_string()),
+            avatar: None,
+            links: vec!["https://neko.example".to_string()],
+        };
+
+        let updated = apply_update(current, UpdateProfileRequest {
+            bio: Some("  ".to_string()),
+            links: Some(vec!["https://a.example".to_string(), "".to_string()]),
+            ..Default::default()
+        });
+
+        assert_eq!(updated.display_name.as_deref(), Some("Neko"));
+        assert_eq!(updated.bio, None);
+        assert_eq!(updated.links, vec!["https://a.example".to_string()]);
+    }
+
+    #[test]
+    fn test_validate_profile_limits() {
+        let valid = ChannelProfile {
+            display_name: Some("Neko".to_string()),
+            bio: Some("Line one\nLine two".to_string()),
+            avatar: Some(AVATAR.to_string()),
+            links: vec!["https://neko.example".to_string()],
+        };
+        assert!(validate_profile(&valid).is_ok());
+
+        let invalid_profiles = [
+            ChannelProfile { display_name: Some("n".repeat(MAX_DISPLAY_NAME_LENGTH + 1)), ..valid.clone() },
+            ChannelProfile { bio: Some("b".repeat(MAX_BIO_LENGTH + 1)), ..valid.clone() },
+            ChannelProfile { links: vec!["javascript:alert(1)".to_string()], ..valid.clone() },
+            ChannelProfile { links: vec!["https://a.example".to_string(); MAX_LINKS + 1], ..valid.clone() },
+            ChannelProfile { avatar: Some(format!("{AVATAR}\n⣿⣿⣿⣿\n⣿⣿⣿⣿")), ..valid.clone() },
+            ChannelProfile { avatar: Some("Ascii Art Animation, 4x1\nabcd".to_string()), ..valid.clone() },
+            ChannelProfile { avatar: Some(format!("Ascii Art Animation, 17x1\n{}", "⣿".repeat(17))), ..valid.clone() },
+        ];
+
+        for profile in invalid_profiles {
+            assert!(validate_profile(&profile).is_err(), "should reject {profile:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_update_profile_saves_for_authenticated_channel() {
+        let storage = Arc::new(MemoryStorage::new());
+        let channel = storage.create_channel("neko", "hash", 1).await.unwrap();
+        let state = AppState::for_tests(storage.clone());
+
+        let headers = owner_headers(&state, &channel);
+
+        let request = UpdateProfileRequest {
+            display_name: Some("Neko".to_string()),
+            avatar: Some(AVATAR.to_string()),
+            ..Default::default()
+        };
+        let Json(profile) = update_profile(State(state), headers, Json(request)).await.unwrap();
+
+        assert_eq!(profile.display_name.as_deref(), Some("Neko"));
+        assert_eq!(storage.find_profile(channel.id).await.unwrap(), profile);
+    }
+
+    #[tokio::test]
+    async fn test_update_profile_requires_auth() {
+        let state = AppState::for_tests(Arc::new(MemoryStorage::new()));
+
+        let result = update_profile(State(state), HeaderMap::new(), Json(UpdateProfileRequest::default())).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::UNAUTHORIZED);
+    }
+}
@@ -1,16 +1,28 @@
-use crate::{auth_endpoints, channel_endpoints, middleware, rate_limiter, AppState, DbPool};
+use crate::{
+    atom, audit, auth_endpoints, broadcast, channel_endpoints, comment_endpoints, feed_cache::FeedCache, member_endpoints,
+    middleware, mirror, preview,
+    profile_endpoints,
+    rate_limiter,
+    shutdown::{self, Shutdown},
+    storage::{ContentRecord, Listing, SqliteStorage, Storage},
+    tls_reload, visibility,
+    wav::{self, WavCache},
+    webhook_endpoints,
+    webhooks::{self, RetryPolicy, WebhookDispatcher},
+    AppState,
+};
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{header, HeaderMap, StatusCode},
     middleware as axum_middleware,
-    response::Json,
-    routing::{get, post},
+    response::{IntoResponse, Json, Response},
+    routing::{delete, get, patch, post, put},
     Router,
 };
-use r2d2_sqlite::SqliteConnectionManager;
-use rusqlite::params;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use tama::api::{ChannelProfile, ContentProvenance};
+use tama::content_parser::{self, ContentFile};
 use tower_http::{cors::CorsLayer, services::{ServeDir, ServeFile}, trace::TraceLayer};
 use axum_server::tls_rustls::RustlsConfig;
 
@@ -29,15 +41,26 @@ pub struct FeedItem {
 #[derive(Serialize, Deserialize)]
 pub struct ContentData {
     pub id: i64,
+    #[serde(default)]
+    pub name: String,
     pub art: String,
     pub midi_composition: String,
     pub fps: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// Key to check `signature` with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+    /// Set when the content was mirrored from a peer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<ContentProvenance>,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct ChannelResponse {
     pub id: i64,
     pub name: String,
+    pub profile: ChannelProfile,
     pub contents: Vec<ContentData>,
 }
 
@@ -53,217 +76,183 @@ fn default_limit() -> i64 {
     50
 }
 
-
-pub fn initialize_database(db_path: &str) -> Result<DbPool, String> {
-    let manager = SqliteConnectionManager::file(db_path);
-    let pool = r2d2::Pool::builder()
-        .max_size(10)
-        .build(manager)
-        .map_err(|e| format!("Failed to create connection pool: {e}"))?;
-
-    // Get a connection from the pool to initialize the tables
-    let conn = pool.get()
-        .map_err(|e| format!("Failed to get connection from pool: {e}"))?;
-
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS channels (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            name TEXT NOT NULL UNIQUE,
-            password_hash TEXT NOT NULL,
-            created_at INTEGER NOT NULL
-        )",
-        [],
-    )
-    .map_err(|e| format!("Failed to create channels table: {e}"))?;
-
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS contents (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            channel_id INTEGER NOT NULL,
-            name TEXT NOT NULL DEFAULT '',
-            art TEXT NOT NULL,
-            midi_composition TEXT NOT NULL,
-            fps REAL NOT NULL,
-            created_at INTEGER NOT NULL,
-            FOREIGN KEY (channel_id) REFERENCES channels(id)
-        )",
-        [],
-    )
-    .map_err(|e| format!("Failed to create contents table: {e}"))?;
-
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS servers (
-            server_url TEXT PRIMARY KEY
-        )",
-        [],
-    )
-    .map_err(|e| format!("Failed to create servers table: {e}"))?;
-
-    // Create indexes for better query performance
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_channels_name ON channels(name)",
-        [],
-    )
-    .map_err(|e| format!("Failed to create index on channels.name: {e}"))?;
-
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_contents_channel_id ON contents(channel_id)",
-        [],
-    )
-    .map_err(|e| format!("Failed to create index on contents.channel_id: {e}"))?;
-
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_contents_created_at ON contents(created_at)",
-        [],
-    )
-    .map_err(|e| format!("Failed to create index on contents.created_at: {e}"))?;
-
-    // Drop the connection back to the pool
-    drop(conn);
-
-    Ok(pool)
+impl From<ContentRecord> for ContentData {
+    fn from(record: ContentRecord) -> Self {
+        Self {
+            id: record.id,
+            name: record.name,
+            art: record.art,
+            midi_composition: record.midi_composition,
+            fps: record.fps,
+            signature: record.signature,
+            public_key: record.public_key,
+            origin: record.origin.map(|origin| ContentProvenance {
+                server_url: origin.server_url,
+                content_id: origin.content_id,
+            }),
+        }
+    }
 }
 
-async fn get_feed(State(state): State<AppState>) -> Result<Json<Vec<FeedItem>>, StatusCode> {
-    println!("[GET /feed] Request received");
-    let db = state.db.get()
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    let mut stmt = db
-        .prepare(
-            "SELECT c.id, c.name, co.id, co.art, co.midi_composition, co.fps
-             FROM channels c
-             JOIN contents co ON c.id = co.channel_id
-             ORDER BY co.created_at DESC
-             LIMIT 30",
-        )
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+async fn get_feed(State(state): State<AppState>) -> Result<Response, StatusCode> {
+    tracing::debug!("[GET /feed] Request received");
+
+    let now = chrono::Utc::now().timestamp();
+    let (body, cache_status) = match state.feed_cache.get(now) {
+        Ok(body) => (body, "HIT"),
+        Err(generation) => {
+            let entries = state.storage.latest_contents(Listing::PublicAt(now), 30).await
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            // The feed changes by itself when the next scheduled content goes live
+            let expires_at = state.storage.next_scheduled_publish(now).await
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+            let feed_items: Vec<FeedItem> = entries
+                .into_iter()
+                .map(|entry| FeedItem {
+                    channel: ChannelInfo {
+                        id: entry.channel_id,
+                        name: entry.channel_name,
+                    },
+                    content: ContentData::from(entry.content),
+                })
+                .collect();
+
+            tracing::debug!("[GET /feed] Rebuilt feed with {} content items", feed_items.len());
+            let body = serde_json::to_vec(&feed_items)
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            let body = axum::body::Bytes::from(body);
+            state.feed_cache.store(generation, body.clone(), expires_at);
+            (body, "MISS")
+        }
+    };
 
-    let feed_items = stmt
-        .query_map([], |row| {
-            Ok(FeedItem {
-                channel: ChannelInfo {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                },
-                content: ContentData {
-                    id: row.get(2)?,
-                    art: row.get(3)?,
-                    midi_composition: row.get(4)?,
-                    fps: row.get(5)?,
-                },
-            })
-        })
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let stats = state.feed_cache.stats();
+    tracing::debug!("[GET /feed] Cache {cache_status} (hits: {}, misses: {})", stats.hits, stats.misses);
 
-    println!("[GET /feed] Returning {} content items", feed_items.len());
-    Ok(Json(feed_items))
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/json"),
+            (header::HeaderName::from_static("x-cache"), cache_status),
+        ],
+        body,
+    ).into_response())
 }
 
+/// Lists public, published contents. The channel's own token also lists unlisted,
+/// private and scheduled ones.
 async fn get_channel(
     Path(channel_identifier): Path<String>,
     Query(pagination): Query<PaginationParams>,
+    headers: HeaderMap,
     State(state): State<AppState>,
 ) -> Result<Json<ChannelResponse>, StatusCode> {
-    println!("[GET /channel/{channel_identifier}] Request received");
-    let db = state.db.get()
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    tracing::debug!("[GET /channel/{channel_identifier}] Request received");
 
     // Validate pagination parameters
     let limit = pagination.limit.clamp(1, 100);
     let offset = pagination.offset.max(0);
 
-    // Try to parse as integer ID first, otherwise treat as channel name
-    let (id, name): (i64, String) = if let Ok(channel_id) = channel_identifier.parse::<i64>() {
-        // Query by ID
-        db.query_row(
-            "SELECT id, name FROM channels WHERE id = ?1",
-            params![channel_id],
-            |row| Ok((row.get(0)?, row.get(1)?)),
-        )
-        .map_err(|_| StatusCode::NOT_FOUND)?
+    let channel = state.storage.find_channel(&channel_identifier).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let is_member = visibility::is_member(&state, &headers, channel.id).await
+        .map_err(|(status, _)| status)?;
+
+    let listing = if is_member {
+        Listing::All
     } else {
-        // Query by name
-        db.query_row(
-            "SELECT id, name FROM channels WHERE name = ?1",
-            params![channel_identifier],
-            |row| Ok((row.get(0)?, row.get(1)?)),
-        )
-        .map_err(|_| StatusCode::NOT_FOUND)?
+        Listing::PublicAt(chrono::Utc::now().timestamp())
     };
 
-    let mut stmt = db
-        .prepare(
-            "SELECT id, art, midi_composition, fps
-             FROM contents
-             WHERE channel_id = ?1
-             ORDER BY id
-             LIMIT ?2 OFFSET ?3",
-        )
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-    let contents = stmt
-        .query_map(params![id, limit, offset], |row| {
-            Ok(ContentData {
-                id: row.get(0)?,
-                art: row.get(1)?,
-                midi_composition: row.get(2)?,
-                fps: row.get(3)?,
-            })
-        })
+    let contents = state.storage.list_channel_contents(channel.id, listing, limit, offset).await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .collect::<Result<Vec<_>, _>>()
+        .into_iter()
+        .map(ContentData::from)
+        .collect();
+
+    let profile = state.storage.find_profile(channel.id).await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    Ok(Json(ChannelResponse { id, name, contents }))
+    Ok(Json(ChannelResponse {
+        id: channel.id,
+        name: channel.name,
+        profile,
+        contents,
+    }))
 }
 
+/// `/content/:id` returns JSON, `/content/:id.txt` the content file read by `content_parser`
 async fn get_content(
-    Path(content_id): Path<i64>,
+    Path(content_ref): Path<String>,
+    headers: HeaderMap,
     State(state): State<AppState>,
-) -> Result<Json<ContentData>, StatusCode> {
-    println!("[GET /content/{content_id}] Request received");
-    let db = state.db.get()
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+) -> Result<Response, StatusCode> {
+    tracing::debug!("[GET /content/{content_ref}] Request received");
+    let (id_part, as_text) = match content_ref.strip_suffix(".txt") {
+        Some(id_part) => (id_part, true),
+        None => (content_ref.as_str(), false),
+    };
+    let content_id = id_part.parse::<i64>().map_err(|_| StatusCode::BAD_REQUEST)?;
 
-    let content = db
-        .query_row(
-            "SELECT id, art, midi_composition, fps
-             FROM contents
-             WHERE id = ?1",
-            params![content_id],
-            |row| {
-                Ok(ContentData {
-                    id: row.get(0)?,
-                    art: row.get(1)?,
-                    midi_composition: row.get(2)?,
-                    fps: row.get(3)?,
-                })
-            },
-        )
-        .map_err(|_| StatusCode::NOT_FOUND)?;
+    let content = visibility::find_visible_content(&state, content_id, &headers).await
+        .map_err(|(status, _)| status)?;
+
+    if !as_text {
+        return Ok(Json(ContentData::from(content)).into_response());
+    }
 
-    Ok(Json(content))
+    let file_name = sanitize_file_name(&content.name);
+    let body = content_parser::serialize_content(&ContentFile {
+        midi_composition: content.midi_composition,
+        art: content.art,
+        fps: content.fps,
+    });
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/plain; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, format!("inline; filename=\"{file_name}.txt\"")),
+        ],
+        body,
+    ).into_response())
 }
 
-async fn get_servers(State(state): State<AppState>) -> Result<Json<Vec<String>>, StatusCode> {
-    println!("[GET /servers] Request received");
-    let db = state.db.get()
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+/// Keeps content names usable as download file names
+fn sanitize_file_name(name: &str) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
 
-    let mut stmt = db
-        .prepare("SELECT server_url FROM servers")
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if sanitized.trim_matches('_').is_empty() {
+        "content".to_string()
+    } else {
+        sanitized
+    }
+}
 
-    let servers = stmt
-        .query_map([], |row| row.get(0))
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .collect::<Result<Vec<String>, _>>()
+/// Unlisted contents by share token, `/share/:token` on the wire
+async fn get_shared_content(
+    Path(token): Path<String>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Json<ContentData>, StatusCode> {
+    tracing::debug!("[GET /share/...] Request received");
+    let content = visibility::find_shared_content(&state, &token, &headers).await
+        .map_err(|(status, _)| status)?;
+
+    Ok(Json(ContentData::from(content)))
+}
+
+async fn get_servers(State(state): State<AppState>) -> Result<Json<Vec<String>>, StatusCode> {
+    tracing::debug!("[GET /servers] Request received");
+    let servers = state.storage.list_servers().await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    println!("[GET /servers] Returning {} servers", servers.len());
+    tracing::debug!("[GET /servers] Returning {} servers", servers.len());
     Ok(Json(servers))
 }
 
@@ -283,7 +272,7 @@ async fn http_redirect_handler(
     axum::response::Redirect::permanent(&https_uri)
 }
 
-async fn run_http_redirect_server() {
+async fn run_http_redirect_server(shutdown: Shutdown) {
     use axum::routing::any;
 
     let app = Router::new()
@@ -293,7 +282,10 @@ async fn run_http_redirect_server() {
     match tokio::net::TcpListener::bind(addr).await {
         Ok(listener) => {
             println!("HTTP redirect server listening on {addr} -> HTTPS");
-            if let Err(e) = axum::serve(listener, app).await {
+            if let Err(e) = axum::serve(listener, app)
+                .with_graceful_shutdown(async move { shutdown.wait().await })
+                .await
+            {
                 eprintln!("HTTP redirect server error: {e}");
             }
         }
@@ -305,7 +297,15 @@ async fn run_http_redirect_server() {
 }
 
 pub async fn run_server(db_path: &str, port: u16, jwt_secret: String) -> Result<(), String> {
-    let pool = initialize_database(db_path)?;
+    let storage: Arc<dyn Storage> = Arc::new(SqliteStorage::open(db_path)?);
+
+    if std::env::var("PUBLIC_URL").is_err() && !std::env::var(preview::TRUST_PROXY_VAR).is_ok_and(|value| value == "true") {
+        eprintln!("Warning: PUBLIC_URL is not set, links in previews and Atom feeds point to localhost");
+    }
+
+    let shutdown = Shutdown::new();
+    shutdown.listen_for_signals();
+    let drain_timeout = shutdown::drain_timeout_from_env();
 
     // Rate limiters with different limits for different endpoint types
     // Auth: 5 requests per minute (stricter to prevent brute force)
@@ -342,19 +342,37 @@ pub async fn run_server(db_path: &str, port: u16, jwt_secret: String) -> Result<
         }
     });
 
+    audit::start_pruning(storage.clone());
+
+    let webhooks = Arc::new(WebhookDispatcher::start(storage.clone(), RetryPolicy::default(), webhooks::allowed_hosts_from_env()));
+    mirror::start(storage.clone(), mirror::sync_interval_from_env());
+
     let state = AppState {
-        db: pool,
+        storage,
+        feed_cache: Arc::new(FeedCache::new()),
+        wav_cache: Arc::new(WavCache::default()),
+        broadcast: Arc::new(broadcast::Broadcast::new()),
         jwt_secret,
         auth_rate_limiter,
         api_rate_limiter,
         upload_rate_limiter,
+        webhooks,
     };
 
     // Public API routes with standard rate limiting
     let public_routes = Router::new()
         .route("/feed", get(get_feed))
+        .route("/feed.atom", get(atom::get_server_feed))
+        .route("/broadcast/now", get(broadcast::get_broadcast_now))
+        .route("/channel/:channel_id/feed.atom", get(atom::get_channel_feed))
         .route("/channel/:channel_id", get(get_channel))
         .route("/content/:content_id", get(get_content))
+        .route("/content/:content_id/comments", get(comment_endpoints::list_comments))
+        .route("/share/:token", get(get_shared_content))
+        .route("/content/:content_id/preview.png", get(preview::get_preview_png))
+        .route("/content/:content_id/preview.gif", get(preview::get_preview_gif))
+        .route("/content/:content_id/audio.wav", get(wav::get_content_audio))
+        .route("/view/content/:content_id", get(preview::view_content_page))
         .route("/servers", get(get_servers))
         .route_layer(axum_middleware::from_fn_with_state(
             state.clone(),
@@ -366,14 +384,36 @@ pub async fn run_server(db_path: &str, port: u16, jwt_secret: String) -> Result<
         .route("/auth/register", post(auth_endpoints::register))
         .route("/auth/login", post(auth_endpoints::login))
         .route("/auth/login-or-signup", post(auth_endpoints::login_or_signup))
+        .route("/auth/users/register", post(member_endpoints::register_user))
+        .route("/auth/users/login", post(member_endpoints::login_user))
+        .route("/invites/:token/accept", post(member_endpoints::accept_invite))
         .route_layer(axum_middleware::from_fn_with_state(
             state.clone(),
             middleware::rate_limit_auth,
         ));
 
+    // Authenticated reads with standard rate limiting
+    let account_routes = Router::new()
+        .route("/channel/me/members", get(member_endpoints::list_members))
+        .route("/channel/me/security-log", get(auth_endpoints::get_security_log))
+        .route("/channel/me/webhooks", get(webhook_endpoints::list_webhooks))
+        .route("/channel/me/webhooks/deliveries", get(webhook_endpoints::list_deliveries))
+        .route_layer(axum_middleware::from_fn_with_state(
+            state.clone(),
+            middleware::rate_limit_api,
+        ));
+
     // Upload routes with upload rate limiting and size validation
     let upload_routes = Router::new()
         .route("/content", post(channel_endpoints::create_content))
+        .route("/content/:content_id/comments", post(comment_endpoints::create_comment))
+        .route("/content/:content_id/comments/:comment_id", delete(comment_endpoints::delete_comment))
+        .route("/channel/me", patch(profile_endpoints::update_profile))
+        .route("/channel/me/invites", post(member_endpoints::create_invite))
+        .route("/channel/me/members/:user_id", delete(member_endpoints::remove_member))
+        .route("/channel/me/key", put(channel_endpoints::register_key))
+        .route("/channel/me/webhooks", post(webhook_endpoints::create_webhook))
+        .route("/channel/me/webhooks/:webhook_id", delete(webhook_endpoints::delete_webhook))
         .with_state(state.clone())
         .route_layer(axum_middleware::from_fn(middleware::validate_content_size))
         .route_layer(axum_middleware::from_fn_with_state(
@@ -389,6 +429,7 @@ pub async fn run_server(db_path: &str, port: u16, jwt_secret: String) -> Result<
     let app = Router::new()
         .merge(public_routes)
         .merge(auth_routes)
+        .merge(account_routes)
         .nest_service("/", static_service)
         .layer(CorsLayer::permissive())
         .layer(TraceLayer::new_for_http())
@@ -408,16 +449,33 @@ pub async fn run_server(db_path: &str, port: u16, jwt_secret: String) -> Result<
 
             let tls_config = load_tls_config(&cert_path, &key_path).await?;
 
+            // Pick up renewed certificates without restarting
+            tls_reload::spawn_tls_reloader(
+                tls_config.clone(),
+                cert_path.clone(),
+                key_path.clone(),
+                shutdown.clone(),
+            );
+
             let addr = format!("0.0.0.0:{port}");
             println!("HTTPS server listening on {addr}");
 
             // Spawn HTTP->HTTPS redirect server on port 80 if we're on port 443
             if port == 443 {
-                tokio::spawn(run_http_redirect_server());
+                tokio::spawn(run_http_redirect_server(shutdown.clone()));
             }
 
+            let handle = axum_server::Handle::new();
+            let shutdown_handle = handle.clone();
+            let shutdown_signal = shutdown.clone();
+            tokio::spawn(async move {
+                shutdown_signal.wait().await;
+                shutdown_handle.graceful_shutdown(Some(drain_timeout));
+            });
+
             // Start HTTPS server
             axum_server::bind_rustls(addr.parse().unwrap(), tls_config)
+                .handle(handle)
                 .serve(app.into_make_service_with_connect_info::<std::net::SocketAddr>())
                 .await
                 .map_err(|e| format!("HTTPS server error: {e}"))?;
@@ -433,26 +491,183 @@ pub async fn run_server(db_path: &str, port: u16, jwt_secret: String) -> Result<
             println!("HTTP server listening on {addr}");
             println!("WARNING: HTTP-only mode is NOT secure for production!");
 
-            axum::serve(
+            let shutdown_signal = shutdown.clone();
+            let server = axum::serve(
                 listener,
                 app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
             )
-            .await
-            .map_err(|e| format!("HTTP server error: {e}"))?;
+            .with_graceful_shutdown(async move { shutdown_signal.wait().await });
+
+            // axum::serve waits for every connection to close, so cap the drain ourselves
+            tokio::select! {
+                result = server => result.map_err(|e| format!("HTTP server error: {e}"))?,
+                _ = async {
+                    shutdown.wait().await;
+                    tokio::time::sleep(drain_timeout).await;
+                } => {
+                    println!("Drain timeout of {}s elapsed, closing remaining connections", drain_timeout.as_secs());
+                }
+            }
         }
     }
 
+    println!("Server stopped");
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::{owner_headers};
+    use crate::storage::{ChannelStore, ContentStore, MemoryStorage, NewContent};
 
-    #[test]
-    fn test_initialize_database() {
-        let conn = initialize_database(":memory:");
-        assert!(conn.is_ok());
+    async fn feed_items(response: Response) -> Vec<FeedItem> {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
     }
 
+    async fn state_with_content() -> AppState {
+        let storage = MemoryStorage::new();
+        let channel = storage.create_channel("neko", "hash", 1).await.unwrap();
+        storage.create_content(NewContent {
+            channel_id: channel.id,
+            name: "idle".to_string(),
+            art: "Ascii Art Animation, 2x1\n⠁⠁".to_string(),
+            midi_composition: "4c".to_string(),
+            fps: 10.0,
+            created_at: 2,
+            ..Default::default()
+        }).await.unwrap();
+
+        AppState::for_tests(Arc::new(storage))
+    }
+
+    #[tokio::test]
+    async fn test_get_feed_returns_contents() {
+        let state = state_with_content().await;
+
+        let feed = feed_items(get_feed(State(state)).await.unwrap()).await;
+        assert_eq!(feed.len(), 1);
+        assert_eq!(feed[0].channel.name, "neko");
+        assert_eq!(feed[0].content.midi_composition, "4c");
+    }
+
+    #[tokio::test]
+    async fn test_get_feed_is_cached_until_upload() {
+        let state = state_with_content().await;
+
+        let first = get_feed(State(state.clone())).await.unwrap();
+        assert_eq!(first.headers()["x-cache"], "MISS");
+        let second = get_feed(State(state.clone())).await.unwrap();
+        assert_eq!(second.headers()["x-cache"], "HIT");
+        assert_eq!(state.feed_cache.stats().hits, 1);
+
+        state.feed_cache.invalidate();
+        let third = get_feed(State(state)).await.unwrap();
+        assert_eq!(third.headers()["x-cache"], "MISS");
+    }
+
+    #[tokio::test]
+    async fn test_get_channel_by_name_and_id() {
+        let state = state_with_content().await;
+        let pagination = || Query(PaginationParams { limit: 50, offset: 0 });
+
+        let Json(by_name) = get_channel(Path("neko".to_string()), pagination(), HeaderMap::new(), State(state.clone()))
+            .await
+            .unwrap();
+        let Json(by_id) = get_channel(Path(by_name.id.to_string()), pagination(), HeaderMap::new(), State(state))
+            .await
+            .unwrap();
+
+        assert_eq!(by_name.contents.len(), 1);
+        assert_eq!(by_id.name, "neko");
+        assert!(by_id.profile.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_channel_includes_profile() {
+        let state = state_with_content().await;
+        let profile = ChannelProfile {
+            display_name: Some("Neko".to_string()),
+            ..Default::default()
+        };
+        let channel = state.storage.find_channel_by_name("neko").await.unwrap().unwrap();
+        state.storage.save_profile(channel.id, &profile).await.unwrap();
+
+        let Json(response) = get_channel(
+            Path("neko".to_string()),
+            Query(PaginationParams { limit: 50, offset: 0 }),
+            HeaderMap::new(),
+            State(state),
+        ).await.unwrap();
+
+        assert_eq!(response.profile, profile);
+    }
+
+    #[tokio::test]
+    async fn test_private_contents_are_only_visible_to_owner() {
+        let state = state_with_content().await;
+        let channel = state.storage.find_channel_by_name("neko").await.unwrap().unwrap();
+        let private = state.storage.create_content(NewContent {
+            channel_id: channel.id,
+            name: "secret".to_string(),
+            art: "Ascii Art Animation, 2x1\n⠁⠁".to_string(),
+            midi_composition: "4c".to_string(),
+            fps: 10.0,
+            created_at: 3,
+            visibility: tama::api::Visibility::Private,
+            ..Default::default()
+        }).await.unwrap();
+
+        let owner = owner_headers(&state, &channel);
+        let pagination = || Query(PaginationParams { limit: 50, offset: 0 });
+
+        let Json(anonymous_view) = get_channel(Path("neko".to_string()), pagination(), HeaderMap::new(), State(state.clone()))
+            .await
+            .unwrap();
+        let Json(owner_view) = get_channel(Path("neko".to_string()), pagination(), owner.clone(), State(state.clone()))
+            .await
+            .unwrap();
+        assert_eq!(anonymous_view.contents.len(), 1);
+        assert_eq!(owner_view.contents.len(), 2);
+
+        let path = || Path(private.id.to_string());
+        let result = get_content(path(), HeaderMap::new(), State(state.clone())).await;
+        assert_eq!(result.err(), Some(StatusCode::NOT_FOUND));
+        assert!(get_content(path(), owner, State(state.clone())).await.is_ok());
+
+        let feed = feed_items(get_feed(State(state)).await.unwrap()).await;
+        assert_eq!(feed.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_content_not_found() {
+        let state = state_with_content().await;
+
+        let result = get_content(Path("999".to_string()), HeaderMap::new(), State(state.clone())).await;
+        assert_eq!(result.err(), Some(StatusCode::NOT_FOUND));
+
+        let result = get_content(Path("abc.txt".to_string()), HeaderMap::new(), State(state)).await;
+        assert_eq!(result.err(), Some(StatusCode::BAD_REQUEST));
+    }
+
+    #[tokio::test]
+    async fn test_get_content_as_text_round_trips() {
+        let state = state_with_content().await;
+
+        let response = get_content(Path("1.txt".to_string()), HeaderMap::new(), State(state)).await.unwrap();
+        assert_eq!(response.headers()[header::CONTENT_DISPOSITION], "inline; filename=\"idle.txt\"");
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let parsed = content_parser::parse_content(std::str::from_utf8(&body).unwrap()).unwrap();
+        assert_eq!(parsed.midi_composition, "4c");
+        assert_eq!(parsed.fps, 10.0);
+        assert_eq!(parsed.art, "Ascii Art Animation, 2x1\n⠁⠁");
+    }
+
+    #[test]
+    fn test_sanitize_file_name() {
+        assert_eq!(sanitize_file_name("neko idle/2"), "neko_idle_2");
+        assert_eq!(sanitize_file_name("../"), "content");
+    }
 }
@@ -0,0 +1,109 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Time given to in-flight requests (e.g. uploads) to complete after a shutdown signal
+pub const DEFAULT_DRAIN_TIMEOUT_SECS: u64 = 30;
+
+/// Shared shutdown flag, flipped once on SIGTERM/SIGINT and observed by every server task
+#[derive(Clone)]
+pub struct Shutdown {
+    sender: Arc<watch::Sender<bool>>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        let (sender, _) = watch::channel(false);
+        Self {
+            sender: Arc::new(sender),
+        }
+    }
+
+    /// Spawns a task that triggers the shutdown on SIGTERM or SIGINT
+    pub fn listen_for_signals(&self) {
+        let shutdown = self.clone();
+        tokio::spawn(async move {
+            wait_for_termination_signal().await;
+            tracing::info!("Shutdown signal received, draining connections...");
+            shutdown.trigger();
+        });
+    }
+
+    pub fn trigger(&self) {
+        self.sender.send_replace(true);
+    }
+
+    /// Resolves once the shutdown has been triggered
+    pub async fn wait(&self) {
+        let mut receiver = self.sender.subscribe();
+        // The sender lives as long as `self`, so this can only fail if it was dropped
+        receiver.wait_for(|triggered| *triggered).await.ok();
+    }
+}
+
+pub fn drain_timeout_from_env() -> Duration {
+    let seconds = std::env::var("SHUTDOWN_DRAIN_SECONDS")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_DRAIN_TIMEOUT_SECS);
+
+    Duration::from_secs(seconds)
+}
+
+#[cfg(unix)]
+async fn wait_for_termination_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut sigterm = match signal(SignalKind::terminate()) {
+        Ok(sigterm) => sigterm,
+        Err(e) => {
+            tracing::error!("Failed to install SIGTERM handler: {}", e);
+            tokio::signal::ctrl_c().await.ok();
+            return;
+        }
+    };
+
+    tokio::select! {
+        _ = sigterm.recv() => {}
+        _ = tokio::signal::ctrl_c() => {}
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_termination_signal() {
+    tokio::signal::ctrl_c().await.ok();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_wait_resolves_after_trigger() {
+        let shutdown = Shutdown::new();
+        let waiter = shutdown.clone();
+        let handle = tokio::spawn(async move { waiter.wait().await });
+
+        shutdown.trigger();
+        tokio::time::timeout(Duration::from_secs(1), handle)
+            .await
+            .expect("wait() should resolve after trigger")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_wait_resolves_when_already_triggered() {
+        let shutdown = Shutdown::new();
+        shutdown.trigger();
+
+        tokio::time::timeout(Duration::from_secs(1), shutdown.wait())
+            .await
+            .expect("wait() should resolve immediately");
+    }
+}
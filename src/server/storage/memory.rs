@@ -0,0 +1,630 @@
+use async_trait::async_trait;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Mutex, MutexGuard};
+use tama::api::{AuthEventKind, ChannelProfile, DeliveryStatus, Role, Visibility, WebhookEvent};
+
+use super::{
+    ArchiveImport, ArchiveStore, ChannelImport, ChannelImportResult, ChannelImportStatus, AuditStore, AuthEventRecord, ChannelRecord, ChannelStore, CommentRecord, CommentStore, ContentRecord,
+    ContentStore, DeliveryRecord, DeliveryUpdate, FeedEntry, InviteRecord, Listing, LoginFailures, MemberRecord, MemberStore,
+    MirrorRecord, MirrorStore, NewAuthEvent, NewComment, NewContent, NewWebhook, ServerStore, StorageError, StorageResult, UserRecord,
+    WebhookRecord, WebhookStore,
+};
+
+#[derive(Default, Clone)]
+struct MemoryData {
+    channels: Vec<ChannelRecord>,
+    contents: Vec<ContentRecord>,
+    servers: BTreeSet<String>,
+    profiles: HashMap<i64, ChannelProfile>,
+    public_keys: HashMap<i64, String>,
+    users: Vec<UserRecord>,
+    /// Keyed by channel id, in join order
+    members: HashMap<i64, Vec<(i64, Role, i64)>>,
+    invites: HashMap<String, InviteRecord>,
+    auth_events: Vec<AuthEventRecord>,
+    comments: Vec<CommentRecord>,
+    webhooks: Vec<WebhookRecord>,
+    deliveries: Vec<DeliveryRecord>,
+    mirrors: Vec<MirrorRecord>,
+}
+
+/// In-memory storage for tests, mirrors the behavior of `SqliteStorage`
+#[derive(Default)]
+pub struct MemoryStorage {
+    data: Mutex<MemoryData>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn data(&self) -> StorageResult<MutexGuard<'_, MemoryData>> {
+        self.data
+            .lock()
+            .map_err(|_| StorageError::Backend("Memory storage lock poisoned".to_string()))
+    }
+}
+
+fn next_id(ids: impl Iterator<Item = i64>) -> i64 {
+    ids.max().unwrap_or(0) + 1
+}
+
+#[async_trait]
+impl ChannelStore for MemoryStorage {
+    async fn create_channel(&self, name: &str, password_hash: &str, created_at: i64) -> StorageResult<ChannelRecord> {
+        let mut data = self.data()?;
+
+        if data.channels.iter().any(|c| c.name == name) {
+            return Err(StorageError::AlreadyExists);
+        }
+
+        let channel = ChannelRecord {
+            id: next_id(data.channels.iter().map(|c| c.id)),
+            name: name.to_string(),
+            password_hash: password_hash.to_string(),
+            created_at,
+        };
+        data.channels.push(channel.clone());
+        Ok(channel)
+    }
+
+    async fn find_channel_by_id(&self, id: i64) -> StorageResult<Option<ChannelRecord>> {
+        Ok(self.data()?.channels.iter().find(|c| c.id == id).cloned())
+    }
+
+    async fn find_channel_by_name(&self, name: &str) -> StorageResult<Option<ChannelRecord>> {
+        Ok(self.data()?.channels.iter().find(|c| c.name == name).cloned())
+    }
+
+    async fn list_channels(&self) -> StorageResult<Vec<ChannelRecord>> {
+        Ok(self.data()?.channels.clone())
+    }
+
+    async fn find_profile(&self, channel_id: i64) -> StorageResult<ChannelProfile> {
+        Ok(self.data()?.profiles.get(&channel_id).cloned().unwrap_or_default())
+    }
+
+    async fn save_profile(&self, channel_id: i64, profile: &ChannelProfile) -> StorageResult<()> {
+        self.data()?.profiles.insert(channel_id, profile.clone());
+        Ok(())
+    }
+
+    async fn find_public_key(&self, channel_id: i64) -> StorageResult<Option<String>> {
+        Ok(self.data()?.public_keys.get(&channel_id).cloned())
+    }
+
+    async fn save_public_key(&self, channel_id: i64, public_key: &str, _updated_at: i64) -> StorageResult<()> {
+        self.data()?.public_keys.insert(channel_id, public_key.to_string());
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ContentStore for MemoryStorage {
+    async fn create_content(&self, content: NewContent) -> StorageResult<ContentRecord> {
+        let mut data = self.data()?;
+
+        let record = ContentRecord {
+            id: next_id(data.contents.iter().map(|c| c.id)),
+            channel_id: content.channel_id,
+            name: content.name,
+            art: content.art,
+            midi_composition: content.midi_composition,
+            fps: content.fps,
+            created_at: content.created_at,
+            visibility: content.visibility,
+            publish_at: content.publish_at,
+            share_token: content.share_token,
+            signature: content.signature,
+            public_key: content.public_key,
+            origin: content.origin,
+        };
+        data.contents.push(record.clone());
+        Ok(record)
+    }
+
+    async fn find_content(&self, id: i64) -> StorageResult<Option<ContentRecord>> {
+        Ok(self.data()?.contents.iter().find(|c| c.id == id).cloned())
+    }
+
+    async fn find_content_by_share_token(&self, token: &str) -> StorageResult<Option<ContentRecord>> {
+        Ok(self.data()?
+            .contents
+            .iter()
+            .find(|c| c.share_token.as_deref() == Some(token))
+            .cloned())
+    }
+
+    async fn list_channel_contents(&self, channel_id: i64, listing: Listing, limit: i64, offset: i64) -> StorageResult<Vec<ContentRecord>> {
+        Ok(self.data()?
+            .contents
+            .iter()
+            .filter(|c| c.channel_id == channel_id && listing.includes(c))
+            .skip(offset.max(0) as usize)
+            .take(limit.max(0) as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn latest_channel_contents(&self, channel_id: i64, listing: Listing, limit: i64) -> StorageResult<Vec<ContentRecord>> {
+        let mut contents: Vec<ContentRecord> = self.data()?
+            .contents
+            .iter()
+            .filter(|c| c.channel_id == channel_id && listing.includes(c))
+            .cloned()
+            .collect();
+
+        contents.sort_by_key(|c| std::cmp::Reverse((c.published_at(), c.id)));
+        contents.truncate(limit.max(0) as usize);
+        Ok(contents)
+    }
+
+    async fn latest_contents(&self, listing: Listing, limit: i64) -> StorageResult<Vec<FeedEntry>> {
+        let data = self.data()?;
+
+        let mut entries: Vec<FeedEntry> = data
+            .contents
+            .iter()
+            .filter(|content| listing.includes(content) && content.origin.is_none())
+            .filter_map(|content| {
+                let channel = data.channels.iter().find(|c| c.id == content.channel_id)?;
+                Some(FeedEntry {
+                    channel_id: channel.id,
+                    channel_name: channel.name.clone(),
+                    content: content.clone(),
+                })
+            })
+            .collect();
+
+        entries.sort_by(|a, b| {
+            (b.content.published_at(), b.content.id).cmp(&(a.content.published_at(), a.content.id))
+        });
+        entries.truncate(limit.max(0) as usize);
+        Ok(entries)
+    }
+
+    async fn next_scheduled_publish(&self, now: i64) -> StorageResult<Option<i64>> {
+        Ok(self.data()?
+            .contents
+            .iter()
+            .filter(|c| c.visibility == Visibility::Public)
+            .filter_map(|c| c.publish_at)
+            .filter(|publish_at| *publish_at > now)
+            .min())
+    }
+
+    async fn find_identical_content(&self, content: &NewContent) -> StorageResult<Option<i64>> {
+        Ok(self.data()?
+            .contents
+            .iter()
+            .find(|c| {
+                c.channel_id == content.channel_id
+                    && c.created_at == content.created_at
+                    && c.name == content.name
+                    && c.art == content.art
+                    && c.midi_composition == content.midi_composition
+            })
+            .map(|c| c.id))
+    }
+
+    async fn find_content_by_hash(&self, channel_id: i64, content_hash: &str) -> StorageResult<Option<i64>> {
+        Ok(self.data()?
+            .contents
+            .iter()
+            .find(|c| c.channel_id == channel_id && c.origin.as_ref().is_some_and(|o| o.content_hash == content_hash))
+            .map(|c| c.id))
+    }
+}
+
+#[async_trait]
+impl ArchiveStore for MemoryStorage {
+    async fn import_archive(&self, channels: Vec<ChannelImport>, servers: Vec<String>, _keys_updated_at: i64) -> StorageResult<ArchiveImport> {
+        let mut data = self.data()?;
+        // Changes are made to a copy and swapped in at the end, like a transaction
+        let mut staged = data.clone();
+        let mut results = Vec::new();
+
+        for channel in channels {
+            let existing = staged.channels.iter().find(|c| c.name == channel.name);
+
+            let (channel_id, status) = match existing {
+                Some(existing) if existing.password_hash == channel.password_hash => (existing.id, ChannelImportStatus::Existing),
+                Some(_) => {
+                    results.push(ChannelImportResult { status: ChannelImportStatus::Conflict, contents_created: 0, contents_existing: 0 });
+                    continue;
+                }
+                None => {
+                    let id = next_id(staged.channels.iter().map(|c| c.id));
+                    staged.channels.push(ChannelRecord {
+                        id,
+                        name: channel.name.clone(),
+                        password_hash: channel.password_hash.clone(),
+                        created_at: channel.created_at,
+                    });
+                    if !channel.profile.is_empty() {
+                        staged.profiles.insert(id, channel.profile.clone());
+                    }
+                    if let Some(public_key) = &channel.public_key {
+                        staged.public_keys.insert(id, public_key.clone());
+                    }
+                    (id, ChannelImportStatus::Created)
+                }
+            };
+
+            let mut result = ChannelImportResult { status, contents_created: 0, contents_existing: 0 };
+            for content in channel.contents {
+                let identical = staged.contents.iter().any(|c| {
+                    c.channel_id == channel_id
+                        && c.created_at == content.created_at
+                        && c.name == content.name
+                        && c.art == content.art
+                        && c.midi_composition == content.midi_composition
+                });
+                if identical {
+                    result.contents_existing += 1;
+                    continue;
+                }
+
+                staged.contents.push(ContentRecord {
+                    id: next_id(staged.contents.iter().map(|c| c.id)),
+                    channel_id,
+                    name: content.name,
+                    art: content.art,
+                    midi_composition: content.midi_composition,
+                    fps: content.fps,
+                    created_at: content.created_at,
+                    visibility: content.visibility,
+                    publish_at: content.publish_at,
+                    share_token: content.share_token,
+                    signature: content.signature,
+                    public_key: content.public_key,
+                    origin: content.origin,
+                });
+                result.contents_created += 1;
+            }
+            results.push(result);
+        }
+
+        let servers_added = servers.into_iter().filter(|server_url| staged.servers.insert(server_url.clone())).count();
+
+        *data = staged;
+        Ok(ArchiveImport { channels: results, servers_added })
+    }
+}
+
+#[async_trait]
+impl ServerStore for MemoryStorage {
+    async fn list_servers(&self) -> StorageResult<Vec<String>> {
+        Ok(self.data()?.servers.iter().cloned().collect())
+    }
+
+    async fn add_server(&self, server_url: &str) -> StorageResult<bool> {
+        Ok(self.data()?.servers.insert(server_url.to_string()))
+    }
+}
+
+#[async_trait]
+impl MemberStore for MemoryStorage {
+    async fn create_user(&self, name: &str, password_hash: &str, created_at: i64) -> StorageResult<UserRecord> {
+        let mut data = self.data()?;
+
+        if data.users.iter().any(|u| u.name == name) {
+            return Err(StorageError::AlreadyExists);
+        }
+
+        let user = UserRecord {
+            id: next_id(data.users.iter().map(|u| u.id)),
+            name: name.to_string(),
+            password_hash: password_hash.to_string(),
+            created_at,
+        };
+        data.users.push(user.clone());
+        Ok(user)
+    }
+
+    async fn find_user_by_name(&self, name: &str) -> StorageResult<Option<UserRecord>> {
+        Ok(self.data()?.users.iter().find(|u| u.name == name).cloned())
+    }
+
+    async fn find_user_by_id(&self, id: i64) -> StorageResult<Option<UserRecord>> {
+        Ok(self.data()?.users.iter().find(|u| u.id == id).cloned())
+    }
+
+    async fn set_member(&self, channel_id: i64, user_id: i64, role: Role, joined_at: i64) -> StorageResult<()> {
+        let mut data = self.data()?;
+        let members = data.members.entry(channel_id).or_default();
+
+        match members.iter_mut().find(|(id, _, _)| *id == user_id) {
+            Some(member) => member.1 = role,
+            None => members.push((user_id, role, joined_at)),
+        }
+        Ok(())
+    }
+
+    async fn find_member_role(&self, channel_id: i64, user_id: i64) -> StorageResult<Option<Role>> {
+        Ok(self.data()?
+            .members
+            .get(&channel_id)
+            .and_then(|members| members.iter().find(|(id, _, _)| *id == user_id))
+            .map(|(_, role, _)| *role))
+    }
+
+    async fn list_members(&self, channel_id: i64) -> StorageResult<Vec<MemberRecord>> {
+        let data = self.data()?;
+
+        Ok(data
+            .members
+            .get(&channel_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|(user_id, role, joined_at)| {
+                let user = data.users.iter().find(|u| u.id == *user_id)?;
+                Some(MemberRecord {
+                    user_id: *user_id,
+                    user_name: user.name.clone(),
+                    role: *role,
+                    joined_at: *joined_at,
+                })
+            })
+            .collect())
+    }
+
+    async fn remove_member(&self, channel_id: i64, user_id: i64) -> StorageResult<bool> {
+        let mut data = self.data()?;
+        let Some(members) = data.members.get_mut(&channel_id) else {
+            return Ok(false);
+        };
+
+        let before = members.len();
+        members.retain(|(id, _, _)| *id != user_id);
+        Ok(members.len() < before)
+    }
+
+    async fn create_invite(&self, invite: &InviteRecord) -> StorageResult<()> {
+        self.data()?.invites.insert(invite.token.clone(), invite.clone());
+        Ok(())
+    }
+
+    async fn find_invite(&self, token: &str) -> StorageResult<Option<InviteRecord>> {
+        Ok(self.data()?.invites.get(token).cloned())
+    }
+
+    async fn take_invite(&self, token: &str) -> StorageResult<Option<InviteRecord>> {
+        Ok(self.data()?.invites.remove(token))
+    }
+}
+
+#[async_trait]
+impl AuditStore for MemoryStorage {
+    async fn record_auth_event(&self, event: &NewAuthEvent) -> StorageResult<()> {
+        let mut data = self.data()?;
+        let id = next_id(data.auth_events.iter().map(|e| e.id));
+
+        data.auth_events.push(AuthEventRecord {
+            id,
+            channel_name: event.channel_name.clone(),
+            kind: event.kind,
+            ip: event.ip.clone(),
+            user_name: event.user_name.clone(),
+            created_at: event.created_at,
+        });
+        Ok(())
+    }
+
+    async fn list_auth_events(&self, channel_name: &str, limit: i64) -> StorageResult<Vec<AuthEventRecord>> {
+        Ok(self.data()?
+            .auth_events
+            .iter()
+            .rev()
+            .filter(|e| e.channel_name == channel_name)
+            .take(limit.max(0) as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn login_failures(&self, channel_name: &str, user_name: Option<&str>, since: i64) -> StorageResult<LoginFailures> {
+        let data = self.data()?;
+        let same_login = |e: &&AuthEventRecord| match user_name {
+            Some(user_name) => e.user_name.as_deref() == Some(user_name),
+            None => e.channel_name == channel_name && e.user_name.is_none(),
+        };
+
+        let mut failures = LoginFailures::default();
+        let mut refused_since_failure = false;
+        for event in data.auth_events.iter().rev().filter(same_login) {
+            match event.kind {
+                AuthEventKind::LoginSuccess | AuthEventKind::Register => break,
+                AuthEventKind::LoginFailure if event.created_at >= since => {
+                    if failures.count == 0 {
+                        failures.refused = refused_since_failure;
+                    }
+                    failures.count += 1;
+                    failures.last_at = failures.last_at.max(Some(event.created_at));
+                }
+                AuthEventKind::LockedOut if failures.count == 0 => refused_since_failure = true,
+                _ => {}
+            }
+        }
+        Ok(failures)
+    }
+
+    async fn prune_auth_events(&self, before: i64) -> StorageResult<usize> {
+        let mut data = self.data()?;
+        let count = data.auth_events.len();
+        data.auth_events.retain(|e| e.created_at >= before);
+        Ok(count - data.auth_events.len())
+    }
+}
+
+#[async_trait]
+impl CommentStore for MemoryStorage {
+    async fn create_comment(&self, comment: &NewComment) -> StorageResult<CommentRecord> {
+        let mut data = self.data()?;
+
+        let comment = CommentRecord {
+            id: next_id(data.comments.iter().map(|c| c.id)),
+            content_id: comment.content_id,
+            author_channel_id: comment.author_channel_id,
+            author_user_id: comment.author_user_id,
+            author_name: comment.author_name.clone(),
+            body: comment.body.clone(),
+            created_at: comment.created_at,
+        };
+        data.comments.push(comment.clone());
+        Ok(comment)
+    }
+
+    async fn find_comment(&self, id: i64) -> StorageResult<Option<CommentRecord>> {
+        Ok(self.data()?.comments.iter().find(|c| c.id == id).cloned())
+    }
+
+    async fn list_comments(&self, content_id: i64, limit: i64) -> StorageResult<Vec<CommentRecord>> {
+        Ok(self.data()?
+            .comments
+            .iter()
+            .rev()
+            .filter(|c| c.content_id == content_id)
+            .take(limit.max(0) as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn delete_comment(&self, id: i64) -> StorageResult<bool> {
+        let mut data = self.data()?;
+        let count = data.comments.len();
+        data.comments.retain(|c| c.id != id);
+        Ok(data.comments.len() < count)
+    }
+}
+
+#[async_trait]
+impl MirrorStore for MemoryStorage {
+    async fn create_mirror(&self, server_url: &str, remote_channel: &str, created_at: i64) -> StorageResult<MirrorRecord> {
+        let mut data = self.data()?;
+
+        if data.mirrors.iter().any(|m| m.server_url == server_url && m.remote_channel == remote_channel) {
+            return Err(StorageError::AlreadyExists);
+        }
+
+        let mirror = MirrorRecord {
+            id: next_id(data.mirrors.iter().map(|m| m.id)),
+            server_url: server_url.to_string(),
+            remote_channel: remote_channel.to_string(),
+            channel_id: None,
+            last_synced_at: None,
+            last_error: None,
+            created_at,
+        };
+        data.mirrors.push(mirror.clone());
+        Ok(mirror)
+    }
+
+    async fn list_mirrors(&self) -> StorageResult<Vec<MirrorRecord>> {
+        Ok(self.data()?.mirrors.clone())
+    }
+
+    async fn delete_mirror(&self, id: i64) -> StorageResult<bool> {
+        let mut data = self.data()?;
+        let count = data.mirrors.len();
+        data.mirrors.retain(|m| m.id != id);
+        Ok(data.mirrors.len() < count)
+    }
+
+    async fn mark_mirror_synced(&self, id: i64, channel_id: i64, synced_at: i64) -> StorageResult<()> {
+        if let Some(mirror) = self.data()?.mirrors.iter_mut().find(|m| m.id == id) {
+            mirror.channel_id = Some(channel_id);
+            mirror.last_synced_at = Some(synced_at);
+            mirror.last_error = None;
+        }
+        Ok(())
+    }
+
+    async fn mark_mirror_failed(&self, id: i64, error: &str) -> StorageResult<()> {
+        if let Some(mirror) = self.data()?.mirrors.iter_mut().find(|m| m.id == id) {
+            mirror.last_error = Some(error.to_string());
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl WebhookStore for MemoryStorage {
+    async fn create_webhook(&self, webhook: &NewWebhook) -> StorageResult<WebhookRecord> {
+        let mut data = self.data()?;
+
+        let webhook = WebhookRecord {
+            id: next_id(data.webhooks.iter().map(|w| w.id)),
+            channel_id: webhook.channel_id,
+            url: webhook.url.clone(),
+            secret: webhook.secret.clone(),
+            events: webhook.events.clone(),
+            created_at: webhook.created_at,
+        };
+        data.webhooks.push(webhook.clone());
+        Ok(webhook)
+    }
+
+    async fn list_webhooks(&self, channel_id: i64) -> StorageResult<Vec<WebhookRecord>> {
+        Ok(self.data()?.webhooks.iter().filter(|w| w.channel_id == channel_id).cloned().collect())
+    }
+
+    async fn delete_webhook(&self, channel_id: i64, id: i64) -> StorageResult<bool> {
+        let mut data = self.data()?;
+        let count = data.webhooks.len();
+        data.webhooks.retain(|w| !(w.id == id && w.channel_id == channel_id));
+        if data.webhooks.len() == count {
+            return Ok(false);
+        }
+        data.deliveries.retain(|d| d.webhook_id != id);
+        Ok(true)
+    }
+
+    async fn create_delivery(&self, webhook_id: i64, event: WebhookEvent, created_at: i64) -> StorageResult<DeliveryRecord> {
+        let mut data = self.data()?;
+        let url = data.webhooks.iter()
+            .find(|w| w.id == webhook_id)
+            .map(|w| w.url.clone())
+            .ok_or_else(|| StorageError::Backend(format!("Unknown webhook {webhook_id}")))?;
+
+        let delivery = DeliveryRecord {
+            id: next_id(data.deliveries.iter().map(|d| d.id)),
+            webhook_id,
+            url,
+            event,
+            status: DeliveryStatus::Pending,
+            attempts: 0,
+            response_status: None,
+            error: None,
+            created_at,
+            updated_at: created_at,
+        };
+        data.deliveries.push(delivery.clone());
+        Ok(delivery)
+    }
+
+    async fn update_delivery(&self, id: i64, update: &DeliveryUpdate) -> StorageResult<()> {
+        let mut data = self.data()?;
+        if let Some(delivery) = data.deliveries.iter_mut().find(|d| d.id == id) {
+            delivery.status = update.status;
+            delivery.attempts = update.attempts;
+            delivery.response_status = update.response_status;
+            delivery.error = update.error.clone();
+            delivery.updated_at = update.updated_at;
+        }
+        Ok(())
+    }
+
+    async fn list_deliveries(&self, channel_id: i64, limit: i64) -> StorageResult<Vec<DeliveryRecord>> {
+        let data = self.data()?;
+        let webhook_ids: Vec<i64> = data.webhooks.iter().filter(|w| w.channel_id == channel_id).map(|w| w.id).collect();
+
+        Ok(data.deliveries
+            .iter()
+            .rev()
+            .filter(|d| webhook_ids.contains(&d.webhook_id))
+            .take(limit.max(0) as usize)
+            .cloned()
+            .collect())
+    }
+}
@@ -0,0 +1,430 @@
+#[cfg(test)]
+mod memory;
+mod sqlite;
+
+#[cfg(test)]
+pub use memory::MemoryStorage;
+pub use sqlite::SqliteStorage;
+
+use async_trait::async_trait;
+use std::fmt;
+use tama::api::{AuthEventKind, ChannelProfile, DeliveryStatus, Role, Visibility, WebhookEvent};
+
+#[derive(Debug, PartialEq)]
+pub enum StorageError {
+    AlreadyExists,
+    Backend(String),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::AlreadyExists => write!(f, "Record already exists"),
+            StorageError::Backend(e) => write!(f, "Storage error: {e}"),
+        }
+    }
+}
+
+pub type StorageResult<T> = Result<T, StorageError>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelRecord {
+    pub id: i64,
+    pub name: String,
+    pub password_hash: String,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContentRecord {
+    pub id: i64,
+    pub channel_id: i64,
+    pub name: String,
+    pub art: String,
+    pub midi_composition: String,
+    pub fps: f32,
+    pub created_at: i64,
+    pub visibility: Visibility,
+    pub publish_at: Option<i64>,
+    pub share_token: Option<String>,
+    /// Signature of `tama::signing::content_digest`, made with `public_key`'s private half
+    pub signature: Option<String>,
+    /// Channel key the signature was checked against at upload
+    pub public_key: Option<String>,
+    /// Where a mirrored content was copied from, `None` for uploads
+    pub origin: Option<ContentOrigin>,
+}
+
+/// Provenance of a content copied from a peer by a mirror
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentOrigin {
+    pub server_url: String,
+    /// Id of the content on `server_url`
+    pub content_id: i64,
+    /// Hex `tama::signing::content_digest`, mirrors skip contents they already hold
+    pub content_hash: String,
+}
+
+impl ContentRecord {
+    /// When the content went, or goes, live
+    pub fn published_at(&self) -> i64 {
+        self.publish_at.unwrap_or(self.created_at)
+    }
+
+    /// Whether anyone but the owner may see the content at `now`
+    pub fn is_visible_at(&self, now: i64) -> bool {
+        self.visibility != Visibility::Private && self.published_at() <= now
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NewContent {
+    pub channel_id: i64,
+    pub name: String,
+    pub art: String,
+    pub midi_composition: String,
+    pub fps: f32,
+    pub created_at: i64,
+    pub visibility: Visibility,
+    pub publish_at: Option<i64>,
+    pub share_token: Option<String>,
+    pub signature: Option<String>,
+    pub public_key: Option<String>,
+    pub origin: Option<ContentOrigin>,
+}
+
+/// Which contents a listing includes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Listing {
+    /// Everything, for the owning channel and for exports
+    All,
+    /// Public contents published at or before the given time
+    PublicAt(i64),
+}
+
+impl Listing {
+    pub fn includes(&self, content: &ContentRecord) -> bool {
+        match self {
+            Listing::All => true,
+            Listing::PublicAt(now) => content.visibility == Visibility::Public && content.published_at() <= *now,
+        }
+    }
+}
+
+/// Person acting on channels they are a member of, separate from channel logins
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserRecord {
+    pub id: i64,
+    pub name: String,
+    pub password_hash: String,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemberRecord {
+    pub user_id: i64,
+    pub user_name: String,
+    pub role: Role,
+    pub joined_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InviteRecord {
+    pub token: String,
+    pub channel_id: i64,
+    pub role: Role,
+    pub created_at: i64,
+    pub expires_at: i64,
+}
+
+/// Row of the auth audit log. Events are keyed by channel name rather than id, so that
+/// failed logins to channels that don't exist (yet) are recorded too.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthEventRecord {
+    pub id: i64,
+    pub channel_name: String,
+    pub kind: AuthEventKind,
+    pub ip: Option<String>,
+    pub user_name: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewAuthEvent {
+    pub channel_name: String,
+    pub kind: AuthEventKind,
+    pub ip: Option<String>,
+    pub user_name: Option<String>,
+    pub created_at: i64,
+}
+
+/// Failed logins counted towards a lockout
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LoginFailures {
+    pub count: usize,
+    pub last_at: Option<i64>,
+    /// A refused attempt was already recorded since the last failure
+    pub refused: bool,
+}
+
+/// Comment on a content, written by a channel login or by a member on behalf of a channel
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommentRecord {
+    pub id: i64,
+    pub content_id: i64,
+    pub author_channel_id: i64,
+    pub author_user_id: Option<i64>,
+    /// Member name for member logins, channel name otherwise
+    pub author_name: String,
+    pub body: String,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewComment {
+    pub content_id: i64,
+    pub author_channel_id: i64,
+    pub author_user_id: Option<i64>,
+    pub author_name: String,
+    pub body: String,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebhookRecord {
+    pub id: i64,
+    pub channel_id: i64,
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<WebhookEvent>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewWebhook {
+    pub channel_id: i64,
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<WebhookEvent>,
+    pub created_at: i64,
+}
+
+/// One event sent to one webhook, across all of its attempts
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeliveryRecord {
+    pub id: i64,
+    pub webhook_id: i64,
+    pub url: String,
+    pub event: WebhookEvent,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    pub response_status: Option<u16>,
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Outcome of the latest attempt of a delivery
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeliveryUpdate {
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    pub response_status: Option<u16>,
+    pub error: Option<String>,
+    pub updated_at: i64,
+}
+
+/// Remote channel the server keeps a local copy of
+#[derive(Debug, Clone, PartialEq)]
+pub struct MirrorRecord {
+    pub id: i64,
+    pub server_url: String,
+    /// Id or name of the channel on `server_url`
+    pub remote_channel: String,
+    /// Local `<name>@<host>` channel, `None` until the first successful sync
+    pub channel_id: Option<i64>,
+    pub last_synced_at: Option<i64>,
+    /// Why the latest sync failed, cleared by the next successful one
+    pub last_error: Option<String>,
+    pub created_at: i64,
+}
+
+/// A channel of an archive along with its contents. The contents' `channel_id` is
+/// ignored, they go to the imported channel.
+#[derive(Debug, Clone, Default)]
+pub struct ChannelImport {
+    pub name: String,
+    pub password_hash: String,
+    pub created_at: i64,
+    pub profile: ChannelProfile,
+    pub public_key: Option<String>,
+    pub contents: Vec<NewContent>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChannelImportStatus {
+    Created,
+    /// Same name and password hash as a local channel, contents were merged into it
+    Existing,
+    /// The name belongs to a local channel with another password, nothing was imported
+    Conflict,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelImportResult {
+    pub status: ChannelImportStatus,
+    pub contents_created: usize,
+    pub contents_existing: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchiveImport {
+    /// One per imported channel, in the same order
+    pub channels: Vec<ChannelImportResult>,
+    pub servers_added: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct FeedEntry {
+    pub channel_id: i64,
+    pub channel_name: String,
+    pub content: ContentRecord,
+}
+
+#[async_trait]
+pub trait ChannelStore: Send + Sync {
+    /// Fails with `AlreadyExists` if the name is taken
+    async fn create_channel(&self, name: &str, password_hash: &str, created_at: i64) -> StorageResult<ChannelRecord>;
+    async fn find_channel_by_id(&self, id: i64) -> StorageResult<Option<ChannelRecord>>;
+    async fn find_channel_by_name(&self, name: &str) -> StorageResult<Option<ChannelRecord>>;
+    async fn list_channels(&self) -> StorageResult<Vec<ChannelRecord>>;
+    /// Empty profile if the channel never set one
+    async fn find_profile(&self, channel_id: i64) -> StorageResult<ChannelProfile>;
+    async fn save_profile(&self, channel_id: i64, profile: &ChannelProfile) -> StorageResult<()>;
+    /// PEM public key uploads are signed with, if the channel registered one
+    async fn find_public_key(&self, channel_id: i64) -> StorageResult<Option<String>>;
+    /// Replaces the channel's key. Contents keep the key they were signed with.
+    async fn save_public_key(&self, channel_id: i64, public_key: &str, updated_at: i64) -> StorageResult<()>;
+
+    /// Numeric identifiers are channel ids, anything else is a channel name
+    async fn find_channel(&self, identifier: &str) -> StorageResult<Option<ChannelRecord>> {
+        match identifier.parse::<i64>() {
+            Ok(id) => self.find_channel_by_id(id).await,
+            Err(_) => self.find_channel_by_name(identifier).await,
+        }
+    }
+}
+
+#[async_trait]
+pub trait ContentStore: Send + Sync {
+    async fn create_content(&self, content: NewContent) -> StorageResult<ContentRecord>;
+    async fn find_content(&self, id: i64) -> StorageResult<Option<ContentRecord>>;
+    async fn find_content_by_share_token(&self, token: &str) -> StorageResult<Option<ContentRecord>>;
+    /// Contents of a channel in upload order
+    async fn list_channel_contents(&self, channel_id: i64, listing: Listing, limit: i64, offset: i64) -> StorageResult<Vec<ContentRecord>>;
+    /// Most recently published contents of a channel, newest first
+    async fn latest_channel_contents(&self, channel_id: i64, listing: Listing, limit: i64) -> StorageResult<Vec<ContentRecord>>;
+    /// Most recently published contents across all channels, newest first. Mirrored
+    /// contents are left out, their origin server already lists them.
+    async fn latest_contents(&self, listing: Listing, limit: i64) -> StorageResult<Vec<FeedEntry>>;
+    /// Earliest publish time after `now` of a scheduled public content, if any
+    async fn next_scheduled_publish(&self, now: i64) -> StorageResult<Option<i64>>;
+    /// Id of a content with the same channel, name, payload and creation time, if any
+    async fn find_identical_content(&self, content: &NewContent) -> StorageResult<Option<i64>>;
+    /// Id of a mirrored content of the channel with the given `ContentOrigin::content_hash`
+    async fn find_content_by_hash(&self, channel_id: i64, content_hash: &str) -> StorageResult<Option<i64>>;
+}
+
+#[async_trait]
+pub trait ServerStore: Send + Sync {
+    async fn list_servers(&self) -> StorageResult<Vec<String>>;
+    /// Returns false if the server was already known
+    async fn add_server(&self, server_url: &str) -> StorageResult<bool>;
+}
+
+#[async_trait]
+pub trait MemberStore: Send + Sync {
+    /// Fails with `AlreadyExists` if the name is taken
+    async fn create_user(&self, name: &str, password_hash: &str, created_at: i64) -> StorageResult<UserRecord>;
+    async fn find_user_by_name(&self, name: &str) -> StorageResult<Option<UserRecord>>;
+    async fn find_user_by_id(&self, id: i64) -> StorageResult<Option<UserRecord>>;
+    /// Adds the user to the channel, or changes their role if they already are a member
+    async fn set_member(&self, channel_id: i64, user_id: i64, role: Role, joined_at: i64) -> StorageResult<()>;
+    async fn find_member_role(&self, channel_id: i64, user_id: i64) -> StorageResult<Option<Role>>;
+    /// Members in the order they joined
+    async fn list_members(&self, channel_id: i64) -> StorageResult<Vec<MemberRecord>>;
+    /// Returns false if the user was not a member
+    async fn remove_member(&self, channel_id: i64, user_id: i64) -> StorageResult<bool>;
+    async fn create_invite(&self, invite: &InviteRecord) -> StorageResult<()>;
+    async fn find_invite(&self, token: &str) -> StorageResult<Option<InviteRecord>>;
+    /// Removes and returns the invite, so that each one is accepted at most once
+    async fn take_invite(&self, token: &str) -> StorageResult<Option<InviteRecord>>;
+}
+
+#[async_trait]
+pub trait AuditStore: Send + Sync {
+    async fn record_auth_event(&self, event: &NewAuthEvent) -> StorageResult<()>;
+    /// Most recent events first
+    async fn list_auth_events(&self, channel_name: &str, limit: i64) -> StorageResult<Vec<AuthEventRecord>>;
+    /// Login failures since `since` that came after the last successful login or registration.
+    /// Channel logins are counted by `channel_name`, member logins by `user_name`, whatever
+    /// channel they were made for.
+    async fn login_failures(&self, channel_name: &str, user_name: Option<&str>, since: i64) -> StorageResult<LoginFailures>;
+    /// Deletes events older than `before`, returns how many were deleted
+    async fn prune_auth_events(&self, before: i64) -> StorageResult<usize>;
+}
+
+#[async_trait]
+pub trait CommentStore: Send + Sync {
+    async fn create_comment(&self, comment: &NewComment) -> StorageResult<CommentRecord>;
+    async fn find_comment(&self, id: i64) -> StorageResult<Option<CommentRecord>>;
+    /// Most recent comments of a content first
+    async fn list_comments(&self, content_id: i64, limit: i64) -> StorageResult<Vec<CommentRecord>>;
+    /// Returns false if there was no such comment
+    async fn delete_comment(&self, id: i64) -> StorageResult<bool>;
+}
+
+#[async_trait]
+pub trait WebhookStore: Send + Sync {
+    async fn create_webhook(&self, webhook: &NewWebhook) -> StorageResult<WebhookRecord>;
+    /// Webhooks of a channel in the order they were added
+    async fn list_webhooks(&self, channel_id: i64) -> StorageResult<Vec<WebhookRecord>>;
+    /// Removes the webhook and its delivery log. Returns false if the channel has no such webhook.
+    async fn delete_webhook(&self, channel_id: i64, id: i64) -> StorageResult<bool>;
+    /// Starts a pending delivery with no attempts yet
+    async fn create_delivery(&self, webhook_id: i64, event: WebhookEvent, created_at: i64) -> StorageResult<DeliveryRecord>;
+    async fn update_delivery(&self, id: i64, update: &DeliveryUpdate) -> StorageResult<()>;
+    /// Most recent deliveries of all the channel's webhooks first
+    async fn list_deliveries(&self, channel_id: i64, limit: i64) -> StorageResult<Vec<DeliveryRecord>>;
+}
+
+#[async_trait]
+pub trait ArchiveStore: Send + Sync {
+    /// Imports channels, their contents and servers in a single transaction: if anything
+    /// fails, nothing is written. Contents identical to one already in the channel are skipped.
+    async fn import_archive(&self, channels: Vec<ChannelImport>, servers: Vec<String>, keys_updated_at: i64) -> StorageResult<ArchiveImport>;
+}
+
+#[async_trait]
+pub trait MirrorStore: Send + Sync {
+    /// Fails with `AlreadyExists` if the channel is already mirrored
+    async fn create_mirror(&self, server_url: &str, remote_channel: &str, created_at: i64) -> StorageResult<MirrorRecord>;
+    /// Mirrors in the order they were added
+    async fn list_mirrors(&self) -> StorageResult<Vec<MirrorRecord>>;
+    /// Stops syncing, contents mirrored so far are kept. Returns false if there was no such mirror.
+    async fn delete_mirror(&self, id: i64) -> StorageResult<bool>;
+    async fn mark_mirror_synced(&self, id: i64, channel_id: i64, synced_at: i64) -> StorageResult<()>;
+    async fn mark_mirror_failed(&self, id: i64, error: &str) -> StorageResult<()>;
+}
+
+/// Everything the server persists. Handlers only see this trait, so tests can swap in
+/// `MemoryStorage` for the SQLite-backed implementation.
+pub trait Storage:
+    ChannelStore + ContentStore + ServerStore + MemberStore + AuditStore + CommentStore + WebhookStore + MirrorStore + ArchiveStore
+{
+}
+
+impl<T> Storage for T where
+    T: ChannelStore + ContentStore + ServerStore + MemberStore + AuditStore + CommentStore + WebhookStore + MirrorStore + ArchiveStore
+{
+}
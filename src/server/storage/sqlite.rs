@@ -0,0 +1,1815 @@
+use async_trait::async_trait;
+use r2d2::Pool;
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use tama::api::{AuthEventKind, ChannelProfile, DeliveryStatus, Role, Visibility, WebhookEvent};
+
+use super::{
+    ArchiveImport, ArchiveStore, ChannelImport, ChannelImportResult, ChannelImportStatus, AuditStore, AuthEventRecord, ChannelRecord, ChannelStore, CommentRecord, CommentStore, ContentRecord,
+    ContentOrigin, ContentStore, DeliveryRecord, DeliveryUpdate, FeedEntry, InviteRecord, Listing, LoginFailures, MemberRecord, MemberStore,
+    MirrorRecord, MirrorStore, NewAuthEvent, NewComment, NewContent, NewWebhook, ServerStore, StorageError, StorageResult, UserRecord,
+    WebhookRecord, WebhookStore,
+};
+
+pub type DbPool = Pool<SqliteConnectionManager>;
+
+/// SQLite storage. Every query runs on tokio's blocking thread pool so that
+/// r2d2 and rusqlite never stall the async executor.
+#[derive(Clone)]
+pub struct SqliteStorage {
+    pool: DbPool,
+}
+
+impl From<rusqlite::Error> for StorageError {
+    fn from(err: rusqlite::Error) -> Self {
+        StorageError::Backend(err.to_string())
+    }
+}
+
+impl SqliteStorage {
+    pub fn open(db_path: &str) -> Result<Self, String> {
+        let manager = SqliteConnectionManager::file(db_path);
+        let pool = r2d2::Pool::builder()
+            .max_size(10)
+            .build(manager)
+            .map_err(|e| format!("Failed to create connection pool: {e}"))?;
+
+        Self::from_pool(pool)
+    }
+
+    /// Single-connection in-memory database, every pooled connection would otherwise
+    /// get its own empty database
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self, String> {
+        let manager = SqliteConnectionManager::memory();
+        let pool = r2d2::Pool::builder()
+            .max_size(1)
+            .build(manager)
+            .map_err(|e| format!("Failed to create connection pool: {e}"))?;
+
+        Self::from_pool(pool)
+    }
+
+    fn from_pool(pool: DbPool) -> Result<Self, String> {
+        // Get a connection from the pool to initialize the tables
+        let conn = pool.get()
+            .map_err(|e| format!("Failed to get connection from pool: {e}"))?;
+
+        create_tables(&conn)?;
+
+        // Drop the connection back to the pool
+        drop(conn);
+
+        Ok(Self { pool })
+    }
+
+    async fn with_conn<T, F>(&self, f: F) -> StorageResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> StorageResult<T> + Send + 'static,
+    {
+        let pool = self.pool.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let mut conn = pool.get()
+                .map_err(|e| StorageError::Backend(format!("Failed to get connection from pool: {e}")))?;
+            f(&mut conn)
+        })
+        .await
+        .map_err(|e| StorageError::Backend(format!("Storage task failed: {e}")))?
+    }
+}
+
+pub fn create_tables(conn: &Connection) -> Result<(), String> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS channels (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            password_hash TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create channels table: {e}"))?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS contents (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            channel_id INTEGER NOT NULL,
+            name TEXT NOT NULL DEFAULT '',
+            art TEXT NOT NULL,
+            midi_composition TEXT NOT NULL,
+            fps REAL NOT NULL,
+            created_at INTEGER NOT NULL,
+            FOREIGN KEY (channel_id) REFERENCES channels(id)
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create contents table: {e}"))?;
+
+    // Columns added after the first release, older databases get them on startup
+    add_column_if_missing(conn, "contents", "visibility", "TEXT NOT NULL DEFAULT 'public'")?;
+    add_column_if_missing(conn, "contents", "publish_at", "INTEGER")?;
+    add_column_if_missing(conn, "contents", "share_token", "TEXT")?;
+    add_column_if_missing(conn, "contents", "signature", "TEXT")?;
+    add_column_if_missing(conn, "contents", "public_key", "TEXT")?;
+    add_column_if_missing(conn, "contents", "origin_server", "TEXT")?;
+    add_column_if_missing(conn, "contents", "origin_id", "INTEGER")?;
+    add_column_if_missing(conn, "contents", "content_hash", "TEXT")?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS servers (
+            server_url TEXT PRIMARY KEY
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create servers table: {e}"))?;
+
+    // Separate table so databases created before profiles existed need no migration
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS channel_profiles (
+            channel_id INTEGER PRIMARY KEY,
+            display_name TEXT,
+            bio TEXT,
+            avatar TEXT,
+            links TEXT NOT NULL DEFAULT '[]',
+            FOREIGN KEY (channel_id) REFERENCES channels(id)
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create channel_profiles table: {e}"))?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS channel_keys (
+            channel_id INTEGER PRIMARY KEY,
+            public_key TEXT NOT NULL,
+            updated_at INTEGER NOT NULL,
+            FOREIGN KEY (channel_id) REFERENCES channels(id)
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create channel_keys table: {e}"))?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS users (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            password_hash TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create users table: {e}"))?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS channel_members (
+            channel_id INTEGER NOT NULL,
+            user_id INTEGER NOT NULL,
+            role TEXT NOT NULL,
+            joined_at INTEGER NOT NULL,
+            PRIMARY KEY (channel_id, user_id),
+            FOREIGN KEY (channel_id) REFERENCES channels(id),
+            FOREIGN KEY (user_id) REFERENCES users(id)
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create channel_members table: {e}"))?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS channel_invites (
+            token TEXT PRIMARY KEY,
+            channel_id INTEGER NOT NULL,
+            role TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            expires_at INTEGER NOT NULL,
+            FOREIGN KEY (channel_id) REFERENCES channels(id)
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create channel_invites table: {e}"))?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS auth_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            channel_name TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            ip TEXT,
+            user_name TEXT,
+            created_at INTEGER NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create auth_events table: {e}"))?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS comments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            content_id INTEGER NOT NULL,
+            author_channel_id INTEGER NOT NULL,
+            author_user_id INTEGER,
+            author_name TEXT NOT NULL,
+            body TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            FOREIGN KEY (content_id) REFERENCES contents(id),
+            FOREIGN KEY (author_channel_id) REFERENCES channels(id)
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create comments table: {e}"))?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS webhooks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            channel_id INTEGER NOT NULL,
+            url TEXT NOT NULL,
+            secret TEXT NOT NULL,
+            events TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            FOREIGN KEY (channel_id) REFERENCES channels(id)
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create webhooks table: {e}"))?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS webhook_deliveries (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            webhook_id INTEGER NOT NULL,
+            event TEXT NOT NULL,
+            status TEXT NOT NULL,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            response_status INTEGER,
+            error TEXT,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            FOREIGN KEY (webhook_id) REFERENCES webhooks(id)
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create webhook_deliveries table: {e}"))?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS mirrors (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            server_url TEXT NOT NULL,
+            remote_channel TEXT NOT NULL,
+            channel_id INTEGER,
+            last_synced_at INTEGER,
+            last_error TEXT,
+            created_at INTEGER NOT NULL,
+            UNIQUE (server_url, remote_channel),
+            FOREIGN KEY (channel_id) REFERENCES channels(id)
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create mirrors table: {e}"))?;
+
+    // Create indexes for better query performance
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_channels_name ON channels(name)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create index on channels.name: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_contents_channel_id ON contents(channel_id)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create index on contents.channel_id: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_contents_created_at ON contents(created_at)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create index on contents.created_at: {e}"))?;
+
+    conn.execute(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_contents_share_token ON contents(share_token)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create index on contents.share_token: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_contents_content_hash ON contents(channel_id, content_hash)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create index on contents.content_hash: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_auth_events_channel_name ON auth_events(channel_name, id)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create index on auth_events.channel_name: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_auth_events_user_name ON auth_events(user_name, id)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create index on auth_events.user_name: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_auth_events_created_at ON auth_events(created_at)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create index on auth_events.created_at: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_comments_content_id ON comments(content_id, id)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create index on comments.content_id: {e}"))?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_webhook_id ON webhook_deliveries(webhook_id, id)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create index on webhook_deliveries.webhook_id: {e}"))?;
+
+    Ok(())
+}
+
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<(), String> {
+    let exists = conn
+        .prepare(&format!("SELECT 1 FROM pragma_table_info('{table}') WHERE name = ?1"))
+        .and_then(|mut stmt| stmt.exists(params![column]))
+        .map_err(|e| format!("Failed to inspect {table} table: {e}"))?;
+
+    if !exists {
+        conn.execute(&format!("ALTER TABLE {table} ADD COLUMN {column} {definition}"), [])
+            .map_err(|e| format!("Failed to add {table}.{column}: {e}"))?;
+    }
+    Ok(())
+}
+
+const CHANNEL_COLUMNS: &str = "id, name, password_hash, created_at";
+const CONTENT_COLUMNS: &str = "co.id, co.channel_id, co.name, co.art, co.midi_composition, co.fps, co.created_at, co.visibility, \
+    co.publish_at, co.share_token, co.signature, co.public_key, co.origin_server, co.origin_id, co.content_hash";
+
+/// Filter matching `Listing`, `?1` is bound to the listing time
+const LISTED_CONTENTS: &str =
+    "(?1 IS NULL OR (co.visibility = 'public' AND COALESCE(co.publish_at, co.created_at) <= ?1))";
+
+fn listing_time(listing: Listing) -> Option<i64> {
+    match listing {
+        Listing::All => None,
+        Listing::PublicAt(now) => Some(now),
+    }
+}
+
+fn channel_from_row(row: &Row) -> rusqlite::Result<ChannelRecord> {
+    Ok(ChannelRecord {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        password_hash: row.get(2)?,
+        created_at: row.get(3)?,
+    })
+}
+
+fn role_from_column(row: &Row, index: usize) -> rusqlite::Result<Role> {
+    let value: String = row.get(index)?;
+    Role::parse(&value).ok_or_else(|| {
+        rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, format!("Unknown role '{value}'").into())
+    })
+}
+
+fn auth_event_kind_from_column(row: &Row, index: usize) -> rusqlite::Result<AuthEventKind> {
+    let value: String = row.get(index)?;
+    AuthEventKind::parse(&value).ok_or_else(|| {
+        rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, format!("Unknown auth event '{value}'").into())
+    })
+}
+
+fn webhook_event_from_column(row: &Row, index: usize) -> rusqlite::Result<WebhookEvent> {
+    let value: String = row.get(index)?;
+    WebhookEvent::parse(&value).ok_or_else(|| {
+        rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, format!("Unknown webhook event '{value}'").into())
+    })
+}
+
+fn delivery_status_from_column(row: &Row, index: usize) -> rusqlite::Result<DeliveryStatus> {
+    let value: String = row.get(index)?;
+    DeliveryStatus::parse(&value).ok_or_else(|| {
+        rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, format!("Unknown delivery status '{value}'").into())
+    })
+}
+
+/// Webhook events are stored comma separated, unknown ones are skipped
+fn events_to_column(events: &[WebhookEvent]) -> String {
+    events.iter().map(WebhookEvent::as_str).collect::<Vec<_>>().join(",")
+}
+
+fn events_from_column(value: &str) -> Vec<WebhookEvent> {
+    value.split(',').filter_map(WebhookEvent::parse).collect()
+}
+
+fn content_from_row(row: &Row) -> rusqlite::Result<ContentRecord> {
+    Ok(ContentRecord {
+        id: row.get(0)?,
+        channel_id: row.get(1)?,
+        name: row.get(2)?,
+        art: row.get(3)?,
+        midi_composition: row.get(4)?,
+        fps: row.get(5)?,
+        created_at: row.get(6)?,
+        visibility: Visibility::parse(&row.get::<_, String>(7)?).unwrap_or(Visibility::Private),
+        publish_at: row.get(8)?,
+        share_token: row.get(9)?,
+        signature: row.get(10)?,
+        public_key: row.get(11)?,
+        origin: origin_from_columns(row, 12)?,
+    })
+}
+
+/// Reads the three origin columns starting at `index`
+fn origin_from_columns(row: &Row, index: usize) -> rusqlite::Result<Option<ContentOrigin>> {
+    let server_url: Option<String> = row.get(index)?;
+    let content_id: Option<i64> = row.get(index + 1)?;
+    let content_hash: Option<String> = row.get(index + 2)?;
+
+    Ok(match (server_url, content_id, content_hash) {
+        (Some(server_url), Some(content_id), Some(content_hash)) => Some(ContentOrigin { server_url, content_id, content_hash }),
+        _ => None,
+    })
+}
+
+#[async_trait]
+impl ChannelStore for SqliteStorage {
+    async fn create_channel(&self, name: &str, password_hash: &str, created_at: i64) -> StorageResult<ChannelRecord> {
+        let name = name.to_string();
+        let password_hash = password_hash.to_string();
+
+        self.with_conn(move |db| {
+            let result = db.execute(
+                "INSERT INTO channels (name, password_hash, created_at) VALUES (?1, ?2, ?3)",
+                params![name, password_hash, created_at],
+            );
+
+            match result {
+                Ok(_) => Ok(ChannelRecord {
+                    id: db.last_insert_rowid(),
+                    name,
+                    password_hash,
+                    created_at,
+                }),
+                Err(rusqlite::Error::SqliteFailure(e, _))
+                    if e.code == rusqlite::ErrorCode::ConstraintViolation =>
+                {
+                    Err(StorageError::AlreadyExists)
+                }
+                Err(e) => Err(e.into()),
+            }
+        })
+        .await
+    }
+
+    async fn find_channel_by_id(&self, id: i64) -> StorageResult<Option<ChannelRecord>> {
+        self.with_conn(move |db| {
+            Ok(db
+                .query_row(
+                    &format!("SELECT {CHANNEL_COLUMNS} FROM channels WHERE id = ?1"),
+                    params![id],
+                    channel_from_row,
+                )
+                .optional()?)
+        })
+        .await
+    }
+
+    async fn find_channel_by_name(&self, name: &str) -> StorageResult<Option<ChannelRecord>> {
+        let name = name.to_string();
+
+        self.with_conn(move |db| {
+            Ok(db
+                .query_row(
+                    &format!("SELECT {CHANNEL_COLUMNS} FROM channels WHERE name = ?1"),
+                    params![name],
+                    channel_from_row,
+                )
+                .optional()?)
+        })
+        .await
+    }
+
+    async fn list_channels(&self) -> StorageResult<Vec<ChannelRecord>> {
+        self.with_conn(|db| {
+            let mut stmt = db.prepare(&format!("SELECT {CHANNEL_COLUMNS} FROM channels ORDER BY id"))?;
+            let channels = stmt
+                .query_map([], channel_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(channels)
+        })
+        .await
+    }
+
+    async fn find_profile(&self, channel_id: i64) -> StorageResult<ChannelProfile> {
+        self.with_conn(move |db| {
+            let profile = db
+                .query_row(
+                    "SELECT display_name, bio, avatar, links FROM channel_profiles WHERE channel_id = ?1",
+                    params![channel_id],
+                    |row| {
+                        let links: String = row.get(3)?;
+                        Ok(ChannelProfile {
+                            display_name: row.get(0)?,
+                            bio: row.get(1)?,
+                            avatar: row.get(2)?,
+                            links: serde_json::from_str(&links).unwrap_or_default(),
+                        })
+                    },
+                )
+                .optional()?;
+            Ok(profile.unwrap_or_default())
+        })
+        .await
+    }
+
+    async fn save_profile(&self, channel_id: i64, profile: &ChannelProfile) -> StorageResult<()> {
+        let profile = profile.clone();
+        self.with_conn(move |db| upsert_profile(db, channel_id, &profile)).await
+    }
+
+    async fn find_public_key(&self, channel_id: i64) -> StorageResult<Option<String>> {
+        self.with_conn(move |db| {
+            Ok(db
+                .query_row(
+                    "SELECT public_key FROM channel_keys WHERE channel_id = ?1",
+                    params![channel_id],
+                    |row| row.get(0),
+                )
+                .optional()?)
+        })
+        .await
+    }
+
+    async fn save_public_key(&self, channel_id: i64, public_key: &str, updated_at: i64) -> StorageResult<()> {
+        let public_key = public_key.to_string();
+        self.with_conn(move |db| upsert_public_key(db, channel_id, &public_key, updated_at)).await
+    }
+}
+
+fn upsert_profile(db: &Connection, channel_id: i64, profile: &ChannelProfile) -> StorageResult<()> {
+    let links = serde_json::to_string(&profile.links)
+        .map_err(|e| StorageError::Backend(format!("Failed to serialize links: {e}")))?;
+
+    db.execute(
+        "INSERT INTO channel_profiles (channel_id, display_name, bio, avatar, links)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(channel_id) DO UPDATE SET
+            display_name = excluded.display_name,
+            bio = excluded.bio,
+            avatar = excluded.avatar,
+            links = excluded.links",
+        params![channel_id, profile.display_name, profile.bio, profile.avatar, links],
+    )?;
+    Ok(())
+}
+
+fn upsert_public_key(db: &Connection, channel_id: i64, public_key: &str, updated_at: i64) -> StorageResult<()> {
+    db.execute(
+        "INSERT INTO channel_keys (channel_id, public_key, updated_at)
+         VALUES (?1, ?2, ?3)
+         ON CONFLICT(channel_id) DO UPDATE SET
+            public_key = excluded.public_key,
+            updated_at = excluded.updated_at",
+        params![channel_id, public_key, updated_at],
+    )?;
+    Ok(())
+}
+
+/// Inserts the content, returning its id
+fn insert_content(db: &Connection, content: &NewContent) -> rusqlite::Result<i64> {
+    db.execute(
+        "INSERT INTO contents (channel_id, name, art, midi_composition, fps, created_at, visibility, publish_at, share_token,
+                               signature, public_key, origin_server, origin_id, content_hash)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+        params![
+            content.channel_id,
+            content.name,
+            content.art,
+            content.midi_composition,
+            content.fps,
+            content.created_at,
+            content.visibility.as_str(),
+            content.publish_at,
+            content.share_token,
+            content.signature,
+            content.public_key,
+            content.origin.as_ref().map(|origin| &origin.server_url),
+            content.origin.as_ref().map(|origin| origin.content_id),
+            content.origin.as_ref().map(|origin| &origin.content_hash)
+        ],
+    )?;
+    Ok(db.last_insert_rowid())
+}
+
+fn identical_content_id(db: &Connection, content: &NewContent) -> rusqlite::Result<Option<i64>> {
+    db.query_row(
+        "SELECT id FROM contents
+         WHERE channel_id = ?1 AND created_at = ?2 AND name = ?3
+           AND art = ?4 AND midi_composition = ?5",
+        params![
+            content.channel_id,
+            content.created_at,
+            content.name,
+            content.art,
+            content.midi_composition
+        ],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+#[async_trait]
+impl ContentStore for SqliteStorage {
+    async fn create_content(&self, content: NewContent) -> StorageResult<ContentRecord> {
+        self.with_conn(move |db| {
+            let id = insert_content(db, &content)?;
+
+            Ok(ContentRecord {
+                id,
+                channel_id: content.channel_id,
+                name: content.name,
+                art: content.art,
+                midi_composition: content.midi_composition,
+                fps: content.fps,
+                created_at: content.created_at,
+                visibility: content.visibility,
+                publish_at: content.publish_at,
+                share_token: content.share_token,
+                signature: content.signature,
+                public_key: content.public_key,
+                origin: content.origin,
+            })
+        })
+        .await
+    }
+
+    async fn find_content(&self, id: i64) -> StorageResult<Option<ContentRecord>> {
+        self.with_conn(move |db| {
+            Ok(db
+                .query_row(
+                    &format!("SELECT {CONTENT_COLUMNS} FROM contents co WHERE co.id = ?1"),
+                    params![id],
+                    content_from_row,
+                )
+                .optional()?)
+        })
+        .await
+    }
+
+    async fn find_content_by_share_token(&self, token: &str) -> StorageResult<Option<ContentRecord>> {
+        let token = token.to_string();
+
+        self.with_conn(move |db| {
+            Ok(db
+                .query_row(
+                    &format!("SELECT {CONTENT_COLUMNS} FROM contents co WHERE co.share_token = ?1"),
+                    params![token],
+                    content_from_row,
+                )
+                .optional()?)
+        })
+        .await
+    }
+
+    async fn list_channel_contents(&self, channel_id: i64, listing: Listing, limit: i64, offset: i64) -> StorageResult<Vec<ContentRecord>> {
+        self.with_conn(move |db| {
+            let mut stmt = db.prepare(&format!(
+                "SELECT {CONTENT_COLUMNS}
+                 FROM contents co
+                 WHERE co.channel_id = ?2 AND {LISTED_CONTENTS}
+                 ORDER BY co.id
+                 LIMIT ?3 OFFSET ?4"
+            ))?;
+            let contents = stmt
+                .query_map(params![listing_time(listing), channel_id, limit, offset], content_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(contents)
+        })
+        .await
+    }
+
+    async fn latest_channel_contents(&self, channel_id: i64, listing: Listing, limit: i64) -> StorageResult<Vec<ContentRecord>> {
+        self.with_conn(move |db| {
+            let mut stmt = db.prepare(&format!(
+                "SELECT {CONTENT_COLUMNS}
+                 FROM contents co
+                 WHERE co.channel_id = ?2 AND {LISTED_CONTENTS}
+                 ORDER BY COALESCE(co.publish_at, co.created_at) DESC, co.id DESC
+                 LIMIT ?3"
+            ))?;
+            let contents = stmt
+                .query_map(params![listing_time(listing), channel_id, limit], content_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(contents)
+        })
+        .await
+    }
+
+    async fn latest_contents(&self, listing: Listing, limit: i64) -> StorageResult<Vec<FeedEntry>> {
+        self.with_conn(move |db| {
+            let mut stmt = db.prepare(&format!(
+                "SELECT {CONTENT_COLUMNS}, c.name
+                 FROM channels c
+                 JOIN contents co ON c.id = co.channel_id
+                 WHERE {LISTED_CONTENTS} AND co.origin_server IS NULL
+                 ORDER BY COALESCE(co.publish_at, co.created_at) DESC, co.id DESC
+                 LIMIT ?2"
+            ))?;
+            let entries = stmt
+                .query_map(params![listing_time(listing), limit], |row| {
+                    let content = content_from_row(row)?;
+                    Ok(FeedEntry {
+                        channel_id: content.channel_id,
+                        channel_name: row.get(15)?,
+                        content,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(entries)
+        })
+        .await
+    }
+
+    async fn next_scheduled_publish(&self, now: i64) -> StorageResult<Option<i64>> {
+        self.with_conn(move |db| {
+            Ok(db.query_row(
+                "SELECT MIN(publish_at) FROM contents WHERE visibility = 'public' AND publish_at > ?1",
+                params![now],
+                |row| row.get(0),
+            )?)
+        })
+        .await
+    }
+
+    async fn find_identical_content(&self, content: &NewContent) -> StorageResult<Option<i64>> {
+        let content = content.clone();
+        self.with_conn(move |db| Ok(identical_content_id(db, &content)?)).await
+    }
+
+    async fn find_content_by_hash(&self, channel_id: i64, content_hash: &str) -> StorageResult<Option<i64>> {
+        let content_hash = content_hash.to_string();
+
+        self.with_conn(move |db| {
+            Ok(db
+                .query_row(
+                    "SELECT id FROM contents WHERE channel_id = ?1 AND content_hash = ?2 LIMIT 1",
+                    params![channel_id, content_hash],
+                    |row| row.get(0),
+                )
+                .optional()?)
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl ArchiveStore for SqliteStorage {
+    async fn import_archive(&self, channels: Vec<ChannelImport>, servers: Vec<String>, keys_updated_at: i64) -> StorageResult<ArchiveImport> {
+        self.with_conn(move |db| {
+            let tx = db.transaction()?;
+            let mut results = Vec::new();
+
+            for channel in channels {
+                let existing: Option<(i64, String)> = tx
+                    .query_row(
+                        "SELECT id, password_hash FROM channels WHERE name = ?1",
+                        params![channel.name],
+                        |row| Ok((row.get(0)?, row.get(1)?)),
+                    )
+                    .optional()?;
+
+                let (channel_id, status) = match existing {
+                    Some((id, password_hash)) if password_hash == channel.password_hash => (id, ChannelImportStatus::Existing),
+                    Some(_) => {
+                        results.push(ChannelImportResult { status: ChannelImportStatus::Conflict, contents_created: 0, contents_existing: 0 });
+                        continue;
+                    }
+                    None => {
+                        tx.execute(
+                            "INSERT INTO channels (name, password_hash, created_at) VALUES (?1, ?2, ?3)",
+                            params![channel.name, channel.password_hash, channel.created_at],
+                        )?;
+                        let id = tx.last_insert_rowid();
+
+                        if !channel.profile.is_empty() {
+                            upsert_profile(&tx, id, &channel.profile)?;
+                        }
+                        if let Some(public_key) = &channel.public_key {
+                            upsert_public_key(&tx, id, public_key, keys_updated_at)?;
+                        }
+                        (id, ChannelImportStatus::Created)
+                    }
+                };
+
+                let mut result = ChannelImportResult { status, contents_created: 0, contents_existing: 0 };
+                for mut content in channel.contents {
+                    content.channel_id = channel_id;
+                    if identical_content_id(&tx, &content)?.is_some() {
+                        result.contents_existing += 1;
+                    } else {
+                        insert_content(&tx, &content)?;
+                        result.contents_created += 1;
+                    }
+                }
+                results.push(result);
+            }
+
+            let mut servers_added = 0;
+            for server_url in servers {
+                servers_added += tx.execute(
+                    "INSERT OR IGNORE INTO servers (server_url) VALUES (?1)",
+                    params![server_url],
+                )?;
+            }
+
+            tx.commit()?;
+            Ok(ArchiveImport { channels: results, servers_added })
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl ServerStore for SqliteStorage {
+    async fn list_servers(&self) -> StorageResult<Vec<String>> {
+        self.with_conn(|db| {
+            let mut stmt = db.prepare("SELECT server_url FROM servers")?;
+            let servers = stmt
+                .query_map([], |row| row.get(0))?
+                .collect::<Result<Vec<String>, _>>()?;
+            Ok(servers)
+        })
+        .await
+    }
+
+    async fn add_server(&self, server_url: &str) -> StorageResult<bool> {
+        let server_url = server_url.to_string();
+
+        self.with_conn(move |db| {
+            let inserted = db.execute(
+                "INSERT OR IGNORE INTO servers (server_url) VALUES (?1)",
+                params![server_url],
+            )?;
+            Ok(inserted > 0)
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl MemberStore for SqliteStorage {
+    async fn create_user(&self, name: &str, password_hash: &str, created_at: i64) -> StorageResult<UserRecord> {
+        let name = name.to_string();
+        let password_hash = password_hash.to_string();
+
+        self.with_conn(move |db| {
+            let result = db.execute(
+                "INSERT INTO users (name, password_hash, created_at) VALUES (?1, ?2, ?3)",
+                params![name, password_hash, created_at],
+            );
+
+            match result {
+                Ok(_) => Ok(UserRecord {
+                    id: db.last_insert_rowid(),
+                    name,
+                    password_hash,
+                    created_at,
+                }),
+                Err(rusqlite::Error::SqliteFailure(e, _))
+                    if e.code == rusqlite::ErrorCode::ConstraintViolation =>
+                {
+                    Err(StorageError::AlreadyExists)
+                }
+                Err(e) => Err(e.into()),
+            }
+        })
+        .await
+    }
+
+    async fn find_user_by_name(&self, name: &str) -> StorageResult<Option<UserRecord>> {
+        let name = name.to_string();
+
+        self.with_conn(move |db| {
+            Ok(db
+                .query_row(
+                    "SELECT id, name, password_hash, created_at FROM users WHERE name = ?1",
+                    params![name],
+                    |row| Ok(UserRecord {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                        password_hash: row.get(2)?,
+                        created_at: row.get(3)?,
+                    }),
+                )
+                .optional()?)
+        })
+        .await
+    }
+
+    async fn find_user_by_id(&self, id: i64) -> StorageResult<Option<UserRecord>> {
+        self.with_conn(move |db| {
+            Ok(db
+                .query_row(
+                    "SELECT id, name, password_hash, created_at FROM users WHERE id = ?1",
+                    params![id],
+                    |row| Ok(UserRecord {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                        password_hash: row.get(2)?,
+                        created_at: row.get(3)?,
+                    }),
+                )
+                .optional()?)
+        })
+        .await
+    }
+
+    async fn set_member(&self, channel_id: i64, user_id: i64, role: Role, joined_at: i64) -> StorageResult<()> {
+        self.with_conn(move |db| {
+            db.execute(
+                "INSERT INTO channel_members (channel_id, user_id, role, joined_at)
+                 VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(channel_id, user_id) DO UPDATE SET role = excluded.role",
+                params![channel_id, user_id, role.as_str(), joined_at],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn find_member_role(&self, channel_id: i64, user_id: i64) -> StorageResult<Option<Role>> {
+        self.with_conn(move |db| {
+            Ok(db
+                .query_row(
+                    "SELECT role FROM channel_members WHERE channel_id = ?1 AND user_id = ?2",
+                    params![channel_id, user_id],
+                    |row| role_from_column(row, 0),
+                )
+                .optional()?)
+        })
+        .await
+    }
+
+    async fn list_members(&self, channel_id: i64) -> StorageResult<Vec<MemberRecord>> {
+        self.with_conn(move |db| {
+            let mut stmt = db.prepare(
+                "SELECT u.id, u.name, m.role, m.joined_at
+                 FROM channel_members m
+                 JOIN users u ON u.id = m.user_id
+                 WHERE m.channel_id = ?1
+                 ORDER BY m.joined_at, u.id",
+            )?;
+            let members = stmt
+                .query_map(params![channel_id], |row| {
+                    Ok(MemberRecord {
+                        user_id: row.get(0)?,
+                        user_name: row.get(1)?,
+                        role: role_from_column(row, 2)?,
+                        joined_at: row.get(3)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(members)
+        })
+        .await
+    }
+
+    async fn remove_member(&self, channel_id: i64, user_id: i64) -> StorageResult<bool> {
+        self.with_conn(move |db| {
+            let removed = db.execute(
+                "DELETE FROM channel_members WHERE channel_id = ?1 AND user_id = ?2",
+                params![channel_id, user_id],
+            )?;
+            Ok(removed > 0)
+        })
+        .await
+    }
+
+    async fn create_invite(&self, invite: &InviteRecord) -> StorageResult<()> {
+        let invite = invite.clone();
+
+        self.with_conn(move |db| {
+            db.execute(
+                "INSERT INTO channel_invites (token, channel_id, role, created_at, expires_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![invite.token, invite.channel_id, invite.role.as_str(), invite.created_at, invite.expires_at],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn find_invite(&self, token: &str) -> StorageResult<Option<InviteRecord>> {
+        let token = token.to_string();
+
+        self.with_conn(move |db| {
+            Ok(db
+                .query_row(
+                    "SELECT token, channel_id, role, created_at, expires_at FROM channel_invites WHERE token = ?1",
+                    params![token],
+                    |row| Ok(InviteRecord {
+                        token: row.get(0)?,
+                        channel_id: row.get(1)?,
+                        role: role_from_column(row, 2)?,
+                        created_at: row.get(3)?,
+                        expires_at: row.get(4)?,
+                    }),
+                )
+                .optional()?)
+        })
+        .await
+    }
+
+    async fn take_invite(&self, token: &str) -> StorageResult<Option<InviteRecord>> {
+        let token = token.to_string();
+
+        self.with_conn(move |db| {
+            Ok(db
+                .query_row(
+                    "DELETE FROM channel_invites WHERE token = ?1
+                     RETURNING token, channel_id, role, created_at, expires_at",
+                    params![token],
+                    |row| Ok(InviteRecord {
+                        token: row.get(0)?,
+                        channel_id: row.get(1)?,
+                        role: role_from_column(row, 2)?,
+                        created_at: row.get(3)?,
+                        expires_at: row.get(4)?,
+                    }),
+                )
+                .optional()?)
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl AuditStore for SqliteStorage {
+    async fn record_auth_event(&self, event: &NewAuthEvent) -> StorageResult<()> {
+        let event = event.clone();
+
+        self.with_conn(move |db| {
+            db.execute(
+                "INSERT INTO auth_events (channel_name, kind, ip, user_name, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![event.channel_name, event.kind.as_str(), event.ip, event.user_name, event.created_at],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn list_auth_events(&self, channel_name: &str, limit: i64) -> StorageResult<Vec<AuthEventRecord>> {
+        let channel_name = channel_name.to_string();
+
+        self.with_conn(move |db| {
+            let mut stmt = db.prepare(
+                "SELECT id, channel_name, kind, ip, user_name, created_at
+                 FROM auth_events
+                 WHERE channel_name = ?1
+                 ORDER BY id DESC
+                 LIMIT ?2",
+            )?;
+            let events = stmt
+                .query_map(params![channel_name, limit], |row| {
+                    Ok(AuthEventRecord {
+                        id: row.get(0)?,
+                        channel_name: row.get(1)?,
+                        kind: auth_event_kind_from_column(row, 2)?,
+                        ip: row.get(3)?,
+                        user_name: row.get(4)?,
+                        created_at: row.get(5)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(events)
+        })
+        .await
+    }
+
+    async fn login_failures(&self, channel_name: &str, user_name: Option<&str>, since: i64) -> StorageResult<LoginFailures> {
+        let channel_name = channel_name.to_string();
+        let user_name = user_name.map(str::to_string);
+
+        self.with_conn(move |db| {
+            let same_login = match user_name {
+                Some(_) => "user_name = ?2",
+                None => "channel_name = ?1 AND user_name IS NULL",
+            };
+            let (count, last_at, last_id): (i64, Option<i64>, Option<i64>) = db.query_row(
+                &format!(
+                    "SELECT COUNT(*), MAX(created_at), MAX(id)
+                     FROM auth_events
+                     WHERE {same_login} AND kind = ?3 AND created_at >= ?4
+                       AND id > COALESCE(
+                           (SELECT MAX(id) FROM auth_events WHERE {same_login} AND kind IN (?5, ?6)),
+                           0
+                       )"
+                ),
+                params![
+                    channel_name,
+                    user_name,
+                    AuthEventKind::LoginFailure.as_str(),
+                    since,
+                    AuthEventKind::LoginSuccess.as_str(),
+                    AuthEventKind::Register.as_str(),
+                ],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )?;
+
+            let refused = match last_id {
+                Some(last_id) => db.query_row(
+                    &format!("SELECT EXISTS(SELECT 1 FROM auth_events WHERE {same_login} AND kind = ?3 AND id > ?4)"),
+                    params![channel_name, user_name, AuthEventKind::LockedOut.as_str(), last_id],
+                    |row| row.get(0),
+                )?,
+                None => false,
+            };
+            Ok(LoginFailures { count: count as usize, last_at, refused })
+        })
+        .await
+    }
+
+    async fn prune_auth_events(&self, before: i64) -> StorageResult<usize> {
+        self.with_conn(move |db| {
+            Ok(db.execute("DELETE FROM auth_events WHERE created_at < ?1", params![before])?)
+        })
+        .await
+    }
+}
+
+const COMMENT_COLUMNS: &str = "id, content_id, author_channel_id, author_user_id, author_name, body, created_at";
+
+fn comment_from_row(row: &Row) -> rusqlite::Result<CommentRecord> {
+    Ok(CommentRecord {
+        id: row.get(0)?,
+        content_id: row.get(1)?,
+        author_channel_id: row.get(2)?,
+        author_user_id: row.get(3)?,
+        author_name: row.get(4)?,
+        body: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+#[async_trait]
+impl CommentStore for SqliteStorage {
+    async fn create_comment(&self, comment: &NewComment) -> StorageResult<CommentRecord> {
+        let comment = comment.clone();
+
+        self.with_conn(move |db| {
+            db.execute(
+                "INSERT INTO comments (content_id, author_channel_id, author_user_id, author_name, body, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    comment.content_id,
+                    comment.author_channel_id,
+                    comment.author_user_id,
+                    comment.author_name,
+                    comment.body,
+                    comment.created_at,
+                ],
+            )?;
+
+            Ok(CommentRecord {
+                id: db.last_insert_rowid(),
+                content_id: comment.content_id,
+                author_channel_id: comment.author_channel_id,
+                author_user_id: comment.author_user_id,
+                author_name: comment.author_name,
+                body: comment.body,
+                created_at: comment.created_at,
+            })
+        })
+        .await
+    }
+
+    async fn find_comment(&self, id: i64) -> StorageResult<Option<CommentRecord>> {
+        self.with_conn(move |db| {
+            Ok(db
+                .query_row(
+                    &format!("SELECT {COMMENT_COLUMNS} FROM comments WHERE id = ?1"),
+                    params![id],
+                    comment_from_row,
+                )
+                .optional()?)
+        })
+        .await
+    }
+
+    async fn list_comments(&self, content_id: i64, limit: i64) -> StorageResult<Vec<CommentRecord>> {
+        self.with_conn(move |db| {
+            let mut stmt = db.prepare(&format!(
+                "SELECT {COMMENT_COLUMNS} FROM comments
+                 WHERE content_id = ?1
+                 ORDER BY id DESC
+                 LIMIT ?2"
+            ))?;
+            let comments = stmt
+                .query_map(params![content_id, limit], comment_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(comments)
+        })
+        .await
+    }
+
+    async fn delete_comment(&self, id: i64) -> StorageResult<bool> {
+        self.with_conn(move |db| {
+            let removed = db.execute("DELETE FROM comments WHERE id = ?1", params![id])?;
+            Ok(removed > 0)
+        })
+        .await
+    }
+}
+
+const DELIVERY_COLUMNS: &str = "d.id, d.webhook_id, w.url, d.event, d.status, d.attempts, d.response_status, d.error, d.created_at, d.updated_at";
+
+fn delivery_from_row(row: &Row) -> rusqlite::Result<DeliveryRecord> {
+    Ok(DeliveryRecord {
+        id: row.get(0)?,
+        webhook_id: row.get(1)?,
+        url: row.get(2)?,
+        event: webhook_event_from_column(row, 3)?,
+        status: delivery_status_from_column(row, 4)?,
+        attempts: row.get(5)?,
+        response_status: row.get(6)?,
+        error: row.get(7)?,
+        created_at: row.get(8)?,
+        updated_at: row.get(9)?,
+    })
+}
+
+#[async_trait]
+impl WebhookStore for SqliteStorage {
+    async fn create_webhook(&self, webhook: &NewWebhook) -> StorageResult<WebhookRecord> {
+        let webhook = webhook.clone();
+
+        self.with_conn(move |db| {
+            db.execute(
+                "INSERT INTO webhooks (channel_id, url, secret, events, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![webhook.channel_id, webhook.url, webhook.secret, events_to_column(&webhook.events), webhook.created_at],
+            )?;
+
+            Ok(WebhookRecord {
+                id: db.last_insert_rowid(),
+                channel_id: webhook.channel_id,
+                url: webhook.url,
+                secret: webhook.secret,
+                events: webhook.events,
+                created_at: webhook.created_at,
+            })
+        })
+        .await
+    }
+
+    async fn list_webhooks(&self, channel_id: i64) -> StorageResult<Vec<WebhookRecord>> {
+        self.with_conn(move |db| {
+            let mut stmt = db.prepare(
+                "SELECT id, channel_id, url, secret, events, created_at FROM webhooks WHERE channel_id = ?1 ORDER BY id",
+            )?;
+            let webhooks = stmt
+                .query_map(params![channel_id], |row| {
+                    let events: String = row.get(4)?;
+                    Ok(WebhookRecord {
+                        id: row.get(0)?,
+                        channel_id: row.get(1)?,
+                        url: row.get(2)?,
+                        secret: row.get(3)?,
+                        events: events_from_column(&events),
+                        created_at: row.get(5)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(webhooks)
+        })
+        .await
+    }
+
+    async fn delete_webhook(&self, channel_id: i64, id: i64) -> StorageResult<bool> {
+        self.with_conn(move |db| {
+            let tx = db.transaction()?;
+            tx.execute(
+                "DELETE FROM webhook_deliveries
+                 WHERE webhook_id IN (SELECT id FROM webhooks WHERE id = ?1 AND channel_id = ?2)",
+                params![id, channel_id],
+            )?;
+            let removed = tx.execute("DELETE FROM webhooks WHERE id = ?1 AND channel_id = ?2", params![id, channel_id])?;
+            tx.commit()?;
+            Ok(removed > 0)
+        })
+        .await
+    }
+
+    async fn create_delivery(&self, webhook_id: i64, event: WebhookEvent, created_at: i64) -> StorageResult<DeliveryRecord> {
+        self.with_conn(move |db| {
+            db.execute(
+                "INSERT INTO webhook_deliveries (webhook_id, event, status, attempts, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, 0, ?4, ?4)",
+                params![webhook_id, event.as_str(), DeliveryStatus::Pending.as_str(), created_at],
+            )?;
+
+            Ok(db.query_row(
+                &format!("SELECT {DELIVERY_COLUMNS} FROM webhook_deliveries d JOIN webhooks w ON w.id = d.webhook_id WHERE d.id = ?1"),
+                params![db.last_insert_rowid()],
+                delivery_from_row,
+            )?)
+        })
+        .await
+    }
+
+    async fn update_delivery(&self, id: i64, update: &DeliveryUpdate) -> StorageResult<()> {
+        let update = update.clone();
+
+        self.with_conn(move |db| {
+            db.execute(
+                "UPDATE webhook_deliveries
+                 SET status = ?2, attempts = ?3, response_status = ?4, error = ?5, updated_at = ?6
+                 WHERE id = ?1",
+                params![id, update.status.as_str(), update.attempts, update.response_status, update.error, update.updated_at],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn list_deliveries(&self, channel_id: i64, limit: i64) -> StorageResult<Vec<DeliveryRecord>> {
+        self.with_conn(move |db| {
+            let mut stmt = db.prepare(&format!(
+                "SELECT {DELIVERY_COLUMNS}
+                 FROM webhook_deliveries d
+                 JOIN webhooks w ON w.id = d.webhook_id
+                 WHERE w.channel_id = ?1
+                 ORDER BY d.id DESC
+                 LIMIT ?2"
+            ))?;
+            let deliveries = stmt
+                .query_map(params![channel_id, limit], delivery_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(deliveries)
+        })
+        .await
+    }
+}
+
+const MIRROR_COLUMNS: &str = "id, server_url, remote_channel, channel_id, last_synced_at, last_error, created_at";
+
+fn mirror_from_row(row: &Row) -> rusqlite::Result<MirrorRecord> {
+    Ok(MirrorRecord {
+        id: row.get(0)?,
+        server_url: row.get(1)?,
+        remote_channel: row.get(2)?,
+        channel_id: row.get(3)?,
+        last_synced_at: row.get(4)?,
+        last_error: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+#[async_trait]
+impl MirrorStore for SqliteStorage {
+    async fn create_mirror(&self, server_url: &str, remote_channel: &str, created_at: i64) -> StorageResult<MirrorRecord> {
+        let server_url = server_url.to_string();
+        let remote_channel = remote_channel.to_string();
+
+        self.with_conn(move |db| {
+            let result = db.execute(
+                "INSERT INTO mirrors (server_url, remote_channel, created_at) VALUES (?1, ?2, ?3)",
+                params![server_url, remote_channel, created_at],
+            );
+
+            match result {
+                Ok(_) => Ok(MirrorRecord {
+                    id: db.last_insert_rowid(),
+                    server_url,
+                    remote_channel,
+                    channel_id: None,
+                    last_synced_at: None,
+                    last_error: None,
+                    created_at,
+                }),
+                Err(rusqlite::Error::SqliteFailure(e, _))
+                    if e.code == rusqlite::ErrorCode::ConstraintViolation =>
+                {
+                    Err(StorageError::AlreadyExists)
+                }
+                Err(e) => Err(e.into()),
+            }
+        })
+        .await
+    }
+
+    async fn list_mirrors(&self) -> StorageResult<Vec<MirrorRecord>> {
+        self.with_conn(|db| {
+            let mut stmt = db.prepare(&format!("SELECT {MIRROR_COLUMNS} FROM mirrors ORDER BY id"))?;
+            let mirrors = stmt
+                .query_map([], mirror_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(mirrors)
+        })
+        .await
+    }
+
+    async fn delete_mirror(&self, id: i64) -> StorageResult<bool> {
+        self.with_conn(move |db| {
+            let removed = db.execute("DELETE FROM mirrors WHERE id = ?1", params![id])?;
+            Ok(removed > 0)
+        })
+        .await
+    }
+
+    async fn mark_mirror_synced(&self, id: i64, channel_id: i64, synced_at: i64) -> StorageResult<()> {
+        self.with_conn(move |db| {
+            db.execute(
+                "UPDATE mirrors SET channel_id = ?2, last_synced_at = ?3, last_error = NULL WHERE id = ?1",
+                params![id, channel_id, synced_at],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn mark_mirror_failed(&self, id: i64, error: &str) -> StorageResult<()> {
+        let error = error.to_string();
+
+        self.with_conn(move |db| {
+            db.execute("UPDATE mirrors SET last_error = ?2 WHERE id = ?1", params![id, error])?;
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_initialize_database() {
+        let storage = SqliteStorage::open(":memory:");
+        assert!(storage.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_import_archive_is_all_or_nothing() {
+        let storage = SqliteStorage::open_in_memory().unwrap();
+        storage.pool.get().unwrap().execute_batch(
+            "CREATE TRIGGER fail_import BEFORE INSERT ON contents WHEN NEW.name = 'broken'
+             BEGIN SELECT RAISE(ABORT, 'disk full'); END;",
+        ).unwrap();
+
+        let content = |name: &str| NewContent { name: name.to_string(), art: "⠁".to_string(), ..Default::default() };
+        let channels = vec![
+            ChannelImport { name: "neko".to_string(), password_hash: "x".to_string(), contents: vec![content("idle")], ..Default::default() },
+            ChannelImport { name: "mugs".to_string(), password_hash: "y".to_string(), contents: vec![content("ok"), content("broken")], ..Default::default() },
+        ];
+
+        let result = storage.import_archive(channels, vec!["https://tama.example".to_string()], 0).await;
+        assert!(result.is_err());
+        assert!(storage.list_channels().await.unwrap().is_empty());
+        assert!(storage.list_servers().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_create_channel_rejects_duplicate_name() {
+        let storage = SqliteStorage::open_in_memory().unwrap();
+        storage.create_channel("neko", "hash", 1).await.unwrap();
+
+        let result = storage.create_channel("neko", "hash", 2).await;
+        assert_eq!(result.unwrap_err(), StorageError::AlreadyExists);
+    }
+
+    #[tokio::test]
+    async fn test_latest_contents_newest_first() {
+        let storage = SqliteStorage::open_in_memory().unwrap();
+        let channel = storage.create_channel("neko", "hash", 1).await.unwrap();
+
+        for created_at in [10, 30, 20] {
+            storage.create_content(NewContent {
+                channel_id: channel.id,
+                name: format!("item {created_at}"),
+                art: "art".to_string(),
+                midi_composition: "4c".to_string(),
+                fps: 10.0,
+                created_at,
+                ..Default::default()
+            }).await.unwrap();
+        }
+
+        let feed = storage.latest_contents(Listing::All, 2).await.unwrap();
+        assert_eq!(feed.len(), 2);
+        assert_eq!(feed[0].content.created_at, 30);
+        assert_eq!(feed[1].content.created_at, 20);
+        assert_eq!(feed[0].channel_name, "neko");
+
+        let channel_feed = storage.latest_channel_contents(channel.id, Listing::All, 2).await.unwrap();
+        let created: Vec<i64> = channel_feed.iter().map(|c| c.created_at).collect();
+        assert_eq!(created, vec![30, 20]);
+    }
+
+    #[tokio::test]
+    async fn test_listings_hide_unlisted_private_and_scheduled() {
+        let storage = SqliteStorage::open_in_memory().unwrap();
+        let channel = storage.create_channel("neko", "hash", 1).await.unwrap();
+
+        for (name, visibility, publish_at) in [
+            ("public", Visibility::Public, None),
+            ("unlisted", Visibility::Unlisted, None),
+            ("private", Visibility::Private, None),
+            ("scheduled", Visibility::Public, Some(100)),
+        ] {
+            storage.create_content(NewContent {
+                channel_id: channel.id,
+                name: name.to_string(),
+                art: "art".to_string(),
+                midi_composition: "4c".to_string(),
+                fps: 10.0,
+                created_at: 10,
+                visibility,
+                publish_at,
+                share_token: (visibility == Visibility::Unlisted).then(|| "token".to_string()),
+                ..Default::default()
+            }).await.unwrap();
+        }
+
+        let names = |contents: Vec<ContentRecord>| contents.into_iter().map(|c| c.name).collect::<Vec<_>>();
+
+        let listed = storage.list_channel_contents(channel.id, Listing::PublicAt(50), 10, 0).await.unwrap();
+        assert_eq!(names(listed), vec!["public"]);
+
+        let feed = storage.latest_contents(Listing::PublicAt(100), 10).await.unwrap();
+        let feed_names: Vec<String> = feed.into_iter().map(|e| e.content.name).collect();
+        assert_eq!(feed_names, vec!["scheduled", "public"]);
+
+        let all = storage.list_channel_contents(channel.id, Listing::All, 10, 0).await.unwrap();
+        assert_eq!(all.len(), 4);
+        assert_eq!(all[3].publish_at, Some(100));
+
+        let shared = storage.find_content_by_share_token("token").await.unwrap().unwrap();
+        assert_eq!(shared.visibility, Visibility::Unlisted);
+
+        assert_eq!(storage.next_scheduled_publish(50).await.unwrap(), Some(100));
+        assert_eq!(storage.next_scheduled_publish(100).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_save_and_update_profile() {
+        let storage = SqliteStorage::open_in_memory().unwrap();
+        let channel = storage.create_channel("neko", "hash", 1).await.unwrap();
+        assert!(storage.find_profile(channel.id).await.unwrap().is_empty());
+
+        let mut profile = ChannelProfile {
+            display_name: Some("Neko".to_string()),
+            bio: Some("Sleeps a lot".to_string()),
+            avatar: None,
+            links: vec!["https://neko.example".to_string()],
+        };
+        storage.save_profile(channel.id, &profile).await.unwrap();
+
+        profile.bio = None;
+        storage.save_profile(channel.id, &profile).await.unwrap();
+        assert_eq!(storage.find_profile(channel.id).await.unwrap(), profile);
+    }
+
+    #[tokio::test]
+    async fn test_add_server_is_idempotent() {
+        let storage = SqliteStorage::open_in_memory().unwrap();
+        assert!(storage.add_server("https://a.example").await.unwrap());
+        assert!(!storage.add_server("https://a.example").await.unwrap());
+        assert_eq!(storage.list_servers().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_members_and_invites() {
+        let storage = SqliteStorage::open_in_memory().unwrap();
+        let channel = storage.create_channel("team", "hash", 1).await.unwrap();
+        let alice = storage.create_user("alice", "hash", 2).await.unwrap();
+        assert_eq!(storage.create_user("alice", "other", 3).await.unwrap_err(), StorageError::AlreadyExists);
+
+        storage.set_member(channel.id, alice.id, Role::Viewer, 4).await.unwrap();
+        storage.set_member(channel.id, alice.id, Role::Editor, 5).await.unwrap();
+        assert_eq!(storage.find_member_role(channel.id, alice.id).await.unwrap(), Some(Role::Editor));
+
+        let members = storage.list_members(channel.id).await.unwrap();
+        assert_eq!(members.len(), 1);
+        assert_eq!((members[0].user_name.as_str(), members[0].joined_at), ("alice", 4));
+
+        assert!(storage.remove_member(channel.id, alice.id).await.unwrap());
+        assert!(!storage.remove_member(channel.id, alice.id).await.unwrap());
+
+        let invite = InviteRecord {
+            token: "invite".to_string(),
+            channel_id: channel.id,
+            role: Role::Editor,
+            created_at: 6,
+            expires_at: 100,
+        };
+        storage.create_invite(&invite).await.unwrap();
+        assert_eq!(storage.find_invite("invite").await.unwrap(), Some(invite.clone()));
+        assert_eq!(storage.take_invite("invite").await.unwrap(), Some(invite));
+        assert_eq!(storage.take_invite("invite").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_auth_events_newest_first_per_channel() {
+        let storage = SqliteStorage::open_in_memory().unwrap();
+        for (channel_name, kind, created_at) in [
+            ("neko", AuthEventKind::LoginFailure, 1),
+            ("other", AuthEventKind::LoginFailure, 2),
+            ("neko", AuthEventKind::LoginSuccess, 3),
+        ] {
+            storage.record_auth_event(&NewAuthEvent {
+                channel_name: channel_name.to_string(),
+                kind,
+                ip: Some("127.0.0.1".to_string()),
+                user_name: None,
+                created_at,
+            }).await.unwrap();
+        }
+
+        let events = storage.list_auth_events("neko", 10).await.unwrap();
+        assert_eq!(events.iter().map(|e| e.kind).collect::<Vec<_>>(), vec![AuthEventKind::LoginSuccess, AuthEventKind::LoginFailure]);
+        assert_eq!(events[0].ip.as_deref(), Some("127.0.0.1"));
+        assert_eq!(storage.list_auth_events("neko", 1).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_login_failures_since_last_success() {
+        let storage = SqliteStorage::open_in_memory().unwrap();
+        for (channel_name, kind, user_name, created_at) in [
+            ("neko", AuthEventKind::LoginFailure, None, 1),
+            ("neko", AuthEventKind::LoginSuccess, None, 2),
+            ("neko", AuthEventKind::LoginFailure, None, 2),
+            ("neko", AuthEventKind::LockedOut, None, 3),
+            ("neko", AuthEventKind::LoginFailure, None, 4),
+            ("neko", AuthEventKind::LoginFailure, Some("alice"), 5),
+            ("other", AuthEventKind::LoginFailure, Some("alice"), 6),
+        ] {
+            storage.record_auth_event(&NewAuthEvent {
+                channel_name: channel_name.to_string(),
+                kind,
+                ip: None,
+                user_name: user_name.map(str::to_string),
+                created_at,
+            }).await.unwrap();
+        }
+
+        let failures = storage.login_failures("neko", None, 0).await.unwrap();
+        assert_eq!(failures, LoginFailures { count: 2, last_at: Some(4), refused: false });
+        assert_eq!(storage.login_failures("neko", None, 3).await.unwrap().count, 1);
+        assert_eq!(storage.login_failures("other", None, 0).await.unwrap(), LoginFailures::default());
+
+        let member = storage.login_failures("anything", Some("alice"), 0).await.unwrap();
+        assert_eq!(member, LoginFailures { count: 2, last_at: Some(6), refused: false });
+
+        storage.record_auth_event(&NewAuthEvent {
+            channel_name: "neko".to_string(),
+            kind: AuthEventKind::LockedOut,
+            ip: None,
+            user_name: None,
+            created_at: 7,
+        }).await.unwrap();
+        assert!(storage.login_failures("neko", None, 0).await.unwrap().refused);
+        assert!(!storage.login_failures("anything", Some("alice"), 0).await.unwrap().refused);
+    }
+
+    #[tokio::test]
+    async fn test_prune_auth_events() {
+        let storage = SqliteStorage::open_in_memory().unwrap();
+        for created_at in [1, 2, 3] {
+            storage.record_auth_event(&NewAuthEvent {
+                channel_name: "neko".to_string(),
+                kind: AuthEventKind::LoginFailure,
+                ip: None,
+                user_name: None,
+                created_at,
+            }).await.unwrap();
+        }
+
+        assert_eq!(storage.prune_auth_events(3).await.unwrap(), 2);
+        let events = storage.list_auth_events("neko", 10).await.unwrap();
+        assert_eq!(events.iter().map(|e| e.created_at).collect::<Vec<_>>(), vec![3]);
+    }
+
+    #[tokio::test]
+    async fn test_comments_newest_first_per_content() {
+        let storage = SqliteStorage::open_in_memory().unwrap();
+        let channel = storage.create_channel("neko", "hash", 1).await.unwrap();
+        let mut content_ids = Vec::new();
+        for name in ["nap", "walk"] {
+            let content = storage.create_content(NewContent {
+                channel_id: channel.id,
+                name: name.to_string(),
+                ..Default::default()
+            }).await.unwrap();
+            content_ids.push(content.id);
+        }
+
+        for (index, body) in [(0, "first"), (1, "elsewhere"), (0, "second")] {
+            storage.create_comment(&NewComment {
+                content_id: content_ids[index],
+                author_channel_id: channel.id,
+                author_user_id: None,
+                author_name: channel.name.clone(),
+                body: body.to_string(),
+                created_at: 10,
+            }).await.unwrap();
+        }
+
+        let comments = storage.list_comments(content_ids[0], 10).await.unwrap();
+        assert_eq!(comments.iter().map(|c| c.body.as_str()).collect::<Vec<_>>(), vec!["second", "first"]);
+        assert_eq!(storage.find_comment(comments[0].id).await.unwrap(), Some(comments[0].clone()));
+
+        assert!(storage.delete_comment(comments[0].id).await.unwrap());
+        assert!(!storage.delete_comment(comments[0].id).await.unwrap());
+        assert_eq!(storage.list_comments(content_ids[0], 10).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_webhooks_and_delivery_log() {
+        let storage = SqliteStorage::open_in_memory().unwrap();
+        let channel = storage.create_channel("neko", "hash", 1).await.unwrap();
+        let other = storage.create_channel("tora", "hash", 1).await.unwrap();
+
+        let webhook = storage.create_webhook(&NewWebhook {
+            channel_id: channel.id,
+            url: "http://localhost:9000/hook".to_string(),
+            secret: "secret".to_string(),
+            events: vec![WebhookEvent::ContentCreated],
+            created_at: 10,
+        }).await.unwrap();
+        assert_eq!(storage.list_webhooks(channel.id).await.unwrap(), vec![webhook.clone()]);
+
+        let delivery = storage.create_delivery(webhook.id, WebhookEvent::ContentCreated, 20).await.unwrap();
+        assert_eq!((delivery.status, delivery.attempts, delivery.url.as_str()), (DeliveryStatus::Pending, 0, "http://localhost:9000/hook"));
+
+        let update = DeliveryUpdate {
+            status: DeliveryStatus::Failed,
+            attempts: 3,
+            response_status: Some(500),
+            error: Some("Internal Server Error".to_string()),
+            updated_at: 30,
+        };
+        storage.update_delivery(delivery.id, &update).await.unwrap();
+
+        let log = storage.list_deliveries(channel.id, 10).await.unwrap();
+        assert_eq!(log.len(), 1);
+        assert_eq!((log[0].status, log[0].attempts, log[0].response_status, log[0].updated_at), (DeliveryStatus::Failed, 3, Some(500), 30));
+        assert!(storage.list_deliveries(other.id, 10).await.unwrap().is_empty());
+
+        assert!(!storage.delete_webhook(other.id, webhook.id).await.unwrap());
+        assert!(storage.delete_webhook(channel.id, webhook.id).await.unwrap());
+        assert!(storage.list_webhooks(channel.id).await.unwrap().is_empty());
+        assert!(storage.list_deliveries(channel.id, 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_mirrored_contents_keep_their_origin() {
+        let storage = SqliteStorage::open_in_memory().unwrap();
+        let channel = storage.create_channel("neko@peer.example", "", 1).await.unwrap();
+        let origin = ContentOrigin {
+            server_url: "https://peer.example".to_string(),
+            content_id: 42,
+            content_hash: "abc123".to_string(),
+        };
+
+        let content = storage.create_content(NewContent {
+            channel_id: channel.id,
+            art: "art".to_string(),
+            midi_composition: "4c".to_string(),
+            created_at: 10,
+            origin: Some(origin.clone()),
+            ..Default::default()
+        }).await.unwrap();
+
+        assert_eq!(storage.find_content(content.id).await.unwrap().unwrap().origin, Some(origin));
+        assert_eq!(storage.find_content_by_hash(channel.id, "abc123").await.unwrap(), Some(content.id));
+        assert_eq!(storage.find_content_by_hash(channel.id, "def456").await.unwrap(), None);
+
+        // Served under the mirrored channel, but not in the server's own feed
+        assert_eq!(storage.list_channel_contents(channel.id, Listing::PublicAt(100), 10, 0).await.unwrap().len(), 1);
+        assert!(storage.latest_contents(Listing::PublicAt(100), 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_mirrors() {
+        let storage = SqliteStorage::open_in_memory().unwrap();
+        let mirror = storage.create_mirror("https://peer.example", "neko", 1).await.unwrap();
+        assert!(matches!(
+            storage.create_mirror("https://peer.example", "neko", 2).await,
+            Err(StorageError::AlreadyExists)
+        ));
+
+        let channel = storage.create_channel("neko@peer.example", "", 1).await.unwrap();
+        storage.mark_mirror_failed(mirror.id, "peer is down").await.unwrap();
+        assert_eq!(storage.list_mirrors().await.unwrap()[0].last_error.as_deref(), Some("peer is down"));
+
+        storage.mark_mirror_synced(mirror.id, channel.id, 20).await.unwrap();
+        let synced = storage.list_mirrors().await.unwrap().remove(0);
+        assert_eq!((synced.channel_id, synced.last_synced_at, synced.last_error), (Some(channel.id), Some(20), None));
+
+        assert!(storage.delete_mirror(mirror.id).await.unwrap());
+        assert!(!storage.delete_mirror(mirror.id).await.unwrap());
+        assert!(storage.find_channel_by_id(channel.id).await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,23 @@
+//! Fixtures shared by the handler tests
+
+use axum::http::HeaderMap;
+
+use crate::storage::ChannelRecord;
+use crate::AppState;
+
+/// Headers carrying `token` the way clients send it
+pub fn bearer(token: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(tama::api::HEADER_AUTH, format!("Bearer {token}").parse().unwrap());
+    headers
+}
+
+/// Headers of the channel's own login
+pub fn owner_headers(state: &AppState, channel: &ChannelRecord) -> HeaderMap {
+    bearer(&crate::jwt::create_jwt(channel.id, &channel.name, &state.jwt_secret).unwrap())
+}
+
+/// Headers of a member signed in to the channel
+pub fn member_headers(state: &AppState, channel: &ChannelRecord, user_id: i64, user_name: &str) -> HeaderMap {
+    bearer(&crate::jwt::create_user_jwt(channel.id, &channel.name, user_id, user_name, &state.jwt_secret).unwrap())
+}
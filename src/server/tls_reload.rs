@@ -0,0 +1,135 @@
+use axum_server::tls_rustls::RustlsConfig;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::shutdown::Shutdown;
+
+/// How often the certificate and key files are checked for changes
+const POLL_INTERVAL_SECS: u64 = 60;
+
+type Fingerprint = (Option<SystemTime>, Option<SystemTime>);
+
+fn modified_at(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn fingerprint(cert_path: &Path, key_path: &Path) -> Fingerprint {
+    (modified_at(cert_path), modified_at(key_path))
+}
+
+async fn reload(config: &RustlsConfig, cert_path: &Path, key_path: &Path) {
+    match config.reload_from_pem_file(cert_path, key_path).await {
+        Ok(()) => tracing::info!("TLS certificates reloaded from {}", cert_path.display()),
+        // Keep serving with the previous certificate, the files may be mid-renewal
+        Err(e) => tracing::error!("Failed to reload TLS certificates: {}", e),
+    }
+}
+
+/// Reloads `config` in place whenever the certificate or key file changes on disk,
+/// or when the process receives SIGHUP. Stops when `shutdown` is triggered.
+pub fn spawn_tls_reloader(
+    config: RustlsConfig,
+    cert_path: String,
+    key_path: String,
+    shutdown: Shutdown,
+) {
+    tokio::spawn(async move {
+        let cert_path = PathBuf::from(cert_path);
+        let key_path = PathBuf::from(key_path);
+        let mut last_fingerprint = fingerprint(&cert_path, &key_path);
+
+        let mut interval = tokio::time::interval(Duration::from_secs(POLL_INTERVAL_SECS));
+        interval.tick().await;
+
+        let mut hangup = HangupSignal::new();
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    let current = fingerprint(&cert_path, &key_path);
+                    if current != last_fingerprint {
+                        tracing::info!("TLS certificate files changed on disk");
+                        last_fingerprint = current;
+                        reload(&config, &cert_path, &key_path).await;
+                    }
+                }
+                _ = hangup.recv() => {
+                    tracing::info!("SIGHUP received");
+                    last_fingerprint = fingerprint(&cert_path, &key_path);
+                    reload(&config, &cert_path, &key_path).await;
+                }
+                _ = shutdown.wait() => break,
+            }
+        }
+    });
+}
+
+#[cfg(unix)]
+struct HangupSignal(Option<tokio::signal::unix::Signal>);
+
+#[cfg(unix)]
+impl HangupSignal {
+    fn new() -> Self {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::hangup()) {
+            Ok(sighup) => Self(Some(sighup)),
+            Err(e) => {
+                tracing::error!("Failed to install SIGHUP handler: {}", e);
+                Self(None)
+            }
+        }
+    }
+
+    async fn recv(&mut self) {
+        match &mut self.0 {
+            Some(sighup) => {
+                sighup.recv().await;
+            }
+            None => std::future::pending().await,
+        }
+    }
+}
+
+#[cfg(not(unix))]
+struct HangupSignal;
+
+#[cfg(not(unix))]
+impl HangupSignal {
+    fn new() -> Self {
+        Self
+    }
+
+    async fn recv(&mut self) {
+        std::future::pending().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint_changes_when_file_is_rewritten() {
+        let dir = std::env::temp_dir().join(format!("tama-tls-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let cert = dir.join("cert.pem");
+        let key = dir.join("key.pem");
+        std::fs::write(&cert, "cert").unwrap();
+        std::fs::write(&key, "key").unwrap();
+
+        let before = fingerprint(&cert, &key);
+        let file = std::fs::File::options().write(true).open(&cert).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(10)).unwrap();
+        let after = fingerprint(&cert, &key);
+
+        assert_ne!(before, after);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_fingerprint_of_missing_files() {
+        let missing = Path::new("/nonexistent/tama/cert.pem");
+        assert_eq!(fingerprint(missing, missing), (None, None));
+    }
+}
@@ -0,0 +1,120 @@
+use axum::http::{HeaderMap, StatusCode};
+use tama::api::Visibility;
+
+use crate::error::database_error;
+use crate::{auth, storage::ContentRecord, AppState};
+
+/// Cache-Control for responses derived from a content that anyone may see
+pub const PUBLIC_CACHE_CONTROL: &str = "public, max-age=86400";
+/// Cache-Control for responses only its owner, or share link holders, may see
+pub const PRIVATE_CACHE_CONTROL: &str = "private, no-store";
+
+/// Whether the caller has any role on the channel. Members see all of its contents.
+pub async fn is_member(
+    state: &AppState,
+    headers: &HeaderMap,
+    channel_id: i64,
+) -> Result<bool, (StatusCode, String)> {
+    let Ok(caller) = auth::authenticate_caller(headers, &state.jwt_secret) else {
+        return Ok(false);
+    };
+
+    let role = auth::caller_role(state.storage.as_ref(), &caller, channel_id).await
+        .map_err(database_error)?;
+
+    Ok(role.is_some())
+}
+
+pub fn can_view(content: &ContentRecord, is_member: bool, now: i64) -> bool {
+    is_member || content.is_visible_at(now)
+}
+
+pub fn cache_control(content: &ContentRecord, now: i64) -> &'static str {
+    if content.visibility == Visibility::Public && content.is_visible_at(now) {
+        PUBLIC_CACHE_CONTROL
+    } else {
+        PRIVATE_CACHE_CONTROL
+    }
+}
+
+/// Looks up a content by id. Contents the caller may not see are reported as missing,
+/// so private and scheduled ids can't be probed.
+pub async fn find_visible_content(
+    state: &AppState,
+    content_id: i64,
+    headers: &HeaderMap,
+) -> Result<ContentRecord, (StatusCode, String)> {
+    let content = state.storage.find_content(content_id).await
+        .map_err(database_error)?;
+
+    visible_or_not_found(state, content, headers).await
+}
+
+/// Looks up an unlisted content by share token, with the same rules as `find_visible_content`
+pub async fn find_shared_content(
+    state: &AppState,
+    token: &str,
+    headers: &HeaderMap,
+) -> Result<ContentRecord, (StatusCode, String)> {
+    let content = state.storage.find_content_by_share_token(token).await
+        .map_err(database_error)?;
+
+    visible_or_not_found(state, content, headers).await
+}
+
+async fn visible_or_not_found(
+    state: &AppState,
+    content: Option<ContentRecord>,
+    headers: &HeaderMap,
+) -> Result<ContentRecord, (StatusCode, String)> {
+    let not_found = || (StatusCode::NOT_FOUND, "Content not found".to_string());
+    let content = content.ok_or_else(not_found)?;
+    let now = chrono::Utc::now().timestamp();
+
+    // Membership only matters, and is only looked up, for hidden contents
+    let member = !content.is_visible_at(now) && is_member(state, headers, content.channel_id).await?;
+
+    if can_view(&content, member, now) {
+        Ok(content)
+    } else {
+        Err(not_found())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn content(visibility: Visibility, publish_at: Option<i64>) -> ContentRecord {
+        ContentRecord {
+            channel_id: 7,
+            created_at: 10,
+            visibility,
+            publish_at,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_can_view() {
+        let public = content(Visibility::Public, None);
+        let unlisted = content(Visibility::Unlisted, None);
+        let private = content(Visibility::Private, None);
+        let scheduled = content(Visibility::Public, Some(100));
+
+        assert!(can_view(&public, false, 50));
+        assert!(can_view(&unlisted, false, 50));
+        assert!(!can_view(&private, false, 50));
+        assert!(can_view(&private, true, 50));
+        assert!(!can_view(&scheduled, false, 50));
+        assert!(can_view(&scheduled, false, 100));
+        assert!(can_view(&scheduled, true, 50));
+    }
+
+    #[test]
+    fn test_cache_control() {
+        assert_eq!(cache_control(&content(Visibility::Public, None), 50), PUBLIC_CACHE_CONTROL);
+        assert_eq!(cache_control(&content(Visibility::Unlisted, None), 50), PRIVATE_CACHE_CONTROL);
+        assert_eq!(cache_control(&content(Visibility::Public, Some(100)), 50), PRIVATE_CACHE_CONTROL);
+    }
+}
@@ -0,0 +1,175 @@
+use crate::error::internal_error;
+use crate::{visibility, AppState};
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use tama::midi_composer::render_composition_wav;
+
+/// Rendered WAVs are ~5.8MB per minute of audio (48kHz 16-bit mono), so only keep the most recent ones
+const WAV_CACHE_CAPACITY_BYTES: usize = 64 * 1024 * 1024;
+/// Longer renders are served without being cached, a few of them would fill the cache
+const MAX_CACHED_WAV_BYTES: usize = 8 * 1024 * 1024;
+/// Longest composition the server is willing to render
+const MAX_AUDIO_SECONDS: f32 = 300.0;
+
+#[derive(Default)]
+struct WavCacheData {
+    entries: HashMap<i64, Bytes>,
+    order: VecDeque<i64>,
+    size: usize,
+}
+
+/// Rendered audio per content id. Contents can't be edited, so entries never go stale.
+/// Access is checked on every request, the cache only holds the rendering.
+pub struct WavCache {
+    data: Mutex<WavCacheData>,
+    capacity_bytes: usize,
+    max_entry_bytes: usize,
+}
+
+impl Default for WavCache {
+    fn default() -> Self {
+        Self::new(WAV_CACHE_CAPACITY_BYTES, MAX_CACHED_WAV_BYTES)
+    }
+}
+
+impl WavCache {
+    pub fn new(capacity_bytes: usize, max_entry_bytes: usize) -> Self {
+        Self {
+            data: Mutex::new(WavCacheData::default()),
+            capacity_bytes,
+            max_entry_bytes: max_entry_bytes.min(capacity_bytes),
+        }
+    }
+
+    pub fn get(&self, content_id: i64) -> Option<Bytes> {
+        self.data.lock().ok()?.entries.get(&content_id).cloned()
+    }
+
+    pub fn insert(&self, content_id: i64, wav: Bytes) {
+        if wav.len() > self.max_entry_bytes {
+            return;
+        }
+        let Ok(mut data) = self.data.lock() else {
+            return;
+        };
+
+        data.size += wav.len();
+        match data.entries.insert(content_id, wav) {
+            Some(previous) => data.size -= previous.len(),
+            None => data.order.push_back(content_id),
+        }
+
+        while data.size > self.capacity_bytes {
+            let Some(oldest) = data.order.pop_front() else {
+                break;
+            };
+            if let Some(evicted) = data.entries.remove(&oldest) {
+                data.size -= evicted.len();
+            }
+        }
+    }
+}
+
+pub async fn get_content_audio(
+    Path(content_id): Path<i64>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Response, (StatusCode, String)> {
+    tracing::debug!("[GET /content/{content_id}/audio.wav] Request received");
+
+    let content = visibility::find_visible_content(&state, content_id, &headers).await?;
+    let cache_control = visibility::cache_control(&content, chrono::Utc::now().timestamp());
+
+    let wav = match state.wav_cache.get(content_id) {
+        Some(wav) => wav,
+        None => {
+            // Synthesis is CPU bound, keep it off the async workers
+            let midi_composition = content.midi_composition;
+            let wav = tokio::task::spawn_blocking(move || {
+                render_composition_wav(&midi_composition, MAX_AUDIO_SECONDS)
+            })
+            .await
+            .map_err(|e| internal_error("Render task failed", e))?
+            .map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, e))?;
+
+            let wav = Bytes::from(wav);
+            state.wav_cache.insert(content_id, wav.clone());
+            wav
+        }
+    };
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "audio/wav"),
+            (header::CACHE_CONTROL, cache_control),
+        ],
+        wav,
+    ).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{ChannelStore, ContentStore, MemoryStorage, NewContent};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_cache_evicts_oldest_entries_past_capacity() {
+        let cache = WavCache::new(10, 10);
+        cache.insert(1, Bytes::from_static(b"one"));
+        cache.insert(2, Bytes::from_static(b"two"));
+        cache.insert(1, Bytes::from_static(b"one!"));
+        cache.insert(3, Bytes::from_static(b"three"));
+
+        assert!(cache.get(1).is_none());
+        assert_eq!(cache.get(2).unwrap(), Bytes::from_static(b"two"));
+        assert_eq!(cache.get(3).unwrap(), Bytes::from_static(b"three"));
+    }
+
+    #[test]
+    fn test_cache_skips_large_renders() {
+        let cache = WavCache::new(10, 4);
+        cache.insert(1, Bytes::from_static(b"one"));
+        cache.insert(2, Bytes::from_static(b"three"));
+
+        assert_eq!(cache.get(1).unwrap(), Bytes::from_static(b"one"));
+        assert!(cache.get(2).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_content_audio_renders_and_caches() {
+        let storage = MemoryStorage::new();
+        let channel = storage.create_channel("neko", "hash", 1).await.unwrap();
+        let content = storage.create_content(NewContent {
+            channel_id: channel.id,
+            name: "idle".to_string(),
+            art: "Ascii Art Animation, 1x1\n⠁".to_string(),
+            midi_composition: "8c 8e".to_string(),
+            fps: 10.0,
+            created_at: 2,
+            ..Default::default()
+        }).await.unwrap();
+        let state = AppState::for_tests(Arc::new(storage));
+
+        let response = get_content_audio(Path(content.id), HeaderMap::new(), State(state.clone())).await.unwrap();
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "audio/wav");
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(body.starts_with(b"RIFF"));
+        assert_eq!(state.wav_cache.get(content.id).unwrap(), body);
+    }
+
+    #[tokio::test]
+    async fn test_get_content_audio_not_found() {
+        let state = AppState::for_tests(Arc::new(MemoryStorage::new()));
+
+        let result = get_content_audio(Path(42), HeaderMap::new(), State(state)).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,248 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::{HeaderMap, StatusCode},
+};
+use serde::Deserialize;
+
+use crate::error::{database_error, internal_error};
+use crate::storage::{NewWebhook, WebhookRecord};
+use crate::{auth, AppState};
+use tama::api::{CreateWebhookRequest, WebhookDelivery, WebhookEvent, WebhookInfo};
+
+const MAX_WEBHOOKS_PER_CHANNEL: usize = 10;
+const MAX_URL_LENGTH: usize = 2048;
+const MAX_SECRET_LENGTH: usize = 256;
+const DEFAULT_DELIVERIES_LIMIT: i64 = 50;
+const MAX_DELIVERIES_LIMIT: i64 = 500;
+
+#[derive(Deserialize)]
+pub struct DeliveriesParams {
+    #[serde(default = "default_deliveries_limit")]
+    pub limit: i64,
+}
+
+fn default_deliveries_limit() -> i64 {
+    DEFAULT_DELIVERIES_LIMIT
+}
+
+/// Webhooks act on behalf of the whole channel, so only its owners manage them
+async fn authorize_owner(state: &AppState, headers: &HeaderMap) -> Result<i64, (StatusCode, String)> {
+    let caller = auth::authenticate_caller(headers, &state.jwt_secret)
+        .map_err(|status| (status, "Authentication required".to_string()))?;
+
+    let role = auth::caller_role(state.storage.as_ref(), &caller, caller.channel_id).await
+        .map_err(database_error)?;
+
+    if !role.is_some_and(|role| role.can_manage()) {
+        return Err((StatusCode::FORBIDDEN, "Only channel owners can manage webhooks".to_string()));
+    }
+
+    Ok(caller.channel_id)
+}
+
+fn validate_webhook(request: &CreateWebhookRequest) -> Result<(), String> {
+    let url = reqwest::Url::parse(request.url.trim())
+        .map_err(|e| format!("Invalid webhook URL: {e}"))?;
+
+    if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+        return Err("Webhook URL must be an http or https URL".to_string());
+    }
+
+    if request.url.len() > MAX_URL_LENGTH {
+        return Err("Webhook URL is too long".to_string());
+    }
+
+    if request.secret.is_empty() {
+        return Err("Webhook secret cannot be empty".to_string());
+    }
+
+    if request.secret.len() > MAX_SECRET_LENGTH {
+        return Err(format!("Webhook secret is too long (max {MAX_SECRET_LENGTH} bytes)"));
+    }
+
+    if request.events.is_empty() {
+        return Err("Webhooks need at least one event".to_string());
+    }
+
+    Ok(())
+}
+
+fn webhook_info(webhook: WebhookRecord) -> WebhookInfo {
+    WebhookInfo {
+        id: webhook.id,
+        url: webhook.url,
+        events: webhook.events,
+        created_at: webhook.created_at,
+    }
+}
+
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<CreateWebhookRequest>,
+) -> Result<Json<WebhookInfo>, (StatusCode, String)> {
+    let channel_id = authorize_owner(&state, &headers).await?;
+
+    validate_webhook(&request)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    state.webhooks.check_destination(request.url.trim()).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Webhook URL not allowed: {e}")))?;
+
+    let existing = state.storage.list_webhooks(channel_id).await.map_err(database_error)?;
+    if existing.len() >= MAX_WEBHOOKS_PER_CHANNEL {
+        return Err((StatusCode::CONFLICT, format!("Channels can have at most {MAX_WEBHOOKS_PER_CHANNEL} webhooks")));
+    }
+
+    let mut events = request.events;
+    events.sort_by_key(WebhookEvent::as_str);
+    events.dedup();
+
+    let webhook = state.storage.create_webhook(&NewWebhook {
+        channel_id,
+        url: request.url.trim().to_string(),
+        secret: request.secret,
+        events,
+        created_at: chrono::Utc::now().timestamp(),
+    }).await
+        .map_err(|e| internal_error("Failed to save webhook", e))?;
+
+    tracing::info!("Webhook created: id={}, channel_id={}, url={}", webhook.id, channel_id, webhook.url);
+    Ok(Json(webhook_info(webhook)))
+}
+
+pub async fn list_webhooks(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<WebhookInfo>>, (StatusCode, String)> {
+    let channel_id = authorize_owner(&state, &headers).await?;
+
+    let webhooks = state.storage.list_webhooks(channel_id).await
+        .map_err(database_error)?
+        .into_iter()
+        .map(webhook_info)
+        .collect();
+
+    Ok(Json(webhooks))
+}
+
+/// Removes the webhook along with its delivery log
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    Path(webhook_id): Path<i64>,
+    headers: HeaderMap,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let channel_id = authorize_owner(&state, &headers).await?;
+
+    if !state.storage.delete_webhook(channel_id, webhook_id).await.map_err(database_error)? {
+        return Err((StatusCode::NOT_FOUND, "Webhook not found".to_string()));
+    }
+
+    tracing::info!("Webhook deleted: id={webhook_id}, channel_id={channel_id}");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Most recent deliveries first, across all webhooks of the channel
+pub async fn list_deliveries(
+    State(state): State<AppState>,
+    Query(params): Query<DeliveriesParams>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<WebhookDelivery>>, (StatusCode, String)> {
+    let channel_id = authorize_owner(&state, &headers).await?;
+    let limit = params.limit.clamp(1, MAX_DELIVERIES_LIMIT);
+
+    let deliveries = state.storage.list_deliveries(channel_id, limit).await
+        .map_err(database_error)?
+        .into_iter()
+        .map(|delivery| WebhookDelivery {
+            id: delivery.id,
+            webhook_id: delivery.webhook_id,
+            url: delivery.url,
+            event: delivery.event,
+            status: delivery.status,
+            attempts: delivery.attempts,
+            response_status: delivery.response_status,
+            error: delivery.error,
+            created_at: delivery.created_at,
+            updated_at: delivery.updated_at,
+        })
+        .collect();
+
+    Ok(Json(deliveries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{member_headers, owner_headers};
+    use crate::storage::{ChannelStore, MemberStore, MemoryStorage, WebhookStore};
+    use std::sync::Arc;
+    use tama::api::{DeliveryStatus, Role};
+
+    fn request(url: &str) -> CreateWebhookRequest {
+        CreateWebhookRequest {
+            url: url.to_string(),
+            secret: "s3cret".to_string(),
+            events: vec![WebhookEvent::ContentCreated],
+        }
+    }
+
+    #[test]
+    fn test_validate_webhook() {
+        assert!(validate_webhook(&request("https://chat.example.com/hooks/1")).is_ok());
+        assert!(validate_webhook(&request("http://localhost:8080/build")).is_ok());
+        assert!(validate_webhook(&request("ftp://example.com/hook")).is_err());
+        assert!(validate_webhook(&request("not a url")).is_err());
+        assert!(validate_webhook(&CreateWebhookRequest { secret: String::new(), ..request("https://example.com") }).is_err());
+        assert!(validate_webhook(&CreateWebhookRequest { events: vec![], ..request("https://example.com") }).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_owners_manage_webhooks() {
+        let storage = Arc::new(MemoryStorage::new());
+        let channel = storage.create_channel("neko", "hash", 1).await.unwrap();
+        let editor = storage.create_user("mike", "hash", 1).await.unwrap();
+        storage.set_member(channel.id, editor.id, Role::Editor, 1).await.unwrap();
+
+        let state = AppState::for_tests(storage.clone());
+        let owner = owner_headers(&state, &channel);
+        let editor = member_headers(&state, &channel, editor.id, &editor.name);
+
+        let result = create_webhook(State(state.clone()), editor.clone(), Json(request("https://93.184.215.14/hook"))).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::FORBIDDEN);
+        let result = list_webhooks(State(state.clone()), editor).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::FORBIDDEN);
+
+        let twice = CreateWebhookRequest {
+            events: vec![WebhookEvent::ContentCreated, WebhookEvent::ContentCreated],
+            ..request("https://93.184.215.14/hook")
+        };
+        let Json(created) = create_webhook(State(state.clone()), owner.clone(), Json(twice)).await.unwrap();
+        assert_eq!(created.events, vec![WebhookEvent::ContentCreated]);
+        let Json(webhooks) = list_webhooks(State(state.clone()), owner.clone()).await.unwrap();
+        assert_eq!(webhooks, vec![created.clone()]);
+
+        let delivery = storage.create_delivery(created.id, WebhookEvent::ContentCreated, 5).await.unwrap();
+        let Json(log) = list_deliveries(State(state.clone()), Query(DeliveriesParams { limit: 10 }), owner.clone()).await.unwrap();
+        assert_eq!((log[0].id, log[0].status), (delivery.id, DeliveryStatus::Pending));
+
+        assert_eq!(delete_webhook(State(state.clone()), Path(created.id), owner.clone()).await.unwrap(), StatusCode::NO_CONTENT);
+        let result = delete_webhook(State(state.clone()), Path(created.id), owner.clone()).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::NOT_FOUND);
+        let Json(log) = list_deliveries(State(state), Query(DeliveriesParams { limit: 10 }), owner).await.unwrap();
+        assert!(log.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_webhooks_cant_target_the_local_network() {
+        let storage = Arc::new(MemoryStorage::new());
+        let channel = storage.create_channel("neko", "hash", 1).await.unwrap();
+        let state = AppState::for_tests(storage.clone());
+        let owner = owner_headers(&state, &channel);
+
+        for url in ["http://127.0.0.1:8080/build", "http://169.254.169.254/latest/meta-data", "http://10.0.0.2/hook"] {
+            let result = create_webhook(State(state.clone()), owner.clone(), Json(request(url))).await;
+            assert_eq!(result.unwrap_err().0, StatusCode::BAD_REQUEST, "{url}");
+        }
+        assert!(storage.list_webhooks(channel.id).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,525 @@
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use crate::storage::{ChannelRecord, ContentRecord, DeliveryUpdate, Storage, WebhookRecord};
+use tama::api::{DeliveryStatus, WebhookEvent, HEADER_WEBHOOK_DELIVERY, HEADER_WEBHOOK_EVENT, HEADER_WEBHOOK_SIGNATURE};
+use tama::signing;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Comma separated hosts webhooks may reach even though they aren't public, e.g. a CI on the same network
+pub const ALLOWED_HOSTS_VAR: &str = "WEBHOOK_ALLOWED_HOSTS";
+
+pub fn allowed_hosts_from_env() -> Vec<String> {
+    std::env::var(ALLOWED_HOSTS_VAR)
+        .map(|hosts| {
+            hosts.split(',')
+                .map(|host| host.trim().to_lowercase())
+                .filter(|host| !host.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// False for loopback, private, link-local, unspecified and other addresses that aren't on the internet
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                || a == 0
+                // Carrier-grade NAT, 100.64.0.0/10
+                || (a == 100 && (b & 0xc0) == 64))
+        }
+        IpAddr::V6(ip) => {
+            if let Some(mapped) = ip.to_ipv4_mapped() {
+                return is_public(IpAddr::V4(mapped));
+            }
+            let segments = ip.segments();
+            let embedded = |high: u16, low: u16| IpAddr::V4(Ipv4Addr::from((u32::from(high) << 16) | u32::from(low)));
+            // NAT64, 64:ff9b::/96, and 6to4, 2002::/16, reach the IPv4 address they embed
+            if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+                return is_public(embedded(segments[6], segments[7]));
+            }
+            if segments[0] == 0x2002 {
+                return is_public(embedded(segments[1], segments[2]));
+            }
+            let first = segments[0];
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                // Unique local, fc00::/7
+                || (first & 0xfe00) == 0xfc00
+                // Link-local, fe80::/10
+                || (first & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+/// Resolves the host, refusing it when any of its addresses isn't public, unless it's allowed
+async fn resolve_public(host: &str, port: u16, allowed_hosts: &[String]) -> Result<Vec<SocketAddr>, String> {
+    let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host, port)).await
+        .map_err(|e| format!("Failed to resolve {host}: {e}"))?
+        .collect();
+
+    if !allowed_hosts.iter().any(|allowed| allowed == host)
+        && let Some(address) = addresses.iter().find(|address| !is_public(address.ip()))
+    {
+        return Err(format!("{host} resolves to {}, which is not a public address", address.ip()));
+    }
+    Ok(addresses)
+}
+
+/// Refuses URLs pointing at the server's own network
+pub async fn check_destination(url: &str, allowed_hosts: &[String]) -> Result<(), String> {
+    let url = reqwest::Url::parse(url).map_err(|e| format!("Invalid webhook URL: {e}"))?;
+    let host = url.host_str()
+        .ok_or("Webhook URL has no host")?
+        .trim_start_matches('[')
+        .trim_end_matches(']');
+    let port = url.port_or_known_default().unwrap_or(80);
+
+    resolve_public(host, port, allowed_hosts).await.map(|_| ())
+}
+
+/// Resolves receivers for the HTTP client, so that a host passing `check_destination`
+/// can't switch to a private address right before the request goes out
+struct PublicResolver {
+    allowed_hosts: Arc<[String]>,
+}
+
+impl reqwest::dns::Resolve for PublicResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let allowed_hosts = self.allowed_hosts.clone();
+        Box::pin(async move {
+            let addresses = resolve_public(name.as_str(), 0, &allowed_hosts).await?;
+            Ok(Box::new(addresses.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// How often and how patiently a delivery is attempted
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    /// Wait before the second attempt, doubled before each of the following ones
+    pub base_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay: Duration::from_secs(2),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Wait after the given failed attempt, counting from 1
+    fn delay_after(&self, attempt: u32) -> Duration {
+        self.base_delay * 2u32.saturating_pow(attempt.saturating_sub(1))
+    }
+}
+
+struct Job {
+    channel_id: i64,
+    event: WebhookEvent,
+    payload: String,
+}
+
+/// Hands events to a background worker, so that uploads never wait on receivers
+pub struct WebhookDispatcher {
+    sender: Option<mpsc::UnboundedSender<Job>>,
+    allowed_hosts: Arc<[String]>,
+}
+
+impl WebhookDispatcher {
+    /// Spawns the worker, needs a Tokio runtime. `allowed_hosts` may be reached even if they aren't public.
+    pub fn start(storage: Arc<dyn Storage>, policy: RetryPolicy, allowed_hosts: Vec<String>) -> Self {
+        let allowed_hosts: Arc<[String]> = allowed_hosts.into();
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .dns_resolver(Arc::new(PublicResolver { allowed_hosts: allowed_hosts.clone() }))
+            .build()
+            .unwrap_or_default();
+
+        tokio::spawn(run_worker(storage, client, policy, allowed_hosts.clone(), receiver));
+        Self { sender: Some(sender), allowed_hosts }
+    }
+
+    /// Drops every event
+    #[cfg(test)]
+    pub fn disabled() -> Self {
+        Self { sender: None, allowed_hosts: Arc::new([]) }
+    }
+
+    /// Refuses webhook URLs pointing at the server's own network, unless their host is allowed
+    pub async fn check_destination(&self, url: &str) -> Result<(), String> {
+        check_destination(url, &self.allowed_hosts).await
+    }
+
+    /// Call once the content is stored
+    pub fn content_created(&self, channel: &ChannelRecord, content: &ContentRecord) {
+        let payload = serde_json::json!({
+            "event": WebhookEvent::ContentCreated,
+            "channel": {
+                "id": channel.id,
+                "name": channel.name,
+            },
+            "content": {
+                "id": content.id,
+                "name": content.name,
+                "fps": content.fps,
+                "visibility": content.visibility,
+                "publish_at": content.publish_at,
+                "created_at": content.created_at,
+            },
+        });
+
+        self.enqueue(Job {
+            channel_id: channel.id,
+            event: WebhookEvent::ContentCreated,
+            payload: payload.to_string(),
+        });
+    }
+
+    fn enqueue(&self, job: Job) {
+        if let Some(sender) = &self.sender
+            && sender.send(job).is_err()
+        {
+            tracing::warn!("Webhook worker is not running, event dropped");
+        }
+    }
+}
+
+async fn run_worker(
+    storage: Arc<dyn Storage>,
+    client: reqwest::Client,
+    policy: RetryPolicy,
+    allowed_hosts: Arc<[String]>,
+    mut receiver: mpsc::UnboundedReceiver<Job>,
+) {
+    while let Some(job) = receiver.recv().await {
+        let webhooks = match storage.list_webhooks(job.channel_id).await {
+            Ok(webhooks) => webhooks,
+            Err(e) => {
+                tracing::warn!("Failed to load webhooks of channel {}: {e}", job.channel_id);
+                continue;
+            }
+        };
+
+        let payload: Arc<str> = job.payload.into();
+        for webhook in webhooks.into_iter().filter(|webhook| webhook.events.contains(&job.event)) {
+            let delivery = match storage.create_delivery(webhook.id, job.event, chrono::Utc::now().timestamp()).await {
+                Ok(delivery) => delivery,
+                Err(e) => {
+                    tracing::warn!("Failed to log delivery for webhook {}: {e}", webhook.id);
+                    continue;
+                }
+            };
+
+            // Each delivery retries on its own schedule, a slow receiver doesn't hold back the others
+            let delivery = Delivery { id: delivery.id, event: job.event, payload: payload.clone() };
+            tokio::spawn(deliver(storage.clone(), client.clone(), policy, allowed_hosts.clone(), webhook, delivery));
+        }
+    }
+}
+
+/// What to do after an attempt
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Delivered(u16),
+    /// Worth another try: network errors, timeouts, rate limits and server errors
+    Retry(Option<u16>, String),
+    /// The receiver refused the request, trying again won't help
+    Rejected(u16, String),
+    /// The URL points somewhere webhooks may not go, nothing was sent
+    Refused(String),
+}
+
+/// Only the status is kept, receivers' responses never end up in the delivery log
+fn outcome(status: reqwest::StatusCode) -> Outcome {
+    let code = status.as_u16();
+    let error = || format!("{code} {}", status.canonical_reason().unwrap_or("Unknown status"));
+
+    if status.is_success() {
+        Outcome::Delivered(code)
+    } else if status.is_server_error()
+        || status == reqwest::StatusCode::REQUEST_TIMEOUT
+        || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+    {
+        Outcome::Retry(Some(code), error())
+    } else {
+        Outcome::Rejected(code, error())
+    }
+}
+
+/// One event on its way to one webhook
+struct Delivery {
+    id: i64,
+    event: WebhookEvent,
+    payload: Arc<str>,
+}
+
+async fn attempt(
+    client: &reqwest::Client,
+    allowed_hosts: &[String],
+    webhook: &WebhookRecord,
+    delivery: &Delivery,
+    signature: &str,
+) -> Outcome {
+    // Checked again on every attempt, DNS may have changed since the webhook was added
+    if let Err(e) = check_destination(&webhook.url, allowed_hosts).await {
+        return Outcome::Refused(e);
+    }
+
+    let response = client
+        .post(&webhook.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(HEADER_WEBHOOK_EVENT, delivery.event.as_str())
+        .header(HEADER_WEBHOOK_DELIVERY, delivery.id.to_string())
+        .header(HEADER_WEBHOOK_SIGNATURE, signature)
+        .body(delivery.payload.to_string())
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => outcome(response.status()),
+        Err(e) => Outcome::Retry(None, format!("Request failed: {e}")),
+    }
+}
+
+async fn deliver(
+    storage: Arc<dyn Storage>,
+    client: reqwest::Client,
+    policy: RetryPolicy,
+    allowed_hosts: Arc<[String]>,
+    webhook: WebhookRecord,
+    delivery: Delivery,
+) {
+    let delivery_id = delivery.id;
+    let signature = signing::webhook_signature(&webhook.secret, delivery.payload.as_bytes());
+
+    for attempts in 1..=policy.max_attempts {
+        let outcome = attempt(&client, &allowed_hosts, &webhook, &delivery, &signature).await;
+
+        let (status, response_status, error) = match outcome {
+            Outcome::Delivered(code) => (DeliveryStatus::Succeeded, Some(code), None),
+            Outcome::Rejected(code, error) => (DeliveryStatus::Failed, Some(code), Some(error)),
+            Outcome::Refused(error) => (DeliveryStatus::Failed, None, Some(error)),
+            Outcome::Retry(code, error) if attempts == policy.max_attempts => (DeliveryStatus::Failed, code, Some(error)),
+            Outcome::Retry(code, error) => (DeliveryStatus::Pending, code, Some(error)),
+        };
+
+        let update = DeliveryUpdate {
+            status,
+            attempts,
+            response_status,
+            error,
+            updated_at: chrono::Utc::now().timestamp(),
+        };
+        if let Err(e) = storage.update_delivery(delivery_id, &update).await {
+            tracing::warn!("Failed to update delivery {delivery_id}: {e}");
+        }
+
+        if status != DeliveryStatus::Pending {
+            tracing::info!("Webhook delivery {delivery_id} to {} {} after {attempts} attempt(s)", webhook.url, status.as_str());
+            return;
+        }
+
+        tokio::time::sleep(policy.delay_after(attempts)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{ChannelStore, MemoryStorage, NewWebhook, WebhookStore};
+    use axum::{body::Bytes, extract::State, http::{HeaderMap, StatusCode}, routing::post, Router};
+    use std::sync::Mutex;
+
+    /// Requests seen by the receiver, and the statuses it answers with, in order
+    #[derive(Clone, Default)]
+    struct Receiver {
+        requests: Arc<Mutex<Vec<(HeaderMap, Bytes)>>>,
+        responses: Arc<Mutex<Vec<StatusCode>>>,
+    }
+
+    async fn receive(State(receiver): State<Receiver>, headers: HeaderMap, body: Bytes) -> StatusCode {
+        receiver.requests.lock().unwrap().push((headers, body));
+        let mut responses = receiver.responses.lock().unwrap();
+        if responses.is_empty() { StatusCode::OK } else { responses.remove(0) }
+    }
+
+    /// Local HTTP server standing in for a team chat or a CI, returns its URL
+    async fn start_receiver(responses: Vec<StatusCode>) -> (Receiver, String) {
+        let receiver = Receiver { responses: Arc::new(Mutex::new(responses)), ..Default::default() };
+        let app = Router::new().route("/hook", post(receive)).with_state(receiver.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (receiver, url)
+    }
+
+    async fn setup(url: &str) -> (Arc<MemoryStorage>, ChannelRecord) {
+        let storage = Arc::new(MemoryStorage::new());
+        let channel = storage.create_channel("neko", "hash", 1).await.unwrap();
+        storage.create_webhook(&NewWebhook {
+            channel_id: channel.id,
+            url: url.to_string(),
+            secret: "s3cret".to_string(),
+            events: vec![WebhookEvent::ContentCreated],
+            created_at: 1,
+        }).await.unwrap();
+        (storage, channel)
+    }
+
+    fn content() -> ContentRecord {
+        ContentRecord { id: 7, channel_id: 1, name: "nap".to_string(), fps: 10.0, created_at: 2, ..Default::default() }
+    }
+
+    /// The test receivers listen on loopback
+    fn local() -> Vec<String> {
+        vec!["127.0.0.1".to_string()]
+    }
+
+    fn fast_retries() -> RetryPolicy {
+        RetryPolicy { max_attempts: 3, base_delay: Duration::from_millis(10) }
+    }
+
+    /// Waits for the delivery to settle, the worker runs in the background
+    async fn settled_delivery(storage: &MemoryStorage, channel_id: i64) -> crate::storage::DeliveryRecord {
+        for _ in 0..200 {
+            let deliveries = storage.list_deliveries(channel_id, 10).await.unwrap();
+            if let Some(delivery) = deliveries.first()
+                && delivery.status != DeliveryStatus::Pending
+            {
+                return delivery.clone();
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("Delivery did not settle");
+    }
+
+    #[test]
+    fn test_outcome() {
+        assert_eq!(outcome(reqwest::StatusCode::NO_CONTENT), Outcome::Delivered(204));
+        assert!(matches!(outcome(reqwest::StatusCode::BAD_GATEWAY), Outcome::Retry(Some(502), _)));
+        assert!(matches!(outcome(reqwest::StatusCode::TOO_MANY_REQUESTS), Outcome::Retry(Some(429), _)));
+        assert_eq!(outcome(reqwest::StatusCode::NOT_FOUND), Outcome::Rejected(404, "404 Not Found".to_string()));
+        assert!(matches!(outcome(reqwest::StatusCode::FOUND), Outcome::Rejected(302, _)));
+    }
+
+    #[test]
+    fn test_private_addresses_are_not_public() {
+        for ip in ["127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "0.0.0.0", "100.64.0.1", "::1", "::", "fd00::1", "fe80::1", "::ffff:127.0.0.1", "64:ff9b::7f00:1", "64:ff9b::a9fe:a9fe", "2002:c0a8:101::1"] {
+            assert!(!is_public(ip.parse().unwrap()), "{ip}");
+        }
+        for ip in ["93.184.215.14", "1.1.1.1", "2606:4700:4700::1111", "64:ff9b::101:101", "2002:101:101::1"] {
+            assert!(is_public(ip.parse().unwrap()), "{ip}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_destination() {
+        assert!(check_destination("http://127.0.0.1:8080/hook", &[]).await.is_err());
+        assert!(check_destination("http://169.254.169.254/latest/meta-data", &[]).await.is_err());
+        assert!(check_destination("http://[::1]/hook", &[]).await.is_err());
+        assert!(check_destination("https://93.184.215.14/hook", &[]).await.is_ok());
+        assert!(check_destination("http://127.0.0.1:8080/hook", &["127.0.0.1".to_string()]).await.is_ok());
+    }
+
+    #[test]
+    fn test_backoff_doubles() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.delay_after(1), Duration::from_secs(2));
+        assert_eq!(policy.delay_after(2), Duration::from_secs(4));
+        assert_eq!(policy.delay_after(4), Duration::from_secs(16));
+    }
+
+    #[tokio::test]
+    async fn test_delivery_is_signed() {
+        let (receiver, url) = start_receiver(vec![]).await;
+        let (storage, channel) = setup(&url).await;
+
+        let dispatcher = WebhookDispatcher::start(storage.clone(), fast_retries(), local());
+        dispatcher.content_created(&channel, &content());
+
+        let delivery = settled_delivery(&storage, channel.id).await;
+        assert_eq!((delivery.status, delivery.attempts, delivery.response_status), (DeliveryStatus::Succeeded, 1, Some(200)));
+
+        let requests = receiver.requests.lock().unwrap();
+        let (headers, body) = &requests[0];
+        assert_eq!(headers[HEADER_WEBHOOK_EVENT], "content_created");
+        assert_eq!(headers[HEADER_WEBHOOK_DELIVERY], delivery.id.to_string().as_str());
+        assert_eq!(headers[HEADER_WEBHOOK_SIGNATURE], signing::webhook_signature("s3cret", body).as_str());
+
+        let payload: serde_json::Value = serde_json::from_slice(body).unwrap();
+        assert_eq!(payload["channel"]["name"], "neko");
+        assert_eq!(payload["content"]["id"], 7);
+        assert_eq!(payload["content"]["visibility"], "public");
+    }
+
+    #[tokio::test]
+    async fn test_failed_attempts_are_retried() {
+        let (receiver, url) = start_receiver(vec![StatusCode::INTERNAL_SERVER_ERROR, StatusCode::SERVICE_UNAVAILABLE]).await;
+        let (storage, channel) = setup(&url).await;
+
+        let dispatcher = WebhookDispatcher::start(storage.clone(), fast_retries(), local());
+        dispatcher.content_created(&channel, &content());
+
+        let delivery = settled_delivery(&storage, channel.id).await;
+        assert_eq!((delivery.status, delivery.attempts, delivery.response_status), (DeliveryStatus::Succeeded, 3, Some(200)));
+        assert_eq!(delivery.error, None);
+        assert_eq!(receiver.requests.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_and_unreachable_deliveries_fail() {
+        let (receiver, url) = start_receiver(vec![StatusCode::GONE]).await;
+        let (storage, channel) = setup(&url).await;
+
+        let dispatcher = WebhookDispatcher::start(storage.clone(), fast_retries(), local());
+        dispatcher.content_created(&channel, &content());
+
+        let delivery = settled_delivery(&storage, channel.id).await;
+        assert_eq!((delivery.status, delivery.attempts, delivery.response_status), (DeliveryStatus::Failed, 1, Some(410)));
+        assert_eq!(receiver.requests.lock().unwrap().len(), 1);
+
+        // Nothing listens on the port a dropped listener was bound to
+        let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+        let (storage, channel) = setup(&format!("http://{closed}/hook")).await;
+
+        let dispatcher = WebhookDispatcher::start(storage.clone(), fast_retries(), local());
+        dispatcher.content_created(&channel, &content());
+
+        let delivery = settled_delivery(&storage, channel.id).await;
+        assert_eq!((delivery.status, delivery.attempts, delivery.response_status), (DeliveryStatus::Failed, 3, None));
+        assert!(delivery.error.unwrap().starts_with("Request failed"));
+    }
+
+    #[tokio::test]
+    async fn test_private_receivers_are_refused() {
+        let (receiver, url) = start_receiver(vec![]).await;
+        let (storage, channel) = setup(&url).await;
+
+        let dispatcher = WebhookDispatcher::start(storage.clone(), fast_retries(), vec![]);
+        dispatcher.content_created(&channel, &content());
+
+        let delivery = settled_delivery(&storage, channel.id).await;
+        assert_eq!((delivery.status, delivery.attempts, delivery.response_status), (DeliveryStatus::Failed, 1, None));
+        assert!(delivery.error.unwrap().contains("not a public address"));
+        assert!(receiver.requests.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,140 @@
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use hmac::{Hmac, Mac};
+use rsa::pkcs8::{DecodePrivateKey, DecodePublicKey, EncodePrivateKey, EncodePublicKey, LineEnding};
+use rsa::traits::PublicKeyParts;
+use rsa::{Pkcs1v15Sign, RsaPrivateKey, RsaPublicKey};
+use sha2::{Digest, Sha256};
+
+/// Size of the keys `tama auth` generates, and the minimum servers accept
+pub const KEY_BITS: usize = 2048;
+
+const DIGEST_DOMAIN: &[u8] = b"tama-content-v1";
+
+/// Outcome of checking a content's signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verification {
+    Verified,
+    Unsigned,
+    /// Signed, but the signature doesn't match the content or the key is unusable
+    Invalid,
+    /// Signed, but there is no trusted key to check the signature against
+    Untrusted,
+}
+
+impl Verification {
+    pub fn is_verified(&self) -> bool {
+        matches!(self, Verification::Verified)
+    }
+}
+
+/// SHA-256 of what makes a content play the way it does. Every field is length prefixed
+/// and fps is hashed by its bits, so the digest survives JSON round trips unchanged.
+pub fn content_digest(art: &str, midi_composition: &str, fps: f32) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(DIGEST_DOMAIN);
+    for field in [art.as_bytes(), midi_composition.as_bytes()] {
+        hasher.update((field.len() as u64).to_be_bytes());
+        hasher.update(field);
+    }
+    hasher.update(fps.to_bits().to_be_bytes());
+    hasher.finalize().into()
+}
+
+pub fn generate_private_key() -> Result<RsaPrivateKey, String> {
+    RsaPrivateKey::new(&mut rand::thread_rng(), KEY_BITS)
+        .map_err(|e| format!("Failed to generate key: {e}"))
+}
+
+pub fn private_key_to_pem(private_key: &RsaPrivateKey) -> Result<String, String> {
+    private_key
+        .to_pkcs8_pem(LineEnding::LF)
+        .map(|pem| pem.to_string())
+        .map_err(|e| format!("Failed to encode private key: {e}"))
+}
+
+pub fn private_key_from_pem(pem: &str) -> Result<RsaPrivateKey, String> {
+    RsaPrivateKey::from_pkcs8_pem(pem).map_err(|e| format!("Invalid private key: {e}"))
+}
+
+pub fn public_key_to_pem(private_key: &RsaPrivateKey) -> Result<String, String> {
+    RsaPublicKey::from(private_key)
+        .to_public_key_pem(LineEnding::LF)
+        .map_err(|e| format!("Failed to encode public key: {e}"))
+}
+
+/// Parses a SPKI PEM public key, rejecting keys smaller than `KEY_BITS`
+pub fn public_key_from_pem(pem: &str) -> Result<RsaPublicKey, String> {
+    let public_key = RsaPublicKey::from_public_key_pem(pem.trim())
+        .map_err(|e| format!("Invalid public key: {e}"))?;
+
+    if public_key.size() * 8 < KEY_BITS {
+        return Err(format!("Public key is too small, at least {KEY_BITS} bits are required"));
+    }
+
+    Ok(public_key)
+}
+
+/// Base64 PKCS#1 v1.5 signature of the content digest
+pub fn sign_content(private_key: &RsaPrivateKey, art: &str, midi_composition: &str, fps: f32) -> Result<String, String> {
+    let digest = content_digest(art, midi_composition, fps);
+    let signature = private_key
+        .sign(Pkcs1v15Sign::new::<Sha256>(), &digest)
+        .map_err(|e| format!("Failed to sign content: {e}"))?;
+    Ok(BASE64.encode(signature))
+}
+
+pub fn verify_content(public_key: &RsaPublicKey, art: &str, midi_composition: &str, fps: f32, signature: &str) -> bool {
+    let Ok(signature) = BASE64.decode(signature) else {
+        return false;
+    };
+    let digest = content_digest(art, midi_composition, fps);
+    public_key.verify(Pkcs1v15Sign::new::<Sha256>(), &digest, &signature).is_ok()
+}
+
+/// Value of the `X-Tama-Signature` header of a webhook request: `sha256=` followed by
+/// the hex HMAC of the raw body, keyed with the secret given when registering the webhook
+pub fn webhook_signature(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any length");
+    mac.update(body);
+    let hex: String = mac.finalize().into_bytes().iter().map(|b| format!("{b:02x}")).collect();
+    format!("sha256={hex}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digest_depends_on_every_field() {
+        let digest = content_digest("art", "4c", 10.0);
+        assert_eq!(digest, content_digest("art", "4c", 10.0));
+        assert_ne!(digest, content_digest("art", "4d", 10.0));
+        assert_ne!(digest, content_digest("art", "4c", 12.0));
+        // Length prefixes keep the field boundary from moving
+        assert_ne!(content_digest("ab", "c", 1.0), content_digest("a", "bc", 1.0));
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let private_key = generate_private_key().unwrap();
+        let pem = public_key_to_pem(&private_key).unwrap();
+        let public_key = public_key_from_pem(&pem).unwrap();
+
+        let signature = sign_content(&private_key, "art", "4c 4e", 0.1).unwrap();
+        assert!(verify_content(&public_key, "art", "4c 4e", 0.1, &signature));
+        assert!(!verify_content(&public_key, "art", "4c 4f", 0.1, &signature));
+        assert!(!verify_content(&public_key, "art", "4c 4e", 0.1, "not base64!"));
+
+        let restored = private_key_from_pem(&private_key_to_pem(&private_key).unwrap()).unwrap();
+        assert_eq!(restored, private_key);
+    }
+
+    #[test]
+    fn test_webhook_signature() {
+        // RFC 4231, test case 2
+        assert_eq!(
+            webhook_signature("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
@@ -0,0 +1,142 @@
+use serde::Serialize;
+use tama::ascii_art_converter::AsciiArtSheet;
+use tama::client::{ChannelInfo, ContentData};
+use tama::midi_composer::MidiEngine;
+
+const HEADERS: [&str; 8] = ["CHANNEL", "CHANNEL NAME", "CONTENT", "NAME", "FPS", "FRAMES", "NOTES", "SERVER"];
+
+/// A content as `tama feed`, `tama channel` and `tama content` print it
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ContentRow {
+    /// Unknown for contents only ever fetched by id
+    pub channel_id: Option<i64>,
+    pub channel_name: Option<String>,
+    pub content_id: i64,
+    /// Empty when the server doesn't send content names
+    pub name: String,
+    pub fps: f32,
+    /// Unknown when the art doesn't parse
+    pub frames: Option<usize>,
+    /// Notes played, rests excluded. Unknown when the composition doesn't parse.
+    pub notes: Option<usize>,
+    pub server: String,
+}
+
+impl ContentRow {
+    pub fn new(server_url: &str, channel: Option<&ChannelInfo>, content: &ContentData) -> Self {
+        let frames = AsciiArtSheet::from_string(&content.art)
+            .map(|sheet| sheet.frame_count())
+            .ok();
+
+        let notes = MidiEngine::without_audio(120)
+            .parse_composition(&content.midi_composition)
+            .map(|channels| channels.iter().flatten().filter(|note| note.pitch.is_some()).count())
+            .ok();
+
+        Self {
+            channel_id: channel.map(|channel| channel.id),
+            channel_name: channel.map(|channel| channel.name.clone()),
+            content_id: content.id,
+            name: content.name.clone(),
+            fps: content.fps,
+            frames,
+            notes,
+            server: server_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    fn cells(&self) -> [String; 8] {
+        let unknown = || "-".to_string();
+        [
+            self.channel_id.map_or_else(unknown, |id| id.to_string()),
+            self.channel_name.clone().unwrap_or_else(unknown),
+            self.content_id.to_string(),
+            if self.name.is_empty() { unknown() } else { self.name.clone() },
+            self.fps.to_string(),
+            self.frames.map_or_else(unknown, |frames| frames.to_string()),
+            self.notes.map_or_else(unknown, |notes| notes.to_string()),
+            self.server.clone(),
+        ]
+    }
+}
+
+/// Rows aligned in columns under a header line
+pub fn format_table(rows: &[ContentRow]) -> String {
+    let header = HEADERS.map(str::to_string);
+    let cells: Vec<[String; 8]> = rows.iter().map(ContentRow::cells).collect();
+
+    let mut widths = HEADERS.map(str::len);
+    for row in &cells {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    std::iter::once(&header)
+        .chain(&cells)
+        .map(|row| {
+            row.iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{cell:<width$}"))
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn content(art: &str, midi_composition: &str) -> ContentData {
+        ContentData {
+            id: 42,
+            name: "nap".to_string(),
+            art: art.to_string(),
+            midi_composition: midi_composition.to_string(),
+            fps: 12.5,
+            signature: None,
+            public_key: None,
+            origin: None,
+        }
+    }
+
+    #[test]
+    fn test_content_row() {
+        let channel = ChannelInfo { id: 7, name: "neko".to_string() };
+        let animated = content("Ascii Art Animation, 2x1\n⠁⠁\n⠂⠂\n⠃⠃", "4c 4- 4e 4g");
+
+        let row = ContentRow::new("https://tama.example/", Some(&channel), &animated);
+        assert_eq!(row.channel_id, Some(7));
+        assert_eq!(row.channel_name.as_deref(), Some("neko"));
+        assert_eq!((row.frames, row.notes), (Some(3), Some(3)));
+        assert_eq!(row.server, "https://tama.example");
+
+        let broken = ContentRow::new("https://tama.example", None, &content("not art", "4x"));
+        assert_eq!((broken.channel_id, broken.frames, broken.notes), (None, None, None));
+
+        let json = serde_json::to_value(&row).unwrap();
+        assert_eq!(json["content_id"], 42);
+        assert_eq!(json["channel_name"], "neko");
+        assert_eq!(json["name"], "nap");
+    }
+
+    #[test]
+    fn test_format_table() {
+        let channel = ChannelInfo { id: 7, name: "hiddenmugs".to_string() };
+        let unnamed = ContentData { name: String::new(), ..content("Ascii Art Animation, 2x1\n⠁⠁", "4c") };
+        let rows = vec![
+            ContentRow::new("http://localhost:3000", Some(&channel), &content("Ascii Art Animation, 2x1\n⠁⠁", "4c")),
+            ContentRow::new("https://tama.example", None, &unnamed),
+        ];
+
+        assert_eq!(format_table(&rows), [
+            "CHANNEL  CHANNEL NAME  CONTENT  NAME  FPS   FRAMES  NOTES  SERVER",
+            "7        hiddenmugs    42       nap   12.5  1       1      http://localhost:3000",
+            "-        -             42       -     12.5  1       1      https://tama.example",
+        ].join("\n"));
+    }
+}
@@ -2,15 +2,29 @@ use arboard::Clipboard;
 use clap::{Parser, Subcommand};
 use crossterm::event::{self, KeyCode};
 use std::io;
+use std::sync::mpsc;
 use std::thread;
 use std::time::Duration;
 
+use tama::api::{
+    AuthEventKind, AuthResponse, ChannelProfile, CommentInfo, CreateContentRequest, CreateWebhookRequest, DeliveryStatus, Role,
+    UpdateProfileRequest, Visibility, WebhookEvent,
+};
 use tama::ascii_art_converter::AsciiArtSheet;
-use tama::channel::{Channel, FeedItem, FeedManager};
-use tama::client::{auth_config::AuthConfig, config::TamaConfig, ApiClient};
+use tama::channel::{Channel, FeedItem, FeedManager, Verification};
+use tama::client::{http, paths};
+use tama::client::cache::{self, CachedContent, ContentCache};
+use tama::client::auth_config::{AuthConfig, AuthProfiles, Expiry, DEFAULT_PROFILE};
+use tama::client::federation::{FederatedFeed, FetchStatus, ServerStatus};
+use tama::client::{config::TamaConfig, keys, trusted_keys::TrustedKeys, ApiClient, BroadcastNow, ChannelInfo as ApiChannelInfo, FeedItem as ApiFeedItem};
 use tama::content_parser;
-use tama::midi_composer::MidiEngine;
-use tama::ui::{LoadingAnimation, RemoteAnimation, UI};
+use tama::midi_composer::{self, MidiEngine};
+use tama::signing;
+use tama::ui::{CommentPane, LoadingAnimation, PaneAction, RemoteAnimation, UI};
+
+mod listing;
+
+use listing::ContentRow;
 
 #[derive(Parser)]
 #[command(name = "tama")]
@@ -19,6 +33,12 @@ struct Cli {
     #[arg(help = "Endpoint path (e.g., /content/2 or /channel/hiddenmugs)")]
     endpoint: Option<String>,
 
+    #[arg(long, conflicts_with = "endpoint", help = "Watch the server's broadcast, in sync with everyone else tuned in")]
+    live: bool,
+
+    #[arg(long, global = true, help = "Saved login to use (defaults to the last one authenticated)")]
+    profile: Option<String>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -26,16 +46,141 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     #[command(about = "Authenticate (login or create account)")]
-    Auth,
+    Auth {
+        #[arg(long, help = "Server to log in to (defaults to SERVER_URL)")]
+        server: Option<String>,
+    },
+    #[command(about = "List saved logins and when they expire")]
+    Whoami,
     #[command(about = "Upload content to your channel")]
-    Upload { file_path: String },
+    Upload {
+        file_path: String,
+        #[arg(long, help = "Hide from the feed and the channel, reachable by id or share link")]
+        unlisted: bool,
+        #[arg(long, conflicts_with = "unlisted", help = "Only visible to your channel")]
+        private: bool,
+        #[arg(long, value_name = "TIME", help = "Publish later: RFC 3339, 'YYYY-MM-DD HH:MM' local time or relative like +2h")]
+        at: Option<String>,
+    },
     #[command(about = "Preview local content file")]
     Preview { file_path: String },
+    #[command(about = "Download a content as a content file (e.g. 42, /content/42 or a full URL)")]
+    Download {
+        content_ref: String,
+        #[arg(short, long, help = "Output file (defaults to content_<id>.txt)")]
+        output: Option<String>,
+    },
+    #[command(about = "Show or update your channel profile")]
+    Profile {
+        #[arg(long, help = "Name shown instead of the channel name (empty to clear)")]
+        display_name: Option<String>,
+        #[arg(long, help = "Short description, up to 280 characters (empty to clear)")]
+        bio: Option<String>,
+        #[arg(long, help = "Single-frame braille art file, at most 16x4 (empty to clear)")]
+        avatar: Option<String>,
+        #[arg(long = "link", help = "Link to show on the profile, repeat for more (replaces current links)")]
+        links: Vec<String>,
+        #[arg(long, help = "Remove all links", conflicts_with = "links")]
+        clear_links: bool,
+    },
+    #[command(about = "Create a single-use invite to your channel (owners only)")]
+    Invite {
+        #[arg(long, default_value = "editor", value_parser = parse_role, help = "owner, editor or viewer")]
+        role: Role,
+    },
+    #[command(about = "Join a channel with an invite token, as a user rather than with the channel password")]
+    Join {
+        token: String,
+        #[arg(long, help = "Create the user first")]
+        new_user: bool,
+        #[arg(long, help = "Server that issued the invite (defaults to SERVER_URL)")]
+        server: Option<String>,
+    },
+    #[command(about = "List the members of your channel")]
+    Members,
+    #[command(about = "Account security")]
+    Account {
+        #[command(subcommand)]
+        command: AccountCommand,
+    },
+    #[command(about = "Notify other services when your channel uploads (owners only)")]
+    Webhook {
+        #[command(subcommand)]
+        command: WebhookCommand,
+    },
+    #[command(about = "Print the feed of every known server, without the TUI")]
+    Feed {
+        #[arg(long, help = "Print JSON instead of a table")]
+        json: bool,
+    },
+    #[command(about = "Print the contents of a channel (e.g. neko, 7, /channel/neko or a full URL)")]
+    Channel {
+        channel_ref: String,
+        #[arg(long, help = "Print JSON instead of a table")]
+        json: bool,
+    },
+    #[command(about = "Print a content (e.g. 42, /content/42 or a full URL)")]
+    Content {
+        content_ref: String,
+        #[arg(long, help = "Print JSON instead of a table")]
+        json: bool,
+    },
+    #[command(about = "Manage the copies of fetched contents played when offline")]
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+    #[command(about = "Render the composition of a local content file to WAV")]
+    Render {
+        file_path: String,
+        #[arg(short, long, help = "Output file (defaults to the content file name with .wav)")]
+        output: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum AccountCommand {
+    #[command(about = "Show recent logins, failed attempts and lockouts of your channel")]
+    Log {
+        #[arg(short = 'n', long, default_value_t = 20, help = "Number of events to show")]
+        limit: i64,
+    },
+}
+
+#[derive(Subcommand)]
+enum WebhookCommand {
+    #[command(about = "Register a URL to POST signed JSON events to")]
+    Add {
+        url: String,
+        #[arg(long, help = "Key of the X-Tama-Signature HMAC (generated if omitted)")]
+        secret: Option<String>,
+        #[arg(long = "event", default_value = "content_created", value_parser = parse_webhook_event, help = "Event to send, repeat for more")]
+        events: Vec<WebhookEvent>,
+    },
+    #[command(about = "List the webhooks of your channel")]
+    List,
+    #[command(about = "Remove a webhook and its delivery log")]
+    Remove { webhook_id: i64 },
+    #[command(about = "Show recent deliveries and their outcome")]
+    Log {
+        #[arg(short = 'n', long, default_value_t = 20, help = "Number of deliveries to show")]
+        limit: i64,
+    },
+}
+
+#[derive(Subcommand)]
+enum CacheCommand {
+    #[command(about = "List cached contents, most recently used first")]
+    Ls,
+    #[command(about = "Remove every cached content")]
+    Clear,
 }
 
 enum EndpointType {
     Content(i64),
     Channel(String),
+    /// Unlisted content, by share token
+    Shared(String),
 }
 
 fn add_protocol_if_missing(endpoint: &str) -> String {
@@ -50,7 +195,7 @@ fn add_protocol_if_missing(endpoint: &str) -> String {
     }
 
     // Check if it's a relative path without leading slash (content/... or channel/...)
-    if endpoint.starts_with("content/") || endpoint.starts_with("channel/") {
+    if endpoint.starts_with("content/") || endpoint.starts_with("channel/") || endpoint.starts_with("share/") {
         return endpoint.to_string();
     }
 
@@ -87,7 +232,8 @@ fn parse_endpoint(endpoint: &str) -> Result<(Option<String>, EndpointType), Stri
         // url_parts[3] = "endpoint/path"
 
         let server_url = format!("{}//{}", url_parts[0], url_parts[2]);
-        let path = url_parts[3];
+        // Links to the web player (`/view/content/<id>`) point at the same content
+        let path = url_parts[3].strip_prefix("view/").unwrap_or(url_parts[3]);
 
         let parts: Vec<&str> = path.split('/').collect();
 
@@ -104,13 +250,18 @@ fn parse_endpoint(endpoint: &str) -> Result<(Option<String>, EndpointType), Stri
             "channel" => {
                 EndpointType::Channel(parts[1].to_string())
             }
-            _ => return Err(format!("Unknown endpoint type: {}. Expected 'content' or 'channel'", parts[0]))
+            "share" => {
+                EndpointType::Shared(parts[1].to_string())
+            }
+            _ => return Err(format!("Unknown endpoint type: {}. Expected 'content', 'channel' or 'share'", parts[0]))
         };
 
         Ok((Some(server_url), endpoint_type))
     } else {
         // Parse as relative path
-        let parts: Vec<&str> = endpoint_with_protocol.trim_start_matches('/').split('/').collect();
+        let path = endpoint_with_protocol.trim_start_matches('/');
+        let path = path.strip_prefix("view/").unwrap_or(path);
+        let parts: Vec<&str> = path.split('/').collect();
 
         if parts.len() != 2 {
             return Err(format!("Invalid endpoint format: {endpoint}. Expected /content/<id> or /channel/<identifier>"));
@@ -125,7 +276,10 @@ fn parse_endpoint(endpoint: &str) -> Result<(Option<String>, EndpointType), Stri
             "channel" => {
                 EndpointType::Channel(parts[1].to_string())
             }
-            _ => return Err(format!("Unknown endpoint type: {}. Expected 'content' or 'channel'", parts[0]))
+            "share" => {
+                EndpointType::Shared(parts[1].to_string())
+            }
+            _ => return Err(format!("Unknown endpoint type: {}. Expected 'content', 'channel' or 'share'", parts[0]))
         };
 
         Ok((None, endpoint_type))
@@ -136,6 +290,7 @@ enum PlayMode {
     Feed(FeedManager),
     Channel(FeedManager),
     SingleContent(Channel),
+    Live(BroadcastNow),
 }
 
 #[tokio::main]
@@ -147,17 +302,80 @@ async fn main() -> io::Result<()> {
     let server_url = std::env::var("SERVER_URL")
         .unwrap_or_else(|_| "http://localhost:3000".to_string());
 
+    migrate_legacy_files(&server_url);
+
+    // Timeouts and retries apply to every command, so set them up before any request
+    if let Ok(config) = TamaConfig::load() {
+        http::configure(&config.http).map_err(io::Error::other)?;
+    }
+
+    let profile = cli.profile.as_deref();
+
     // Handle non-UI commands first
     match &cli.command {
-        Some(Commands::Auth) => {
-            return handle_auth(&server_url).await;
+        Some(Commands::Auth { server }) => {
+            let server = server.as_deref().map(|server| add_protocol_if_missing(server).trim_end_matches('/').to_string());
+            return handle_auth(&server_url, server, profile).await;
+        }
+        Some(Commands::Whoami) => {
+            return handle_whoami(&server_url);
         }
-        Some(Commands::Upload { file_path }) => {
-            return handle_upload(&server_url, file_path).await;
+        Some(Commands::Upload { file_path, unlisted, private, at }) => {
+            let visibility = if *unlisted {
+                Visibility::Unlisted
+            } else if *private {
+                Visibility::Private
+            } else {
+                Visibility::Public
+            };
+            let publish_at = match at.as_deref().map(|at| parse_publish_at(at, chrono::Local::now())) {
+                Some(Ok(publish_at)) => Some(publish_at),
+                Some(Err(e)) => return Err(io::Error::other(e)),
+                None => None,
+            };
+            return handle_upload(&server_url, profile, file_path, visibility, publish_at).await;
         }
         Some(Commands::Preview { file_path }) => {
             return handle_preview(file_path).await;
         }
+        Some(Commands::Download { content_ref, output }) => {
+            return handle_download(&server_url, content_ref, output.as_deref()).await;
+        }
+        Some(Commands::Render { file_path, output }) => {
+            return handle_render(file_path, output.as_deref());
+        }
+        Some(Commands::Invite { role }) => {
+            return handle_invite(&server_url, profile, *role).await;
+        }
+        Some(Commands::Join { token, new_user, server }) => {
+            let server_url = server.as_deref().map_or(server_url.clone(), |server| add_protocol_if_missing(server).trim_end_matches('/').to_string());
+            return handle_join(&server_url, profile, token, *new_user).await;
+        }
+        Some(Commands::Members) => {
+            return handle_members(&server_url, profile).await;
+        }
+        Some(Commands::Account { command: AccountCommand::Log { limit } }) => {
+            return handle_account_log(&server_url, profile, *limit).await;
+        }
+        Some(Commands::Webhook { command }) => {
+            return handle_webhook(&server_url, profile, command).await;
+        }
+        Some(Commands::Cache { command }) => {
+            return handle_cache(command);
+        }
+        Some(Commands::Feed { json }) => {
+            return handle_feed(&server_url, *json).await;
+        }
+        Some(Commands::Channel { channel_ref, json }) => {
+            return handle_channel(&server_url, channel_ref, *json).await;
+        }
+        Some(Commands::Content { content_ref, json }) => {
+            return handle_content(&server_url, content_ref, *json).await;
+        }
+        Some(Commands::Profile { display_name, bio, avatar, links, clear_links }) => {
+            let links = if *clear_links { Some(vec![]) } else { Some(links.clone()).filter(|l| !l.is_empty()) };
+            return handle_profile(&server_url, profile, display_name.clone(), bio.clone(), avatar.as_deref(), links).await;
+        }
         _ => {}
     }
 
@@ -169,46 +387,40 @@ async fn main() -> io::Result<()> {
 
     let api_client = ApiClient::new(server_url.clone());
 
-    let mut _config = if TamaConfig::config_exists() {
-        TamaConfig::load().unwrap_or_else(|_| TamaConfig {
-            server_url: server_url.clone(),
-            servers: vec![],
-            server_override: false,
-        })
-    } else {
-        TamaConfig {
-            server_url: server_url.clone(),
-            servers: vec![],
-            server_override: false,
-        }
-    };
-
-    // Try to fetch and update server list (unless server_override is true)
-    if !_config.server_override {
-        if let Ok(servers) = api_client.fetch_servers().await {
-            _config.servers = servers;
-            _config.save().ok();
-        } else if _config.servers.is_empty() {
-            _config.servers = vec![server_url.clone()];
-            _config.save().ok();
+    let mut _config = TamaConfig::load()
+        .unwrap_or_else(|_| TamaConfig::new(server_url.clone()));
+    let cache = _config.content_cache();
+    let mut trusted_keys = TrustedKeys::load().unwrap_or_else(|e| {
+        eprintln!("Warning: {e}, no channel keys are trusted yet");
+        TrustedKeys::default()
+    });
+    // Set when no server could be reached and playback starts from the cache
+    let mut offline = false;
+
+    refresh_servers(&mut _config, &api_client, &server_url).await;
+
+    let play_mode = if cli.live {
+        match api_client.fetch_broadcast_now().await {
+            Ok(now_playing) => PlayMode::Live(now_playing),
+            Err(e) => {
+                UI::cleanup()?;
+                return Err(io::Error::other(format!("Failed to tune in to the broadcast: {e}")));
+            }
         }
-    } else if _config.servers.is_empty() {
-        // If server_override is true but no servers are configured, use the main server
-        _config.servers = vec![server_url.clone()];
-    }
-
-    let play_mode = if let Some(endpoint_str) = &cli.endpoint {
+    } else if let Some(endpoint_str) = &cli.endpoint {
         let (custom_server_url, endpoint) = parse_endpoint(endpoint_str).map_err(|e| {
             UI::cleanup().ok();
             io::Error::other(e)
         })?;
 
         // Use custom server URL if provided, otherwise use default
+        // Contents from other servers are checked against their channel's signing key
+        let is_foreign = custom_server_url.as_ref().is_some_and(|url| url != &server_url);
         let endpoint_server_url = custom_server_url.unwrap_or_else(|| server_url.clone());
-        let endpoint_api_client = ApiClient::new(endpoint_server_url);
+        let endpoint_api_client = ApiClient::new(endpoint_server_url.clone()).with_cache(cache.clone());
 
         match endpoint {
-            EndpointType::Content(content_id) => {
+            endpoint @ (EndpointType::Content(_) | EndpointType::Shared(_)) => {
                 let mut loading_animation = LoadingAnimation::new(30, 9, 15.0);
                 let loading_handle = tokio::spawn(async move {
                     loop {
@@ -221,20 +433,35 @@ async fn main() -> io::Result<()> {
                     }
                 });
 
-                let content_result = endpoint_api_client.fetch_content(content_id).await;
+                let content_result = match &endpoint {
+                    EndpointType::Shared(share_token) => endpoint_api_client.fetch_shared_content(share_token).await,
+                    EndpointType::Content(content_id) => endpoint_api_client.fetch_content(*content_id).await
+                        .or_else(|e| match cache.get(&endpoint_server_url, *content_id) {
+                            Some(cached) => {
+                                offline = true;
+                                Ok(cached.content)
+                            }
+                            None => Err(e),
+                        }),
+                    EndpointType::Channel(_) => unreachable!(),
+                };
                 loading_handle.abort();
 
                 match content_result {
                     Ok(content_data) => {
+                        let verification = is_foreign.then(|| trusted_keys.verify(&endpoint_server_url, None, &content_data));
                         match Channel::new(
-                            content_id,
+                            content_data.id,
                             "Single Content".to_string(),
                             content_data.art,
                             content_data.midi_composition,
                             content_data.fps,
                             content_data.id,
                         ) {
-                            Ok(channel) => PlayMode::SingleContent(channel),
+                            Ok(channel) => {
+                                let channel = channel.with_server_url(endpoint_server_url.clone());
+                                PlayMode::SingleContent(with_verification(channel, verification))
+                            }
                             Err(e) => {
                                 UI::cleanup()?;
                                 return Err(io::Error::other(format!("Failed to create channel: {e}")));
@@ -252,9 +479,16 @@ async fn main() -> io::Result<()> {
 
                 match channel_result {
                     Ok(channel_response) => {
+                        if !show_profile_card(&channel_response.name, &channel_response.profile)? {
+                            UI::cleanup()?;
+                            return Ok(());
+                        }
+
                         let items: Result<Vec<FeedItem>, String> = channel_response.contents
                             .into_iter()
                             .map(|content| {
+                                let verification = is_foreign
+                                    .then(|| trusted_keys.verify(&endpoint_server_url, Some(&channel_response.name), &content));
                                 let channel = Channel::new(
                                     channel_response.id,
                                     channel_response.name.clone(),
@@ -262,8 +496,9 @@ async fn main() -> io::Result<()> {
                                     content.midi_composition,
                                     content.fps,
                                     content.id,
-                                )?;
-                                Ok(FeedItem { channel })
+                                )?
+                                .with_server_url(endpoint_server_url.clone());
+                                Ok(FeedItem { channel: with_verification(channel, verification) })
                             })
                             .collect();
 
@@ -276,8 +511,21 @@ async fn main() -> io::Result<()> {
                         }
                     }
                     Err(e) => {
-                        UI::cleanup()?;
-                        return Err(io::Error::other(format!("Failed to fetch channel: {e}")));
+                        let cached: Vec<_> = cache.server_entries(&endpoint_server_url)
+                            .unwrap_or_default()
+                            .into_iter()
+                            .filter(|cached| cached.channel.as_ref().is_some_and(|channel| {
+                                channel.name == channel_identifier || channel.id.to_string() == channel_identifier
+                            }))
+                            .collect();
+
+                        if cached.is_empty() {
+                            UI::cleanup()?;
+                            return Err(io::Error::other(format!("Failed to fetch channel: {e}")));
+                        }
+
+                        offline = true;
+                        PlayMode::Channel(FeedManager::new(cached_feed_items(cached, &server_url, &mut trusted_keys)))
                     }
                 }
             }
@@ -292,62 +540,57 @@ async fn main() -> io::Result<()> {
 
         println!("Fetching feed from {} server(s)...", servers.len());
 
-        let mut tasks = Vec::new();
-        for server_url in servers {
-            let server_url_clone = server_url.clone();
-            let task = tokio::spawn(async move {
-                let client = ApiClient::new(server_url_clone.clone());
-                let result = client.fetch_feed().await;
-                (server_url_clone, result)
-            });
-            tasks.push(task);
-        }
-
-        // Collect results with a timeout
-        let timeout_duration = Duration::from_secs(5);
-        let mut all_items: Vec<FeedItem> = Vec::new();
-        let mut at_least_one_success = false;
-
-        for task in tasks {
-            match tokio::time::timeout(timeout_duration, task).await {
-                Ok(Ok((server_url, Ok(feed_response)))) => {
-                    println!("✓ Received {} items from {}", feed_response.len(), server_url);
-                    let items: Result<Vec<_>, _> = feed_response
-                        .into_iter()
-                        .map(|item| FeedItem::from_api_feed_item_with_server(item, server_url.clone()))
-                        .collect();
-
-                    match items {
-                        Ok(items) => {
-                            all_items.extend(items);
-                            at_least_one_success = true;
-                        }
-                        Err(e) => {
-                            println!("✗ Failed to process items from {server_url}: {e}");
-                        }
+        let federated = FederatedFeed::new(servers)
+            .with_cache(cache.clone())
+            .fetch(&mut _config.server_stats)
+            .await;
+        _config.save().ok();
+
+        for status in &federated.statuses {
+            println!("{}", format_server_status(status));
+        }
+
+        let at_least_one_success = federated.any_reachable();
+        let mut all_items: Vec<FeedItem> = federated.items
+            .into_iter()
+            .filter_map(|federated_item| {
+                let is_foreign = federated_item.server_url != server_url;
+                let item = &federated_item.item;
+                let verification = is_foreign
+                    .then(|| trusted_keys.verify(&federated_item.server_url, Some(&item.channel.name), &item.content));
+                match FeedItem::from_api_feed_item_with_server(federated_item.item, federated_item.server_url.clone()) {
+                    Ok(feed_item) => Some(FeedItem { channel: with_verification(feed_item.channel, verification) }),
+                    Err(e) => {
+                        println!("✗ Skipped an item from {}: {e}", federated_item.server_url);
+                        None
                     }
                 }
-                Ok(Ok((server_url, Err(e)))) => {
-                    println!("✗ Failed to fetch from {server_url}: {e}");
-                }
-                Ok(Err(e)) => {
-                    println!("✗ Task error: {e}");
-                }
-                Err(_) => {
-                    println!("✗ Timeout waiting for server response");
-                }
-            }
-        }
+            })
+            .collect();
 
         if !at_least_one_success && all_items.is_empty() {
-            UI::cleanup()?;
-            return Err(io::Error::other("Failed to fetch feed from any server"));
+            let cached = cache.entries().map_err(io::Error::other)?.into_iter().map(|entry| entry.item).collect();
+            all_items = cached_feed_items(cached, &server_url, &mut trusted_keys);
+
+            if all_items.is_empty() {
+                UI::cleanup()?;
+                return Err(io::Error::other("Failed to fetch feed from any server"));
+            }
+
+            println!("No server reachable, playing {} cached items", all_items.len());
+            offline = true;
         }
 
         println!("Total items collected: {}", all_items.len());
         PlayMode::Feed(FeedManager::new(all_items))
     };
 
+    if let Err(e) = trusted_keys.save() {
+        eprintln!("Warning: {e}");
+    }
+
+    let mut live = None;
+    let mut music_offset = Duration::ZERO;
     let (mut feed_manager, is_single_content) = match play_mode {
         PlayMode::Feed(manager) => (manager, false),
         PlayMode::Channel(manager) => (manager, false),
@@ -355,13 +598,22 @@ async fn main() -> io::Result<()> {
             let items = vec![FeedItem { channel }];
             (FeedManager::new(items), true)
         }
+        PlayMode::Live(now_playing) => {
+            live = Some(LiveSync::new(server_url.clone(), &now_playing));
+            music_offset = now_playing.elapsed();
+            let item = live_item(now_playing, &server_url).map_err(|e| {
+                UI::cleanup().ok();
+                io::Error::other(e)
+            })?;
+            (FeedManager::new(vec![item]), true)
+        }
     };
 
     let current_channel = feed_manager.current();
-    midi_engine.parse_and_play_looping(&current_channel.content.midi_composition)
+    midi_engine.parse_and_play_looping_from(&current_channel.content.midi_composition, music_offset)
         .map_err(io::Error::other)?;
 
-    let result = tv_loop(&mut feed_manager, &mut midi_engine, is_single_content);
+    let result = tv_loop(&mut feed_manager, &mut midi_engine, is_single_content, Some(&server_url), live, offline);
 
     UI::cleanup()?;
 
@@ -372,13 +624,295 @@ async fn main() -> io::Result<()> {
     result
 }
 
+/// Updates the server list from the home server, unless `server_override` is set
+async fn refresh_servers(config: &mut TamaConfig, api_client: &ApiClient, server_url: &str) {
+    if !config.server_override {
+        if let Ok(servers) = api_client.fetch_servers().await {
+            config.servers = servers;
+            config.save().ok();
+        } else if config.servers.is_empty() {
+            config.servers = vec![server_url.to_string()];
+            config.save().ok();
+        }
+    } else if config.servers.is_empty() {
+        // If server_override is true but no servers are configured, use the main server
+        config.servers = vec![server_url.to_string()];
+    }
+}
+
+/// Older versions kept their files in the working directory, wherever `tama` was run from
+fn migrate_legacy_files(server_url: &str) {
+    let legacy_dir = std::path::Path::new(".");
+    let legacy_config = legacy_dir.join("config.json");
+    let legacy_auth = legacy_dir.join("auth.json");
+
+    // Along with whether the file is ours, rather than another project's file with the
+    // same name, and whether it holds secrets
+    let moves = [
+        (TamaConfig::load_from_path(&legacy_config).is_ok(), legacy_config, TamaConfig::default_config_path(), false),
+        (AuthProfiles::load_from_path(&legacy_auth, server_url).is_ok(), legacy_auth, AuthProfiles::default_auth_path(), true),
+        (
+            keys::load_private_key(legacy_dir).is_ok_and(|key| key.is_some()),
+            keys::private_key_path(legacy_dir),
+            keys::private_key_path(&TamaConfig::default_keys_dir()),
+            true,
+        ),
+    ];
+
+    for (ours, legacy, new, private) in moves {
+        if !ours {
+            continue;
+        }
+        match paths::migrate_file(&legacy, &new) {
+            Ok(true) => {
+                eprintln!("Moved {} to {}", legacy.display(), new.display());
+                if private && let Err(e) = paths::restrict_permissions(&new) {
+                    eprintln!("Warning: failed to restrict permissions of {}: {e}", new.display());
+                }
+            }
+            Ok(false) => {}
+            Err(e) => eprintln!("Warning: {e}"),
+        }
+    }
+
+    // A single login from before profiles was made on the server of that time, bind it now
+    // rather than to whatever server a later command runs against
+    let auth_path = AuthProfiles::default_auth_path();
+    match AuthProfiles::upgrade_single_login(&auth_path, server_url) {
+        Ok(true) => eprintln!("Saved your login to {server_url} as profile '{DEFAULT_PROFILE}'"),
+        Ok(false) => {}
+        Err(e) => eprintln!("Warning: {e}"),
+    }
+}
+
+/// One line per server of the feed, how it answered and how quickly
+fn format_server_status(status: &ServerStatus) -> String {
+    let elapsed = status.latency.as_millis();
+    match &status.status {
+        FetchStatus::Ok { items, duplicates: 0 } => {
+            format!("✓ Received {items} items from {} in {elapsed}ms", status.server_url)
+        }
+        FetchStatus::Ok { items, duplicates } => {
+            format!("✓ Received {items} items from {} in {elapsed}ms, {duplicates} already seen", status.server_url)
+        }
+        FetchStatus::Failed(e) => format!("✗ Failed to fetch from {}: {e}", status.server_url),
+        FetchStatus::TimedOut => format!("✗ Timeout waiting for {}", status.server_url),
+    }
+}
+
+fn with_verification(channel: Channel, verification: Option<Verification>) -> Channel {
+    match verification {
+        Some(verification) => channel.with_verification(verification),
+        None => channel,
+    }
+}
+
+/// Cached contents as feed items, most recently used first. Contents whose channel
+/// is unknown, because they were only ever fetched by id, are left out.
+fn cached_feed_items(cached: Vec<CachedContent>, home_server_url: &str, trusted_keys: &mut TrustedKeys) -> Vec<FeedItem> {
+    cached
+        .into_iter()
+        .filter_map(|cached| {
+            let channel = cached.channel?;
+            let is_foreign = cached.server_url != home_server_url.trim_end_matches('/');
+            let verification = is_foreign.then(|| trusted_keys.verify(&cached.server_url, Some(&channel.name), &cached.content));
+            let item = FeedItem::from_api_feed_item_with_server(ApiFeedItem { channel, content: cached.content }, cached.server_url).ok()?;
+            Some(FeedItem { channel: with_verification(item.channel, verification) })
+        })
+        .collect()
+}
+
+/// Overlay title, flagging cached playback and contents from other servers that aren't validly signed
+fn overlay_title(verification: Option<Verification>, offline: bool) -> &'static str {
+    match (verification, offline) {
+        (None, false) => "Tama Tv",
+        (Some(Verification::Verified), false) => "Tama Tv [signed]",
+        (Some(Verification::Unsigned | Verification::Invalid | Verification::Untrusted), false) => "Tama Tv [unverified]",
+        (None, true) => "Tama Tv [offline]",
+        (Some(Verification::Verified), true) => "Tama Tv [offline] [signed]",
+        (Some(Verification::Unsigned | Verification::Invalid | Verification::Untrusted), true) => "Tama Tv [offline] [unverified]",
+    }
+}
+
+/// How long to wait before asking again after a failed re-sync
+const LIVE_RETRY_DELAY: Duration = Duration::from_secs(5);
+/// Asks a little after the content ends, so the answer is already the next one
+const LIVE_RESYNC_GRACE: Duration = Duration::from_millis(250);
+/// The schedule shifts when contents are uploaded or leave the top of the feed,
+/// so it is checked mid-air too
+const LIVE_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+/// Drift from the server's position tolerated before playback jumps to it
+const LIVE_MAX_DRIFT: Duration = Duration::from_secs(1);
+
+/// Keeps `--live` playback on the server's broadcast schedule
+struct LiveSync {
+    server_url: String,
+    /// Content on air and when it went on air, by our clock
+    on_air: (i64, std::time::Instant),
+    /// When the content on air ends
+    ends_at: std::time::Instant,
+    /// When to ask the server again
+    check_at: std::time::Instant,
+    pending: Option<mpsc::Receiver<Result<BroadcastNow, String>>>,
+}
+
+impl LiveSync {
+    fn new(server_url: String, now_playing: &BroadcastNow) -> Self {
+        let now = std::time::Instant::now();
+        let mut live = Self { server_url, on_air: (0, now), ends_at: now, check_at: now, pending: None };
+        live.tune_in(now_playing, now);
+        live
+    }
+
+    fn tune_in(&mut self, now_playing: &BroadcastNow, now: std::time::Instant) {
+        let started_at = now.checked_sub(now_playing.elapsed()).unwrap_or(now);
+        self.on_air = (now_playing.content.id, started_at);
+        self.ends_at = now + now_playing.remaining();
+        self.check_at = (now + LIVE_CHECK_INTERVAL).min(self.ends_at + LIVE_RESYNC_GRACE);
+    }
+
+    /// Whether `now_playing` is what is already playing, at about the same position
+    fn is_on_air(&self, now_playing: &BroadcastNow, now: std::time::Instant) -> bool {
+        let (content_id, started_at) = self.on_air;
+        content_id == now_playing.content.id
+            && now.duration_since(started_at).abs_diff(now_playing.elapsed()) <= LIVE_MAX_DRIFT
+    }
+
+    /// What went on air, when the schedule moved on or shifted since the last answer
+    fn poll(&mut self) -> Option<BroadcastNow> {
+        let now = std::time::Instant::now();
+
+        if let Some(pending) = &self.pending {
+            return match pending.try_recv() {
+                Ok(Ok(now_playing)) => {
+                    self.pending = None;
+                    let changed = !self.is_on_air(&now_playing, now);
+                    self.tune_in(&now_playing, now);
+                    changed.then_some(now_playing)
+                }
+                Ok(Err(_)) | Err(mpsc::TryRecvError::Disconnected) => {
+                    self.pending = None;
+                    self.check_at = now + LIVE_RETRY_DELAY;
+                    None
+                }
+                Err(mpsc::TryRecvError::Empty) => None,
+            };
+        }
+
+        if now >= self.check_at {
+            let (sender, receiver) = mpsc::channel();
+            let client = ApiClient::new(self.server_url.clone());
+            tokio::spawn(async move {
+                let _ = sender.send(client.fetch_broadcast_now().await);
+            });
+            self.pending = Some(receiver);
+        }
+        None
+    }
+}
+
+/// The content on air, with its animation already at the broadcast's position
+fn live_item(now_playing: BroadcastNow, server_url: &str) -> Result<FeedItem, String> {
+    let elapsed = now_playing.elapsed();
+    let mut channel = Channel::new(
+        now_playing.channel.id,
+        now_playing.channel.name,
+        now_playing.content.art,
+        now_playing.content.midi_composition,
+        now_playing.content.fps,
+        now_playing.content.id,
+    )?
+    .with_server_url(server_url.to_string());
+
+    channel.player.seek(elapsed.as_secs_f32());
+    Ok(FeedItem { channel })
+}
+
+/// Results of the comment requests `tv_loop` runs in the background
+enum CommentEvent {
+    Loaded { content_id: i64, server_url: String, result: Result<Vec<CommentInfo>, String> },
+    Posted { content_id: i64, server_url: String, result: Result<CommentInfo, String> },
+}
+
+/// Session to comment with on `server_url`, from any profile logged in to that server
+fn comment_session(home_server_url: &str, server_url: &str) -> Option<String> {
+    let profiles = AuthProfiles::load(home_server_url).ok()?;
+    let auth = profiles.for_server(server_url)?;
+
+    (auth.validate().is_ok() && auth.expiry(chrono::Utc::now().timestamp()) != Expiry::Expired)
+        .then(|| auth.jwt_token.clone())
+}
+
+fn comments_client(home_server_url: &str, server_url: &str) -> ApiClient {
+    match comment_session(home_server_url, server_url) {
+        Some(token) => ApiClient::with_session_token(server_url.to_string(), token),
+        None => ApiClient::new(server_url.to_string()),
+    }
+}
+
+/// Opens the pane for the content on screen and starts loading its comments.
+/// Local previews have nowhere to load comments from.
+fn open_comment_pane(
+    channel: &Channel,
+    home_server_url: Option<&str>,
+    events: &mpsc::Sender<CommentEvent>,
+) -> Option<CommentPane> {
+    let home_server_url = home_server_url?;
+    let server_url = channel.server_url.clone()?;
+    let content_id = channel.content_id;
+
+    let client = comments_client(home_server_url, &server_url);
+    let can_post = comment_session(home_server_url, &server_url).is_some();
+    let events = events.clone();
+    let pane = CommentPane::new(content_id, server_url.clone(), can_post);
+
+    tokio::spawn(async move {
+        let result = client.fetch_comments(content_id, tama::ui::VISIBLE_COMMENTS as i64).await;
+        let _ = events.send(CommentEvent::Loaded { content_id, server_url, result });
+    });
+
+    Some(pane)
+}
+
+fn post_comment(pane: &CommentPane, home_server_url: &str, body: String, events: &mpsc::Sender<CommentEvent>) {
+    let client = comments_client(home_server_url, &pane.server_url);
+    let content_id = pane.content_id;
+    let server_url = pane.server_url.clone();
+    let events = events.clone();
+
+    tokio::spawn(async move {
+        let result = client.post_comment(content_id, body).await;
+        let _ = events.send(CommentEvent::Posted { content_id, server_url, result });
+    });
+}
+
+fn apply_comment_event(pane: &mut CommentPane, event: CommentEvent) {
+    match event {
+        CommentEvent::Loaded { content_id, server_url, result } if pane.shows(content_id, &server_url) => match result {
+            Ok(comments) => pane.set_comments(comments),
+            Err(e) => pane.set_status(format!("Failed to load comments: {e}")),
+        },
+        CommentEvent::Posted { content_id, server_url, result } if pane.shows(content_id, &server_url) => match result {
+            Ok(comment) => pane.add_comment(comment),
+            Err(e) => pane.set_status(format!("Failed to post comment: {e}")),
+        },
+        // Answers for a content that is no longer on screen
+        _ => {}
+    }
+}
+
 fn tv_loop(
     feed_manager: &mut FeedManager,
     midi_engine: &mut MidiEngine,
     is_single_content: bool,
+    home_server_url: Option<&str>,
+    mut live: Option<LiveSync>,
+    offline: bool,
 ) -> io::Result<()> {
     let mut remote = RemoteAnimation::new();
     let mut last_update = std::time::Instant::now();
+    let (comment_events, comment_results) = mpsc::channel();
+    let mut comment_pane: Option<CommentPane> = None;
 
     loop {
         let now = std::time::Instant::now();
@@ -398,33 +932,79 @@ fn tv_loop(
                 let current_channel = feed_manager.current();
                 midi_engine.parse_and_play_looping(&current_channel.content.midi_composition)
                     .map_err(io::Error::other)?;
+
+                if comment_pane.is_some() {
+                    comment_pane = open_comment_pane(feed_manager.current(), home_server_url, &comment_events);
+                }
+            }
+
+        if let Some(now_playing) = live.as_mut().and_then(LiveSync::poll)
+            && let Some(server_url) = home_server_url
+        {
+            let music_offset = now_playing.elapsed();
+            let item = live_item(now_playing, server_url).map_err(io::Error::other)?;
+            *feed_manager = FeedManager::new(vec![item]);
+
+            let current_channel = feed_manager.current();
+            midi_engine.parse_and_play_looping_from(&current_channel.content.midi_composition, music_offset)
+                .map_err(io::Error::other)?;
+
+            if comment_pane.is_some() {
+                comment_pane = open_comment_pane(feed_manager.current(), home_server_url, &comment_events);
+            }
+        }
+
+        while let Ok(event) = comment_results.try_recv() {
+            if let Some(pane) = &mut comment_pane {
+                apply_comment_event(pane, event);
             }
+        }
 
         let ascii_art = {
             let current_channel = feed_manager.current();
             current_channel.render(delta_time).to_string()
         };
 
-        let title = "Tama Tv"; // feed_manager.current().name.clone();
+        let title = if live.is_some() { "Tama Tv [live]" } else { overlay_title(feed_manager.current().verification, offline) };
         let channel_id = feed_manager.current().id;
         let content_id = feed_manager.current().content_id;
         let server_url = feed_manager.current().server_url.as_deref().unwrap_or("unknown");
 
         let remote_frame = remote.get_frame();
         UI::display_channel_ascii(title, &ascii_art, remote_frame)?;
+        if let Some(pane) = &comment_pane {
+            UI::display_comment_pane(pane)?;
+        }
 
         if event::poll(Duration::from_millis(100))? {
             if let crossterm::event::Event::Key(key_event) = event::read()? {
+                // While the pane is open, keys go to the comment being typed
+                if let Some(pane) = &mut comment_pane {
+                    match pane.handle_key(key_event.code) {
+                        PaneAction::Close => comment_pane = None,
+                        PaneAction::Post(body) => {
+                            if let Some(home_server_url) = home_server_url {
+                                post_comment(pane, home_server_url, body, &comment_events);
+                            }
+                        }
+                        PaneAction::None => {}
+                    }
+                    continue;
+                }
+
                 match key_event.code {
                     KeyCode::Char('q') | KeyCode::Char('Q') => {
                         break;
                     }
                     KeyCode::Char('s') | KeyCode::Char('S') => {
                         if let Ok(clipboard) = &mut Clipboard::new() {
-                            let text = format!("{server_url}/content/{content_id} {server_url}/channel/{channel_id}");
+                            let text = format!("{server_url}/view/content/{content_id} {server_url}/channel/{channel_id}");
                             clipboard.set_text(text).unwrap();
                         };
                     }
+                    KeyCode::Char('c') | KeyCode::Char('C') if !feed_manager.is_empty_state() => {
+                        comment_pane = open_comment_pane(feed_manager.current(), home_server_url, &comment_events);
+                    }
                     KeyCode::Char('1') if !remote.is_playing() && !is_single_content => {
                         remote.trigger(0);
                     }
@@ -442,33 +1022,58 @@ fn tv_loop(
     Ok(())
 }
 
-async fn handle_auth(server_url: &str) -> io::Result<()> {
+/// Shows the channel profile until a key is pressed or a few seconds pass.
+/// Returns false if the user chose to exit.
+fn show_profile_card(channel_name: &str, profile: &ChannelProfile) -> io::Result<bool> {
+    UI::display_profile_card(channel_name, profile)?;
+
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    while std::time::Instant::now() < deadline {
+        if event::poll(Duration::from_millis(100))?
+            && let crossterm::event::Event::Key(key_event) = event::read()?
+        {
+            return Ok(!matches!(key_event.code, KeyCode::Char('q') | KeyCode::Char('Q')));
+        }
+    }
+
+    Ok(true)
+}
+
+/// Logs `profile` in to `server`, or to the server it was logged in to before
+async fn handle_auth(default_server_url: &str, server: Option<String>, profile: Option<&str>) -> io::Result<()> {
     println!("=== Tama Authentication ===\n");
 
+    let mut profiles = match AuthProfiles::load(default_server_url) {
+        Ok(profiles) => profiles,
+        Err(_) => {
+            println!("⚠ Found invalid auth file, creating new authentication...\n");
+            AuthProfiles::default()
+        }
+    };
+    let profile = profile.unwrap_or(DEFAULT_PROFILE).to_string();
+    let server_url = server
+        .or_else(|| profiles.profiles.get(&profile).map(|auth| auth.server_url.clone()))
+        .unwrap_or_else(|| default_server_url.to_string());
+    let server_url = server_url.as_str();
+
     // Check if already authenticated
-    if AuthConfig::auth_exists() {
-        match AuthConfig::load() {
-            Ok(auth) => {
-                if auth.validate().is_ok() {
-                    println!("✓ Already authenticated as: {}", auth.channel_name);
-                    println!("  Channel ID: {}", auth.channel_id);
-                    println!("\nDo you want to re-authenticate? (y/N): ");
-                    io::Write::flush(&mut io::stdout())?;
-                    let mut response = String::new();
-                    io::stdin().read_line(&mut response)?;
-                    let response = response.trim().to_lowercase();
-
-                    if response != "y" && response != "yes" {
-                        println!("\n✨ Authentication unchanged.");
-                        return Ok(());
-                    }
-                    println!();
-                }
-            }
-            Err(_) => {
-                println!("⚠ Found invalid auth file, creating new authentication...\n");
-            }
+    if let Some(auth) = profiles.profiles.get(&profile)
+        && auth.validate().is_ok()
+    {
+        println!("✓ Profile '{profile}' is authenticated as: {}", auth.channel_name);
+        println!("  Channel ID: {}", auth.channel_id);
+        println!("  Server: {}", auth.server_url);
+        println!("\nDo you want to re-authenticate? (y/N): ");
+        io::Write::flush(&mut io::stdout())?;
+        let mut response = String::new();
+        io::stdin().read_line(&mut response)?;
+        let response = response.trim().to_lowercase();
+
+        if response != "y" && response != "yes" {
+            println!("\n✨ Authentication unchanged.");
+            return Ok(());
         }
+        println!();
     }
 
     print!("Channel Name (no spaces, max 250 chars): ");
@@ -505,30 +1110,34 @@ async fn handle_auth(server_url: &str) -> io::Result<()> {
             println!("  Channel ID: {}", response.channel.id);
             println!("  Channel Name: {}", response.channel.name);
 
-            let auth = AuthConfig {
-                channel_id: response.channel.id,
-                channel_name: response.channel.name.clone(),
-                jwt_token: response.token,
-            };
-
-            auth.save()
+            profiles.insert(&profile, AuthConfig::from_response(server_url, &response));
+            profiles.save()
                 .map_err(|e| io::Error::other(format!("Failed to save auth: {e}")))?;
 
-            println!("✓ Auth saved to {}", AuthConfig::default_auth_path().display());
+            println!("✓ Saved as profile '{profile}' in {}", AuthProfiles::default_auth_path().display());
 
-            let servers = api_client.fetch_servers().await
+            let mut servers = api_client.fetch_servers().await
                 .unwrap_or_else(|_| vec![server_url.to_string()]);
+            if !servers.iter().any(|server| server == server_url) {
+                servers.push(server_url.to_string());
+            }
 
-            let config = TamaConfig {
-                server_url: server_url.to_string(),
-                servers,
-                server_override: false,
-            };
+            // Network and cache settings of an existing config are kept
+            let mut config = TamaConfig::load()
+                .unwrap_or_else(|_| TamaConfig::new(server_url.to_string()));
+            config.server_url = server_url.to_string();
+            config.servers = servers;
+            config.server_override = false;
 
             config.save()
                 .map_err(|e| io::Error::other(format!("Failed to save config: {e}")))?;
 
             println!("✓ Config saved to {}", TamaConfig::default_config_path().display());
+
+            if let Err(e) = register_signing_key(&api_client).await {
+                println!("⚠ Uploads won't be signed: {e}");
+            }
+
             println!("\n✨ You're all set! You can now upload content with:");
             println!("   cargo run --bin tama upload <content-file.txt>");
             Ok(())
@@ -539,25 +1148,65 @@ async fn handle_auth(server_url: &str) -> io::Result<()> {
     }
 }
 
-async fn handle_upload(server_url: &str, file_path: &str) -> io::Result<()> {
-    println!("=== Tama Content Upload ===\n");
+/// Creates the local signing key on first use and registers its public half with the server
+async fn register_signing_key(api_client: &ApiClient) -> Result<(), String> {
+    let keys_dir = TamaConfig::default_keys_dir();
+    let (private_key, is_new) = keys::load_or_generate_private_key(&keys_dir)?;
+    if is_new {
+        println!("✓ Signing key saved to {}", keys::private_key_path(&keys_dir).display());
+    }
 
-    // Check auth
-    if !AuthConfig::auth_exists() {
-        println!("✗ No account found");
-        println!("\n💡 Please create a channel with: cargo run --bin tama auth");
-        return Ok(());
+    let public_key = signing::public_key_to_pem(&private_key)?;
+    api_client.register_key(public_key).await?;
+    println!("✓ Signing key registered");
+    Ok(())
+}
+
+/// Accepts RFC 3339, `YYYY-MM-DD HH:MM` in local time, or `+<n>m|h|d` from now
+fn parse_publish_at(value: &str, now: chrono::DateTime<chrono::Local>) -> Result<i64, String> {
+    use chrono::TimeZone;
+
+    let value = value.trim();
+    let invalid = || format!("Invalid time '{value}', expected e.g. 2025-06-01T18:00:00Z, '2025-06-01 18:00' or +2h");
+
+    if let Some(relative) = value.strip_prefix('+') {
+        let unit_start = relative.char_indices().last().map(|(index, _)| index).ok_or_else(invalid)?;
+        let (amount, unit) = relative.split_at(unit_start);
+        let amount: i64 = amount.parse().map_err(|_| invalid())?;
+        let seconds = match unit {
+            "m" => 60,
+            "h" => 60 * 60,
+            "d" => 24 * 60 * 60,
+            _ => return Err(invalid()),
+        };
+        return Ok(now.timestamp() + amount * seconds);
     }
 
-    let auth = AuthConfig::load()
-        .map_err(|e| io::Error::other(format!("Failed to load auth: {e}")))?;
+    if let Ok(time) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Ok(time.timestamp());
+    }
 
-    // Validate auth
-    if let Err(e) = auth.validate() {
-        println!("✗ Authentication error: {e}");
-        println!("\n💡 Please review your settings with: cargo run --bin tama auth");
+    let naive = chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").map_err(|_| invalid())?;
+    now.timezone()
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|time| time.timestamp())
+        .ok_or_else(invalid)
+}
+
+async fn handle_upload(
+    server_url: &str,
+    profile: Option<&str>,
+    file_path: &str,
+    visibility: Visibility,
+    publish_at: Option<i64>,
+) -> io::Result<()> {
+    println!("=== Tama Content Upload ===\n");
+
+    let Some(auth) = load_valid_auth(server_url, profile)? else {
         return Ok(());
-    }
+    };
+    let server_url = auth.server_url.as_str();
 
     // Parse content file
     println!("Parsing content file: {file_path}");
@@ -571,8 +1220,7 @@ async fn handle_upload(server_url: &str, file_path: &str) -> io::Result<()> {
 
     // Validate MIDI composition
     println!("\nValidating MIDI composition...");
-    let midi_engine = MidiEngine::new(120)
-        .map_err(|e| io::Error::other(format!("Failed to initialize MIDI engine: {e}")))?;
+    let midi_engine = MidiEngine::without_audio(120);
 
     let midi_valid = match midi_engine.parse_notes(&content.midi_composition) {
         Ok(notes) => {
@@ -587,7 +1235,7 @@ async fn handle_upload(server_url: &str, file_path: &str) -> io::Result<()> {
 
     // Validate ASCII art
     println!("\nValidating ASCII art...");
-    let art_valid = match AsciiArtSheet::from_string(&content.art) {
+    let art_valid = match AsciiArtSheet::validate(&content.art) {
         Ok(sheet) => {
             println!("✓ ASCII art is valid ({}x{}, {} frames)",
                 sheet.width, sheet.height, sheet.frames.len());
@@ -625,18 +1273,43 @@ async fn handle_upload(server_url: &str, file_path: &str) -> io::Result<()> {
         .unwrap_or("Untitled")
         .to_string();
 
+    let signature = match keys::load_private_key(&TamaConfig::default_keys_dir()) {
+        Ok(Some(private_key)) => {
+            let signature = signing::sign_content(&private_key, &content.art, &content.midi_composition, content.fps)
+                .map_err(io::Error::other)?;
+            println!("✓ Content signed");
+            Some(signature)
+        }
+        Ok(None) => {
+            println!("⚠ No signing key, uploading unsigned (run `tama auth` to create one)");
+            None
+        }
+        Err(e) => {
+            println!("⚠ Uploading unsigned: {e}");
+            None
+        }
+    };
+
     // Upload
     println!("\nUploading content...");
-    match api_client.upload_content(
-        auth.channel_id,
-        content_name.clone(),
-        content.art,
-        content.midi_composition,
-        content.fps,
-    ).await {
+    let request = CreateContentRequest {
+        channel_id: auth.channel_id,
+        name: content_name,
+        art: content.art,
+        midi: content.midi_composition,
+        fps: content.fps,
+        visibility,
+        publish_at,
+        signature,
+    };
+
+    match api_client.upload_content(&request).await {
         Ok(response) => {
             println!("✓ {}", response.message);
             println!("  Content ID: {}", response.id);
+            if let Some(share_token) = &response.share_token {
+                println!("  Share link: {server_url}/share/{share_token}");
+            }
             println!("\n✨ Upload complete!");
             Ok(())
         }
@@ -688,7 +1361,7 @@ async fn handle_preview(file_path: &str) -> io::Result<()> {
     let items = vec![FeedItem { channel }];
     let mut feed_manager = FeedManager::new(items);
 
-    let result = tv_loop(&mut feed_manager, &mut midi_engine, true);
+    let result = tv_loop(&mut feed_manager, &mut midi_engine, true, None, None, false);
 
     UI::cleanup()?;
 
@@ -699,10 +1372,558 @@ async fn handle_preview(file_path: &str) -> io::Result<()> {
     result
 }
 
+/// Accepts a bare name or id as well as anything `parse_endpoint` understands as a channel
+fn parse_channel_ref(channel_ref: &str) -> Result<(Option<String>, String), String> {
+    let channel_ref = channel_ref.trim();
+    if !channel_ref.contains('/') {
+        return Ok((None, channel_ref.to_string()));
+    }
+
+    match parse_endpoint(channel_ref)? {
+        (server_url, EndpointType::Channel(channel)) => Ok((server_url, channel)),
+        (_, EndpointType::Content(_) | EndpointType::Shared(_)) => Err(format!("Expected a channel, got a content: {channel_ref}")),
+    }
+}
+
+/// Accepts a bare id as well as anything `parse_endpoint` understands as a content
+fn parse_content_ref(content_ref: &str) -> Result<(Option<String>, i64), String> {
+    if let Ok(content_id) = content_ref.trim().parse::<i64>() {
+        return Ok((None, content_id));
+    }
+
+    match parse_endpoint(content_ref)? {
+        (server_url, EndpointType::Content(content_id)) => Ok((server_url, content_id)),
+        (_, EndpointType::Channel(_)) => Err(format!("Expected a content, got a channel: {content_ref}")),
+        (_, EndpointType::Shared(_)) => Err(format!("Expected a content id, got a share link: {content_ref}")),
+    }
+}
+
+async fn handle_download(server_url: &str, content_ref: &str, output: Option<&str>) -> io::Result<()> {
+    let (custom_server_url, content_id) = parse_content_ref(content_ref).map_err(io::Error::other)?;
+    let server_url = custom_server_url.unwrap_or_else(|| server_url.to_string());
+
+    println!("Downloading content {content_id} from {server_url}...");
+    let api_client = ApiClient::new(server_url);
+    let file = api_client.download_content_file(content_id).await
+        .map_err(io::Error::other)?;
+
+    // Refuse to save something `tama preview` and `tama upload` could not read back
+    content_parser::parse_content(&file)
+        .map_err(|e| io::Error::other(format!("Server returned an invalid content file: {e:?}")))?;
+
+    let output = output
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("content_{content_id}.txt"));
+
+    std::fs::write(&output, file)?;
+    println!("✓ Saved to {output}");
+    println!("💡 Preview it with: cargo run --bin tama preview {output}");
+    Ok(())
+}
+
+async fn handle_profile(
+    server_url: &str,
+    profile: Option<&str>,
+    display_name: Option<String>,
+    bio: Option<String>,
+    avatar_path: Option<&str>,
+    links: Option<Vec<String>>,
+) -> io::Result<()> {
+    println!("=== Tama Channel Profile ===\n");
+
+    let Some(auth) = load_valid_auth(server_url, profile)? else {
+        return Ok(());
+    };
+    let server_url = auth.server_url.as_str();
+
+    let avatar = match avatar_path {
+        Some("") => Some(String::new()),
+        Some(path) => Some(std::fs::read_to_string(path)
+            .map_err(|e| io::Error::other(format!("Failed to read avatar file: {e}")))?),
+        None => None,
+    };
+
+    let request = UpdateProfileRequest { display_name, bio, avatar, links };
+    let api_client = ApiClient::with_session_token(server_url.to_string(), auth.jwt_token.clone());
+
+    let profile = if request.display_name.is_none() && request.bio.is_none()
+        && request.avatar.is_none() && request.links.is_none()
+    {
+        api_client.fetch_channel(&auth.channel_name).await
+            .map_err(io::Error::other)?
+            .profile
+    } else {
+        match api_client.update_profile(&request).await {
+            Ok(profile) => {
+                println!("✓ Profile updated\n");
+                profile
+            }
+            Err(e) => {
+                println!("✗ Profile update failed: {e}");
+                return Ok(());
+            }
+        }
+    };
+
+    for line in tama::ui::profile_card_lines(&auth.channel_name, &profile) {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+fn parse_role(value: &str) -> Result<Role, String> {
+    Role::parse(&value.to_lowercase()).ok_or_else(|| format!("Unknown role '{value}', expected owner, editor or viewer"))
+}
+
+fn parse_webhook_event(value: &str) -> Result<WebhookEvent, String> {
+    WebhookEvent::parse(&value.to_lowercase()).ok_or_else(|| format!("Unknown event '{value}', expected content_created"))
+}
+
+/// Loads the login of `profile`, or of the default profile, printing a hint and returning
+/// None when there is none or it expired. Warns when it is about to expire.
+fn load_valid_auth(default_server_url: &str, profile: Option<&str>) -> io::Result<Option<AuthConfig>> {
+    let profiles = AuthProfiles::load(default_server_url)
+        .map_err(|e| io::Error::other(format!("Failed to load auth: {e}")))?;
+
+    let Some((name, auth)) = profiles.get(profile) else {
+        match profile {
+            Some(profile) => println!("✗ No profile named '{profile}'"),
+            None => println!("✗ No account found"),
+        }
+        println!("\n💡 Please create a channel with: cargo run --bin tama auth{}", profile_flag(profile));
+        return Ok(None);
+    };
+
+    if let Err(e) = auth.validate() {
+        println!("✗ Authentication error: {e}");
+        println!("\n💡 Please review your settings with: cargo run --bin tama auth{}", profile_flag(Some(name)));
+        return Ok(None);
+    }
+
+    match auth.expiry(chrono::Utc::now().timestamp()) {
+        Expiry::Expired => {
+            println!("✗ The session of profile '{name}' expired");
+            println!("\n💡 Please log in again with: cargo run --bin tama auth{}", profile_flag(Some(name)));
+            return Ok(None);
+        }
+        Expiry::ExpiresSoon { remaining_secs } => {
+            println!("⚠ The session of profile '{name}' expires in {}", format_remaining(remaining_secs));
+            println!("  Log in again with: cargo run --bin tama auth{}\n", profile_flag(Some(name)));
+        }
+        Expiry::Valid | Expiry::Unknown => {}
+    }
+
+    Ok(Some(auth.clone()))
+}
+
+/// `--profile` to repeat in hints, omitted for the default profile
+fn profile_flag(profile: Option<&str>) -> String {
+    match profile {
+        Some(profile) if profile != DEFAULT_PROFILE => format!(" --profile {profile}"),
+        _ => String::new(),
+    }
+}
+
+fn format_remaining(seconds: i64) -> String {
+    match seconds {
+        ..3600 => format!("{} minute(s)", (seconds / 60).max(1)),
+        3600..86_400 => format!("{} hour(s)", seconds / 3600),
+        _ => format!("{} day(s)", seconds / 86_400),
+    }
+}
+
+fn handle_whoami(default_server_url: &str) -> io::Result<()> {
+    let profiles = AuthProfiles::load(default_server_url)
+        .map_err(|e| io::Error::other(format!("Failed to load auth: {e}")))?;
+
+    if profiles.profiles.is_empty() {
+        println!("Not logged in");
+        println!("\n💡 Log in with: cargo run --bin tama auth");
+        return Ok(());
+    }
+
+    let default_name = profiles.default_name();
+    let now = chrono::Utc::now().timestamp();
+
+    for (name, auth) in &profiles.profiles {
+        let marker = if Some(name.as_str()) == default_name { "*" } else { " " };
+        let account = match &auth.user_name {
+            Some(user_name) => format!("{user_name}@{}", auth.channel_name),
+            None => auth.channel_name.clone(),
+        };
+        let expiry = match (auth.expiry(now), auth.expires_at) {
+            (Expiry::Expired, _) => "expired".to_string(),
+            (Expiry::Valid | Expiry::ExpiresSoon { .. }, Some(expires_at)) => {
+                format!("expires in {}", format_remaining(expires_at - now))
+            }
+            _ => "expiry unknown".to_string(),
+        };
+        println!("{marker} {name:<12} {account:<24} {:<32} {expiry}", auth.server_url);
+    }
+    Ok(())
+}
+
+async fn handle_invite(server_url: &str, profile: Option<&str>, role: Role) -> io::Result<()> {
+    println!("=== Tama Channel Invite ===\n");
+
+    let Some(auth) = load_valid_auth(server_url, profile)? else {
+        return Ok(());
+    };
+    let server_url = auth.server_url.as_str();
+
+    let api_client = ApiClient::with_session_token(server_url.to_string(), auth.jwt_token.clone());
+    match api_client.create_invite(role).await {
+        Ok(invite) => {
+            let expires_at = chrono::DateTime::from_timestamp(invite.expires_at, 0)
+                .map(|time| time.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default();
+            println!("✓ Invite to '{}' as {} created", invite.channel.name, invite.role.as_str());
+            println!("  Single use, expires {expires_at}");
+            println!("\n💡 Share this command: cargo run --bin tama join {}", invite.token);
+        }
+        Err(e) => println!("✗ Invite failed: {e}"),
+    }
+    Ok(())
+}
+
+async fn handle_join(server_url: &str, profile: Option<&str>, token: &str, new_user: bool) -> io::Result<()> {
+    println!("=== Tama Join Channel ===\n");
+
+    print!("User Name: ");
+    io::Write::flush(&mut io::stdout())?;
+    let mut user_name = String::new();
+    io::stdin().read_line(&mut user_name)?;
+    let user_name = user_name.trim().to_string();
+
+    if user_name.is_empty() {
+        return Err(io::Error::other("User name cannot be empty"));
+    }
+
+    let password = rpassword::prompt_password("Password: ")
+        .map_err(|e| io::Error::other(format!("Failed to read password: {e}")))?;
+
+    let mut api_client = ApiClient::new(server_url.to_string());
+
+    if new_user {
+        api_client.register_user(user_name.clone(), password.clone()).await
+            .map_err(|e| io::Error::other(format!("Failed to create user: {e}")))?;
+        println!("✓ User '{user_name}' created");
+    }
+
+    let response = api_client.accept_invite(token, user_name, password).await
+        .map_err(|e| io::Error::other(format!("Failed to join: {e}")))?;
+
+    save_member_auth(server_url, profile, &response)?;
+    Ok(())
+}
+
+fn save_member_auth(server_url: &str, profile: Option<&str>, response: &AuthResponse) -> io::Result<()> {
+    let role = response.role.map(|role| role.as_str()).unwrap_or("member");
+    println!("✓ Joined '{}' as {role}", response.channel.name);
+
+    let profile = profile.unwrap_or(DEFAULT_PROFILE);
+    let mut profiles = AuthProfiles::load(server_url)
+        .map_err(|e| io::Error::other(format!("Failed to load auth: {e}")))?;
+    profiles.insert(profile, AuthConfig::from_response(server_url, response));
+    profiles.save()
+        .map_err(|e| io::Error::other(format!("Failed to save auth: {e}")))?;
+
+    println!("✓ Saved as profile '{profile}' in {}", AuthProfiles::default_auth_path().display());
+    Ok(())
+}
+
+async fn handle_members(server_url: &str, profile: Option<&str>) -> io::Result<()> {
+    let Some(auth) = load_valid_auth(server_url, profile)? else {
+        return Ok(());
+    };
+    let server_url = auth.server_url.as_str();
+
+    let api_client = ApiClient::with_session_token(server_url.to_string(), auth.jwt_token.clone());
+    let members = api_client.list_members().await
+        .map_err(io::Error::other)?;
+
+    println!("Members of '{}':", auth.channel_name);
+    if members.is_empty() {
+        println!("  (only the channel password owner)");
+    }
+    for member in members {
+        println!("  {:<8} {} (#{})", member.role.as_str(), member.user.name, member.user.id);
+    }
+    Ok(())
+}
+
+async fn handle_account_log(server_url: &str, profile: Option<&str>, limit: i64) -> io::Result<()> {
+    let Some(auth) = load_valid_auth(server_url, profile)? else {
+        return Ok(());
+    };
+    let server_url = auth.server_url.as_str();
+
+    let api_client = ApiClient::with_session_token(server_url.to_string(), auth.jwt_token.clone());
+    let events = api_client.fetch_security_log(limit).await
+        .map_err(io::Error::other)?;
+
+    println!("Security log of '{}':", auth.channel_name);
+    if events.is_empty() {
+        println!("  (no events)");
+    }
+    for event in events {
+        let time = format_local_time(event.created_at);
+        let marker = match event.kind {
+            AuthEventKind::LoginFailure | AuthEventKind::LockedOut => "✗",
+            _ => "✓",
+        };
+        println!(
+            "  {time}  {marker} {:<14} {:<16} {}",
+            event.kind.as_str(),
+            event.ip.as_deref().unwrap_or("-"),
+            event.user_name.as_deref().unwrap_or(""),
+        );
+    }
+    Ok(())
+}
+
+fn format_local_time(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|time| time.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_default()
+}
+
+async fn handle_webhook(server_url: &str, profile: Option<&str>, command: &WebhookCommand) -> io::Result<()> {
+    let Some(auth) = load_valid_auth(server_url, profile)? else {
+        return Ok(());
+    };
+    let server_url = auth.server_url.as_str();
+
+    let api_client = ApiClient::with_session_token(server_url.to_string(), auth.jwt_token.clone());
+
+    match command {
+        WebhookCommand::Add { url, secret, events } => {
+            let generated = secret.is_none();
+            let secret = secret.clone().unwrap_or_else(|| uuid::Uuid::new_v4().simple().to_string());
+            let request = CreateWebhookRequest { url: url.clone(), secret: secret.clone(), events: events.clone() };
+
+            let webhook = api_client.create_webhook(&request).await
+                .map_err(io::Error::other)?;
+
+            println!("✓ Webhook #{} added for {}", webhook.id, webhook.url);
+            if generated {
+                println!("  Secret: {secret}");
+                println!("  Keep it to check the X-Tama-Signature header, it won't be shown again");
+            }
+        }
+        WebhookCommand::List => {
+            let webhooks = api_client.list_webhooks().await
+                .map_err(io::Error::other)?;
+
+            println!("Webhooks of '{}':", auth.channel_name);
+            if webhooks.is_empty() {
+                println!("  (none)");
+            }
+            for webhook in webhooks {
+                let events: Vec<&str> = webhook.events.iter().map(WebhookEvent::as_str).collect();
+                println!("  #{:<4} {}  [{}]", webhook.id, webhook.url, events.join(", "));
+            }
+        }
+        WebhookCommand::Remove { webhook_id } => {
+            api_client.delete_webhook(*webhook_id).await
+                .map_err(io::Error::other)?;
+            println!("✓ Webhook #{webhook_id} removed");
+        }
+        WebhookCommand::Log { limit } => {
+            let deliveries = api_client.fetch_webhook_deliveries(*limit).await
+                .map_err(io::Error::other)?;
+
+            println!("Webhook deliveries of '{}':", auth.channel_name);
+            if deliveries.is_empty() {
+                println!("  (no deliveries)");
+            }
+            for delivery in deliveries {
+                let marker = match delivery.status {
+                    DeliveryStatus::Succeeded => "✓",
+                    DeliveryStatus::Failed => "✗",
+                    DeliveryStatus::Pending => "…",
+                };
+                println!(
+                    "  {}  {marker} {:<9} {:<15} #{:<4} {} attempt(s)  {}",
+                    format_local_time(delivery.updated_at),
+                    delivery.status.as_str(),
+                    delivery.event.as_str(),
+                    delivery.webhook_id,
+                    delivery.attempts,
+                    delivery.error.as_deref().unwrap_or(&delivery.url),
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..1024 => format!("{bytes} B"),
+        1024..1_048_576 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+/// JSON for scripts, or an aligned table
+fn print_rows<T: serde::Serialize>(json_value: &T, rows: &[ContentRow], json: bool) -> io::Result<()> {
+    if json {
+        let json = serde_json::to_string_pretty(json_value).map_err(io::Error::other)?;
+        println!("{json}");
+    } else {
+        println!("{}", listing::format_table(rows));
+    }
+    Ok(())
+}
+
+/// Server statuses go to stderr, so the listing on stdout can be piped
+async fn handle_feed(server_url: &str, json: bool) -> io::Result<()> {
+    let mut config = TamaConfig::load()
+        .unwrap_or_else(|_| TamaConfig::new(server_url.to_string()));
+    refresh_servers(&mut config, &ApiClient::new(server_url.to_string()), server_url).await;
+
+    let federated = FederatedFeed::new(config.servers.clone())
+        .with_cache(config.content_cache())
+        .fetch(&mut config.server_stats)
+        .await;
+    config.save().ok();
+
+    for status in &federated.statuses {
+        eprintln!("{}", format_server_status(status));
+    }
+    if !federated.any_reachable() {
+        return Err(io::Error::other("Failed to fetch feed from any server"));
+    }
+
+    let rows: Vec<ContentRow> = federated.items
+        .iter()
+        .map(|federated_item| ContentRow::new(&federated_item.server_url, Some(&federated_item.item.channel), &federated_item.item.content))
+        .collect();
+
+    print_rows(&rows, &rows, json)
+}
+
+async fn handle_channel(server_url: &str, channel_ref: &str, json: bool) -> io::Result<()> {
+    let (custom_server_url, channel_identifier) = parse_channel_ref(channel_ref).map_err(io::Error::other)?;
+    let server_url = custom_server_url.unwrap_or_else(|| server_url.to_string());
+
+    let api_client = ApiClient::new(server_url.clone());
+    let channel = api_client.fetch_channel(&channel_identifier).await
+        .map_err(io::Error::other)?;
+
+    let channel_info = ApiChannelInfo { id: channel.id, name: channel.name.clone() };
+    let rows: Vec<ContentRow> = channel.contents
+        .iter()
+        .map(|content| ContentRow::new(&server_url, Some(&channel_info), content))
+        .collect();
+
+    print_rows(&rows, &rows, json)
+}
+
+async fn handle_content(server_url: &str, content_ref: &str, json: bool) -> io::Result<()> {
+    let (custom_server_url, content_id) = parse_content_ref(content_ref).map_err(io::Error::other)?;
+    let server_url = custom_server_url.unwrap_or_else(|| server_url.to_string());
+
+    let cache = TamaConfig::load()
+        .map(|config| config.content_cache())
+        .unwrap_or_else(|_| ContentCache::new(TamaConfig::default_cache_dir(), cache::DEFAULT_MAX_MB * 1024 * 1024));
+    let api_client = ApiClient::new(server_url.clone()).with_cache(cache.clone());
+    let content = api_client.fetch_content(content_id).await
+        .map_err(io::Error::other)?;
+
+    // Contents don't say which channel they belong to, but the feed may have told the cache
+    let channel = cache.get(&server_url, content_id).and_then(|cached| cached.channel);
+    let row = ContentRow::new(&server_url, channel.as_ref(), &content);
+
+    print_rows(&row, std::slice::from_ref(&row), json)
+}
+
+fn handle_cache(command: &CacheCommand) -> io::Result<()> {
+    let cache = TamaConfig::load()
+        .map(|config| config.content_cache())
+        .unwrap_or_else(|_| ContentCache::new(TamaConfig::default_cache_dir(), cache::DEFAULT_MAX_MB * 1024 * 1024));
+
+    match command {
+        CacheCommand::Ls => {
+            let entries = cache.entries().map_err(io::Error::other)?;
+            let total: u64 = entries.iter().map(|entry| entry.size).sum();
+
+            println!("Cached contents in {}:", cache.dir().display());
+            if entries.is_empty() {
+                println!("  (empty)");
+            }
+            for entry in &entries {
+                let last_used = entry.last_used
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|since_epoch| format_local_time(since_epoch.as_secs() as i64))
+                    .unwrap_or_default();
+                let channel = entry.item.channel.as_ref().map_or("?", |channel| channel.name.as_str());
+
+                println!(
+                    "  {last_used}  #{:<6} {:<20} {:>9}  {}",
+                    entry.item.content.id,
+                    channel,
+                    format_size(entry.size),
+                    entry.item.server_url,
+                );
+            }
+            println!("{} item(s), {} of {}", entries.len(), format_size(total), format_size(cache.max_bytes()));
+        }
+        CacheCommand::Clear => {
+            let removed = cache.clear().map_err(io::Error::other)?;
+            println!("✓ Removed {removed} cached item(s)");
+        }
+    }
+    Ok(())
+}
+
+fn handle_render(file_path: &str, output: Option<&str>) -> io::Result<()> {
+    println!("Parsing content file: {file_path}");
+    let content = content_parser::parse_content_file(file_path)
+        .map_err(|e| io::Error::other(format!("Failed to parse content file: {e:?}")))?;
+
+    let output = match output {
+        Some(output) => std::path::PathBuf::from(output),
+        None => std::path::Path::new(file_path).with_extension("wav"),
+    };
+
+    let wav = midi_composer::render_composition_wav(&content.midi_composition, f32::INFINITY)
+        .map_err(|e| io::Error::other(format!("Failed to render composition: {e}")))?;
+
+    std::fs::write(&output, &wav)?;
+    println!("✓ Rendered {} to {}", file_path, output.display());
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn broadcast(content_id: i64, elapsed_ms: i64) -> BroadcastNow {
+        serde_json::from_value(serde_json::json!({
+            "channel": { "id": 1, "name": "neko" },
+            "content": { "id": content_id, "art": "", "midi_composition": "", "fps": 10.0 },
+            "elapsed_ms": elapsed_ms,
+            "duration_ms": 60_000,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_live_sync_follows_schedule_shifts() {
+        let live = LiveSync::new("http://localhost:3000".to_string(), &broadcast(7, 10_000));
+        let now = std::time::Instant::now();
+
+        assert!(live.is_on_air(&broadcast(7, 10_300), now));
+        // Same content, but the schedule moved under it
+        assert!(!live.is_on_air(&broadcast(7, 25_000), now));
+        assert!(!live.is_on_air(&broadcast(8, 10_000), now));
+
+        // Checked mid-air rather than only when the content ends
+        assert!(live.check_at <= now + LIVE_CHECK_INTERVAL);
+    }
+
     #[test]
     fn test_parse_endpoint_content_id() {
         let result = parse_endpoint("/content/123");
@@ -787,6 +2008,106 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_endpoint_web_player_url() {
+        let (server_url, endpoint) = parse_endpoint("https://example.org/view/content/42").unwrap();
+        assert_eq!(server_url, Some("https://example.org".to_string()));
+        match endpoint {
+            EndpointType::Content(id) => assert_eq!(id, 42),
+            _ => panic!("Expected Content endpoint"),
+        }
+    }
+
+    #[test]
+    fn test_parse_endpoint_share_link() {
+        let (server_url, endpoint) = parse_endpoint("https://tama.example/share/abc123").unwrap();
+        assert_eq!(server_url.as_deref(), Some("https://tama.example"));
+        assert!(matches!(endpoint, EndpointType::Shared(token) if token == "abc123"));
+        assert!(matches!(parse_endpoint("share/abc123").unwrap().1, EndpointType::Shared(_)));
+    }
+
+    #[test]
+    fn test_profile_hints() {
+        assert_eq!(profile_flag(None), "");
+        assert_eq!(profile_flag(Some(DEFAULT_PROFILE)), "");
+        assert_eq!(profile_flag(Some("work")), " --profile work");
+
+        assert_eq!(format_remaining(30), "1 minute(s)");
+        assert_eq!(format_remaining(2 * 3600 + 5), "2 hour(s)");
+        assert_eq!(format_remaining(3 * 86_400), "3 day(s)");
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(20 * 1024 * 1024), "20.0 MB");
+    }
+
+    #[test]
+    fn test_format_server_status() {
+        let status = |status| ServerStatus { server_url: "https://tama.example".to_string(), latency: Duration::from_millis(120), status };
+
+        assert_eq!(format_server_status(&status(FetchStatus::Ok { items: 12, duplicates: 0 })), "✓ Received 12 items from https://tama.example in 120ms");
+        assert_eq!(format_server_status(&status(FetchStatus::Ok { items: 12, duplicates: 3 })), "✓ Received 12 items from https://tama.example in 120ms, 3 already seen");
+        assert_eq!(format_server_status(&status(FetchStatus::TimedOut)), "✗ Timeout waiting for https://tama.example");
+    }
+
+    #[test]
+    fn test_overlay_title_flags_unverified_contents() {
+        assert_eq!(overlay_title(None, false), "Tama Tv");
+        assert_eq!(overlay_title(Some(Verification::Verified), false), "Tama Tv [signed]");
+        assert_eq!(overlay_title(Some(Verification::Unsigned), false), "Tama Tv [unverified]");
+        assert_eq!(overlay_title(Some(Verification::Invalid), false), "Tama Tv [unverified]");
+        assert_eq!(overlay_title(None, true), "Tama Tv [offline]");
+        assert_eq!(overlay_title(Some(Verification::Invalid), true), "Tama Tv [offline] [unverified]");
+    }
+
+    #[test]
+    fn test_parse_role() {
+        assert_eq!(parse_role("Viewer"), Ok(Role::Viewer));
+        assert!(parse_role("admin").is_err());
+    }
+
+    #[test]
+    fn test_parse_publish_at() {
+        use chrono::TimeZone;
+
+        let now = chrono::Local.timestamp_opt(1_700_000_000, 0).unwrap();
+        assert_eq!(parse_publish_at("+2h", now), Ok(1_700_000_000 + 7200));
+        assert_eq!(parse_publish_at("+1d", now), Ok(1_700_000_000 + 86400));
+        assert_eq!(parse_publish_at("2024-01-01T00:00:00Z", now), Ok(1_704_067_200));
+
+        let local = chrono::Local.with_ymd_and_hms(2024, 1, 1, 18, 30, 0).unwrap();
+        assert_eq!(parse_publish_at("2024-01-01 18:30", now), Ok(local.timestamp()));
+
+        assert!(parse_publish_at("tomorrow", now).is_err());
+        assert!(parse_publish_at("+2w", now).is_err());
+    }
+
+    #[test]
+    fn test_parse_channel_ref() {
+        assert_eq!(parse_channel_ref("neko").unwrap(), (None, "neko".to_string()));
+        assert_eq!(parse_channel_ref("neko@tama.example").unwrap(), (None, "neko@tama.example".to_string()));
+        assert_eq!(parse_channel_ref("/channel/7").unwrap(), (None, "7".to_string()));
+        assert_eq!(
+            parse_channel_ref("https://tama.example/channel/neko").unwrap(),
+            (Some("https://tama.example".to_string()), "neko".to_string())
+        );
+        assert!(parse_channel_ref("/content/42").is_err());
+    }
+
+    #[test]
+    fn test_parse_content_ref() {
+        assert_eq!(parse_content_ref("42").unwrap(), (None, 42));
+        assert_eq!(parse_content_ref("/content/7").unwrap(), (None, 7));
+        assert_eq!(
+            parse_content_ref("https://example.org/view/content/3").unwrap(),
+            (Some("https://example.org".to_string()), 3)
+        );
+        assert!(parse_content_ref("/channel/neko").is_err());
+    }
+
     #[test]
     fn test_parse_endpoint_invalid_format() {
         let result = parse_endpoint("/invalid");
@@ -40,6 +40,13 @@ impl AsciiArtPlayer {
         }
     }
 
+    /// Jumps to where playback would be after `seconds` from the first frame
+    pub fn seek(&mut self, seconds: f32) {
+        let frames = seconds.max(0.0) * self.fps;
+        self.current_frame = frames.floor() as usize % self.sheet.frames.len();
+        self.frame_timer = frames.fract() / self.fps;
+    }
+
     pub fn current_frame(&self) -> &str {
         &self.sheet.frames[self.current_frame]
     }
@@ -80,4 +87,17 @@ mod tests {
         player.update(0.1);
         assert_eq!(player.current_frame, 0);
     }
+
+    #[test]
+    fn test_player_seek() {
+        let mut player = AsciiArtPlayer::from_string("Ascii Art Animation, 1x1\n⠀\n⠁\n⠂\n".to_string(), 10.0).unwrap();
+
+        player.seek(0.45);
+        assert_eq!(player.current_frame, 1);
+        player.update(0.06);
+        assert_eq!(player.current_frame, 2);
+
+        player.seek(0.35);
+        assert_eq!(player.current_frame, 0);
+    }
 }
@@ -0,0 +1,170 @@
+use crossterm::event::KeyCode;
+
+use super::profile_card::{pad, truncate, CARD_WIDTH};
+use crate::api::CommentInfo;
+
+/// Comments shown at once, the most recent ones
+pub const VISIBLE_COMMENTS: usize = 6;
+/// Same limit the server enforces
+pub const MAX_COMMENT_LENGTH: usize = 500;
+const INNER_WIDTH: usize = CARD_WIDTH - 4;
+
+/// What the TV loop should do after a key press in the pane
+#[derive(Debug, PartialEq)]
+pub enum PaneAction {
+    None,
+    Close,
+    Post(String),
+}
+
+/// Comments of the content on screen, with a one line composer for logged in users
+pub struct CommentPane {
+    pub content_id: i64,
+    pub server_url: String,
+    /// Newest first, `None` until loaded
+    comments: Option<Vec<CommentInfo>>,
+    input: String,
+    can_post: bool,
+    status: Option<String>,
+}
+
+impl CommentPane {
+    pub fn new(content_id: i64, server_url: String, can_post: bool) -> Self {
+        Self {
+            content_id,
+            server_url,
+            comments: None,
+            input: String::new(),
+            can_post,
+            status: None,
+        }
+    }
+
+    pub fn shows(&self, content_id: i64, server_url: &str) -> bool {
+        self.content_id == content_id && self.server_url == server_url
+    }
+
+    pub fn set_comments(&mut self, comments: Vec<CommentInfo>) {
+        self.comments = Some(comments);
+        self.status = None;
+    }
+
+    pub fn add_comment(&mut self, comment: CommentInfo) {
+        self.comments.get_or_insert_with(Vec::new).insert(0, comment);
+        self.status = None;
+    }
+
+    pub fn set_status(&mut self, status: impl Into<String>) {
+        self.status = Some(status.into());
+    }
+
+    pub fn handle_key(&mut self, code: KeyCode) -> PaneAction {
+        match code {
+            KeyCode::Esc => PaneAction::Close,
+            KeyCode::Enter if self.can_post && !self.input.trim().is_empty() => {
+                self.status = Some("Posting...".to_string());
+                PaneAction::Post(std::mem::take(&mut self.input).trim().to_string())
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+                PaneAction::None
+            }
+            KeyCode::Char(c) if self.can_post && self.input.chars().count() < MAX_COMMENT_LENGTH => {
+                self.input.push(c);
+                PaneAction::None
+            }
+            _ => PaneAction::None,
+        }
+    }
+
+    /// Bordered box as wide as the TV frame, every line `CARD_WIDTH` characters wide
+    pub fn lines(&self) -> Vec<String> {
+        let mut body = Vec::new();
+
+        match &self.comments {
+            None => body.push("Loading comments...".to_string()),
+            Some(comments) if comments.is_empty() => body.push("No comments yet".to_string()),
+            Some(comments) => {
+                for comment in comments.iter().take(VISIBLE_COMMENTS) {
+                    body.push(truncate(&format!("{}: {}", comment.author, comment.body), INNER_WIDTH));
+                }
+            }
+        }
+
+        body.push(String::new());
+        if self.can_post {
+            // Keep the end of long drafts, where the cursor is, in view
+            let draft: String = self.input.chars().rev().take(INNER_WIDTH - 3).collect::<Vec<_>>().into_iter().rev().collect();
+            body.push(format!("> {draft}_"));
+        } else {
+            body.push("Log in with `tama auth` to comment".to_string());
+        }
+        if let Some(status) = &self.status {
+            body.push(truncate(status, INNER_WIDTH));
+        }
+
+        let title = " Comments ";
+        let border = "─".repeat(CARD_WIDTH - 2 - title.len());
+        let footer = if self.can_post { " [Enter] Post  [Esc] Close " } else { " [Esc] Close " };
+        let bottom = "─".repeat(CARD_WIDTH - 2 - footer.len());
+
+        let mut lines = vec![format!("╭{title}{border}╮")];
+        lines.extend(body.iter().map(|line| format!("│ {} │", pad(line, INNER_WIDTH))));
+        lines.push(format!("╰{bottom}{footer}╯"));
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(id: i64, author: &str, body: &str) -> CommentInfo {
+        CommentInfo {
+            id,
+            content_id: 1,
+            channel_id: 1,
+            author: author.to_string(),
+            body: body.to_string(),
+            created_at: id,
+        }
+    }
+
+    #[test]
+    fn test_lines_have_constant_width() {
+        let mut pane = CommentPane::new(1, "http://localhost:3000".to_string(), true);
+        assert!(pane.lines().iter().any(|line| line.contains("Loading comments...")));
+
+        pane.set_comments((0..10).map(|id| comment(id, "neko", &"meow ".repeat(30))).collect());
+        "a long draft that scrolls".repeat(10).chars().for_each(|c| { pane.handle_key(KeyCode::Char(c)); });
+
+        let lines = pane.lines();
+        assert!(lines.iter().all(|line| line.chars().count() == CARD_WIDTH), "{lines:#?}");
+        assert_eq!(lines.iter().filter(|line| line.contains("neko: meow")).count(), VISIBLE_COMMENTS);
+        assert!(lines.iter().any(|line| line.contains("scrolls_")));
+    }
+
+    #[test]
+    fn test_typing_and_posting() {
+        let mut pane = CommentPane::new(1, "http://localhost:3000".to_string(), true);
+        assert_eq!(pane.handle_key(KeyCode::Enter), PaneAction::None);
+
+        for c in "hi!".chars() {
+            pane.handle_key(KeyCode::Char(c));
+        }
+        pane.handle_key(KeyCode::Backspace);
+        assert_eq!(pane.handle_key(KeyCode::Enter), PaneAction::Post("hi".to_string()));
+        assert_eq!(pane.handle_key(KeyCode::Esc), PaneAction::Close);
+
+        pane.add_comment(comment(2, "tora", "hi"));
+        assert!(pane.lines().iter().any(|line| line.contains("tora: hi")));
+    }
+
+    #[test]
+    fn test_logged_out_pane_is_read_only() {
+        let mut pane = CommentPane::new(1, "http://localhost:3000".to_string(), false);
+        pane.handle_key(KeyCode::Char('x'));
+        assert_eq!(pane.handle_key(KeyCode::Enter), PaneAction::None);
+        assert!(pane.lines().iter().any(|line| line.contains("tama auth")));
+    }
+}
@@ -1,11 +1,16 @@
 mod remote;
 mod ascii_art_player;
 mod loading;
+mod profile_card;
+mod comment_pane;
 
 pub use remote::RemoteAnimation;
 pub use ascii_art_player::AsciiArtPlayer;
 pub use loading::LoadingAnimation;
+pub use profile_card::profile_card_lines;
+pub use comment_pane::{CommentPane, PaneAction, VISIBLE_COMMENTS};
 
+use crate::api::ChannelProfile;
 use crate::ascii_art_converter::{TV_WIDTH, TV_HEIGHT};
 use std::io::{self, Write};
 
@@ -48,6 +53,24 @@ impl UI {
         Ok(())
     }
 
+    pub fn display_profile_card(channel_name: &str, profile: &ChannelProfile) -> io::Result<()> {
+        Self::clear_screen()?;
+        let mut stdout = io::stdout();
+
+        let lines = profile_card_lines(channel_name, profile);
+        for (i, line) in lines.iter().enumerate() {
+            queue!(stdout, cursor::MoveTo(0, i as u16), Print(line))?;
+        }
+
+        queue!(
+            stdout,
+            cursor::MoveTo(0, lines.len() as u16 + 1),
+            Print("Press any key to start watching, [Q] to exit"),
+        )?;
+        stdout.flush()?;
+        Ok(())
+    }
+
     pub fn display_channel_ascii(
         title: &str,
         ascii_art: &str,
@@ -145,6 +168,8 @@ impl UI {
             cursor::MoveTo(0, bottom_y + 6),
             Print("[S] Copy links to clipboard"),
             cursor::MoveTo(0, bottom_y + 7),
+            Print("[C] Comments"),
+            cursor::MoveTo(0, bottom_y + 8),
             Print("[Q] Exit"),
         )?;
         stdout.flush()?;
@@ -168,4 +193,14 @@ impl UI {
         Ok(())
     }
 
+    /// Draws the comment pane under the key legend of `display_channel_ascii`
+    pub fn display_comment_pane(pane: &CommentPane) -> io::Result<()> {
+        let mut stdout = io::stdout();
+        let top = 5 + TV_HEIGHT as u16 + 10;
+
+        for (i, line) in pane.lines().iter().enumerate() {
+            queue!(stdout, cursor::MoveTo(0, top + i as u16), Print(line))?;
+        }
+        stdout.flush()
+    }
 }
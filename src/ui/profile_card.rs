@@ -0,0 +1,138 @@
+use crate::api::ChannelProfile;
+use crate::ascii_art_converter::{AsciiArtSheet, TV_WIDTH};
+
+/// Same width as the TV frame, so the card lines up with what comes next
+pub const CARD_WIDTH: usize = TV_WIDTH + 4;
+const INNER_WIDTH: usize = CARD_WIDTH - 4;
+
+/// Lines of the bordered card shown before a channel starts playing.
+/// Every line is exactly `CARD_WIDTH` characters wide.
+pub fn profile_card_lines(channel_name: &str, profile: &ChannelProfile) -> Vec<String> {
+    let avatar: Vec<String> = profile
+        .avatar
+        .as_deref()
+        .and_then(|art| AsciiArtSheet::from_string(art).ok())
+        .and_then(|sheet| sheet.frames.into_iter().next())
+        .map(|frame| frame.lines().map(str::to_string).collect())
+        .unwrap_or_default();
+    let avatar_width = avatar.first().map(|line| line.chars().count()).unwrap_or(0);
+
+    let mut heading = Vec::new();
+    if let Some(display_name) = &profile.display_name {
+        heading.push(display_name.clone());
+    }
+    heading.push(format!("@{channel_name}"));
+
+    let mut body = Vec::new();
+
+    // Avatar on the left, name and handle next to it
+    let gap = if avatar_width > 0 { 2 } else { 0 };
+    let text_width = INNER_WIDTH.saturating_sub(avatar_width + gap);
+    for row in 0..avatar.len().max(heading.len()) {
+        let left = avatar.get(row).cloned().unwrap_or_else(|| " ".repeat(avatar_width));
+        let right = heading.get(row).map(|text| truncate(text, text_width)).unwrap_or_default();
+        body.push(format!("{left}{}{right}", " ".repeat(gap)));
+    }
+
+    if let Some(bio) = &profile.bio {
+        body.push(String::new());
+        for paragraph in bio.lines() {
+            body.extend(wrap(paragraph, INNER_WIDTH));
+        }
+    }
+
+    if !profile.links.is_empty() {
+        body.push(String::new());
+        for link in &profile.links {
+            body.push(truncate(&format!("→ {link}"), INNER_WIDTH));
+        }
+    }
+
+    let border = "─".repeat(CARD_WIDTH - 2);
+    let mut lines = vec![format!("╭{border}╮")];
+    lines.extend(body.iter().map(|line| format!("│ {} │", pad(line, INNER_WIDTH))));
+    lines.push(format!("╰{border}╯"));
+    lines
+}
+
+pub(super) fn pad(text: &str, width: usize) -> String {
+    let length = text.chars().count();
+    format!("{text}{}", " ".repeat(width.saturating_sub(length)))
+}
+
+pub(super) fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(width.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Word wrap, words longer than a line are split
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+
+    for word in text.split_whitespace() {
+        let mut word: String = word.to_string();
+
+        while word.chars().count() > width {
+            if !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+            }
+            let head: String = word.chars().take(width).collect();
+            word = word.chars().skip(width).collect();
+            lines.push(head);
+        }
+
+        let needed = if current.is_empty() { 0 } else { current.chars().count() + 1 };
+        if needed + word.chars().count() > width {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(&word);
+    }
+
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_card_lines_have_constant_width() {
+        let profile = ChannelProfile {
+            display_name: Some("Neko".to_string()),
+            bio: Some("A cat that naps all day long and sometimes plays the piano at night".to_string()),
+            avatar: Some("Ascii Art Animation, 4x2\n⣿⠉⠉⣿\n⣿⣀⣀⣿".to_string()),
+            links: vec![format!("https://neko.example/{}", "a".repeat(60))],
+        };
+
+        let lines = profile_card_lines("neko", &profile);
+        assert!(lines.iter().all(|line| line.chars().count() == CARD_WIDTH), "{lines:#?}");
+        assert!(lines[1].starts_with("│ ⣿⠉⠉⣿  Neko"));
+        assert!(lines[2].starts_with("│ ⣿⣀⣀⣿  @neko"));
+        assert!(lines.iter().any(|line| line.contains('…')));
+    }
+
+    #[test]
+    fn test_card_without_profile_shows_handle() {
+        let lines = profile_card_lines("neko", &ChannelProfile::default());
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].contains("@neko"));
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap("one two three", 7), vec!["one two", "three"]);
+        assert_eq!(wrap("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("", 4), vec![""]);
+    }
+}